@@ -0,0 +1,381 @@
+//! # BIP-340 Schnorr signatures
+//!
+//! This module implements the Schnorr signatures of [BIP-340] as a ciphersuite of the
+//! Fiat-Shamir transformation, so that Bitcoin signatures can be produced and verified
+//! with the same tooling as other proofs of knowledge.
+//!
+//! - [`Bip340Schnorr`] is the Schnorr protocol for the discrete logarithm of an x-only public key,
+//!   built on a [`CanonicalLinearRelation`] over secp256k1. Its commitments have an even Y coordinate
+//!   and are serialized as their X coordinate, and its scalars are serialized in 32 big-endian bytes.
+//! - [`Bip340Codec`] derives challenges with the tagged hash `BIP0340/challenge` over SHA-256,
+//!   reduced modulo the order of secp256k1.
+//! - [`Bip340NonceRng`] derives the nonce of a signature from the secret key masked by
+//!   auxiliary randomness, the public key and the message, as in BIP-340.
+//!
+//! A proof bound to a message is the BIP-340 signature `R.x || s` of the message:
+//! [`Bip340Nizk::from_public_key`] and [`Bip340Nizk::from_secret_key`] create the [`Nizk`]
+//! of a public key, [`Nizk::prove_compact_with_message`] signs, and
+//! [`Nizk::verify_compact_with_message`] verifies BIP-340 signatures.
+//! As BIP-340 signatures contain the nonce, compact and batchable proofs are the same bytes.
+//!
+//! BIP-340 challenges only depend on the nonce, the public key and the message,
+//! and cannot bind a session identifier: a [`Nizk`] created with [`Nizk::new`] and a session identifier
+//! produces sound proofs bound to the session, which are not BIP-340 signatures.
+//!
+//! [BIP-340]: https://github.com/bitcoin/bips/blob/master/bip-0340.mediawiki
+
+use crate::errors::{Error, InvalidInstance};
+use crate::fiat_shamir::Nizk;
+use crate::linear_relation::{CanonicalLinearRelation, LinearRelation};
+use crate::schnorr_protocol::SchnorrProverState;
+use crate::signature::{sealed, SignatureCodec, SignatureNonceRng, SignatureScheme, ENCODING_SIZE};
+use crate::traits::{SigmaProtocol, SigmaProtocolBatchVerifier, VerificationEquation};
+use alloc::vec;
+use alloc::vec::Vec;
+use ff::PrimeField;
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::point::{AffineCoordinates, DecompactPoint};
+use k256::{AffinePoint, FieldBytes, ProjectivePoint, Scalar, U256};
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, Zeroizing};
+
+#[cfg(feature = "std")]
+use rand::{CryptoRng, RngCore};
+#[cfg(not(feature = "std"))]
+use rand_core::{CryptoRng, RngCore};
+
+/// Tag of the hash deriving challenges.
+const CHALLENGE_TAG: &[u8] = b"BIP0340/challenge";
+
+/// Tag of the hash masking the secret key with auxiliary randomness.
+const AUX_TAG: &[u8] = b"BIP0340/aux";
+
+/// Tag of the hash deriving nonces.
+const NONCE_TAG: &[u8] = b"BIP0340/nonce";
+
+/// A [`Nizk`] producing and verifying BIP-340 signatures.
+pub type Bip340Nizk = Nizk<Bip340Schnorr, Bip340Codec>;
+
+/// The codec of BIP-340 signatures, deriving the challenge
+/// `int(hash_BIP0340/challenge(R.x || P.x || m)) mod n` of the nonce `R` and the public key `P`.
+///
+/// Bytes squeezed beyond challenges, and IVs bound to a session, are tagged hashes
+/// with the tags `sigma-proofs/bip340/squeeze` and `sigma-proofs/bip340/iv`.
+pub type Bip340Codec = SignatureCodec<Bip340Schnorr>;
+
+/// The nonce generator of BIP-340 signatures.
+///
+/// The first 32 bytes of output are `hash_BIP0340/nonce(t || P.x || m)`,
+/// where `t` is the secret key XORed with `hash_BIP0340/aux(aux_rand)`.
+/// Passed to [`Nizk::prove_compact_with_message`], they make the signature
+/// the BIP-340 signature of `m` with the auxiliary randomness `aux_rand`.
+pub type Bip340NonceRng = SignatureNonceRng<Bip340Schnorr>;
+
+/// Returns the SHA-256 hasher with the prefix `SHA256(tag) || SHA256(tag)` of BIP-340 tagged hashes.
+fn tagged_hasher(tag: &[u8]) -> Sha256 {
+    let tag_hash = Sha256::digest(tag);
+    Sha256::new().chain_update(tag_hash).chain_update(tag_hash)
+}
+
+/// Computes `SHA256(SHA256(tag) || SHA256(tag) || data[0] || data[1] || ...)`.
+fn tagged_hash(tag: &[u8], data: &[&[u8]]) -> FieldBytes {
+    let mut hasher = tagged_hasher(tag);
+    for part in data {
+        hasher.update(part);
+    }
+    hasher.finalize()
+}
+
+/// Returns the X coordinate of a point, in 32 big-endian bytes.
+fn x_only(point: &ProjectivePoint) -> [u8; ENCODING_SIZE] {
+    point.to_affine().x().into()
+}
+
+fn has_even_y(point: &ProjectivePoint) -> bool {
+    !bool::from(point.to_affine().y_is_odd())
+}
+
+/// Returns the point with X coordinate `x` and an even Y coordinate, if any.
+fn lift_x(x: &[u8]) -> Option<ProjectivePoint> {
+    let x = FieldBytes::clone_from_slice(x.get(..ENCODING_SIZE)?);
+    let point: Option<AffinePoint> = AffinePoint::decompact(&x).into();
+    point.map(ProjectivePoint::from)
+}
+
+fn scalar_from_bytes(bytes: &[u8]) -> Option<Scalar> {
+    let bytes = FieldBytes::clone_from_slice(bytes.get(..ENCODING_SIZE)?);
+    Scalar::from_repr(bytes).into()
+}
+
+/// Returns the secret key negated if needed so that its public key has an even Y coordinate,
+/// and the x-only public key.
+fn even_y_secret_key(secret_key: &Scalar) -> (Scalar, [u8; ENCODING_SIZE]) {
+    let public_key = ProjectivePoint::GENERATOR * secret_key;
+    if has_even_y(&public_key) {
+        (*secret_key, x_only(&public_key))
+    } else {
+        (-*secret_key, x_only(&public_key))
+    }
+}
+
+/// The challenge `e` of a BIP-340 signature, a scalar of secp256k1.
+///
+/// It can only be obtained from [`Bip340Codec`], so that the BIP-340 challenge hash
+/// is the only way to challenge a [`Bip340Schnorr`] prover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bip340Challenge(Scalar);
+
+/// The Schnorr protocol for the discrete logarithm of an x-only public key, as in BIP-340.
+///
+/// The witness is the secret key returned by [`Bip340Schnorr::from_secret_key`],
+/// negated if needed so that the public key has an even Y coordinate.
+#[derive(Clone, Debug)]
+pub struct Bip340Schnorr {
+    relation: CanonicalLinearRelation<ProjectivePoint>,
+    public_key: [u8; ENCODING_SIZE],
+}
+
+impl Bip340Schnorr {
+    /// Creates the instance of an x-only public key.
+    ///
+    /// # Errors
+    /// Returns an error if the public key is not the X coordinate of a point of secp256k1.
+    pub fn from_public_key(public_key: &[u8; ENCODING_SIZE]) -> Result<Self, InvalidInstance> {
+        let point = lift_x(public_key)
+            .ok_or_else(|| InvalidInstance::new("The public key is not on the curve"))?;
+
+        let mut relation = LinearRelation::new();
+        let var_x = relation.allocate_scalar();
+        let var_G = relation.allocate_element();
+        let var_P = relation.allocate_eq(var_x * var_G);
+        relation.set_elements([(var_G, ProjectivePoint::GENERATOR), (var_P, point)]);
+
+        Ok(Self {
+            relation: relation.canonical()?,
+            public_key: *public_key,
+        })
+    }
+
+    /// Creates the instance of the public key of `secret_key`, and the corresponding witness.
+    ///
+    /// # Errors
+    /// Returns an error if the secret key is zero.
+    pub fn from_secret_key(secret_key: &Scalar) -> Result<(Self, Vec<Scalar>), InvalidInstance> {
+        if bool::from(secret_key.is_zero()) {
+            return Err(InvalidInstance::new("The secret key is zero"));
+        }
+        let (secret_key, public_key) = even_y_secret_key(secret_key);
+        Ok((Self::from_public_key(&public_key)?, vec![secret_key]))
+    }
+
+    /// Returns the x-only public key.
+    pub fn public_key(&self) -> [u8; ENCODING_SIZE] {
+        self.public_key
+    }
+}
+
+impl SigmaProtocol for Bip340Schnorr {
+    type Commitment = Vec<ProjectivePoint>;
+    type ProverState = SchnorrProverState<ProjectivePoint>;
+    type Response = Vec<Scalar>;
+    type Witness = Vec<Scalar>;
+    type Challenge = Bip340Challenge;
+
+    /// Commits to a nonce with an even Y coordinate.
+    ///
+    /// The nonce is read from 32 bytes of `rng`, as a big-endian integer reduced modulo the group order,
+    /// and negated if its commitment has an odd Y coordinate.
+    ///
+    /// # Errors
+    /// - [`Error::InvalidInstanceWitnessPair`] if the witness is not the secret key of the public key.
+    fn prover_commit(
+        &self,
+        witness: &Self::Witness,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(Self::Commitment, Self::ProverState), Error> {
+        if witness.len() != 1 || self.relation.evaluate(witness) != self.relation.image {
+            return Err(Error::InvalidInstanceWitnessPair);
+        }
+
+        let mut bytes = FieldBytes::default();
+        let mut nonce = Scalar::ZERO;
+        while bool::from(nonce.is_zero()) {
+            rng.fill_bytes(&mut bytes);
+            nonce = <Scalar as Reduce<U256>>::reduce_bytes(&bytes);
+        }
+        bytes.zeroize();
+
+        let mut commitment = self.relation.evaluate(&[nonce]);
+        if !has_even_y(&commitment[0]) {
+            nonce = -nonce;
+            commitment[0] = -commitment[0];
+        }
+        Ok((
+            commitment,
+            SchnorrProverState::new(vec![nonce], witness.clone()),
+        ))
+    }
+
+    fn prover_response(
+        &self,
+        prover_state: Self::ProverState,
+        challenge: &Self::Challenge,
+    ) -> Result<Self::Response, Error> {
+        self.relation.prover_response(prover_state, &challenge.0)
+    }
+
+    /// Verifies the proof, rejecting commitments with an odd Y coordinate.
+    fn verifier(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Self::Challenge,
+        response: &Self::Response,
+    ) -> Result<(), Error> {
+        if !commitment.iter().all(has_even_y) {
+            return Err(Error::VerificationFailure);
+        }
+        self.relation.verifier(commitment, &challenge.0, response)
+    }
+
+    /// Serializes the commitment as the X coordinate of the nonce point.
+    fn serialize_commitment(&self, commitment: &Self::Commitment) -> Vec<u8> {
+        commitment.iter().flat_map(x_only).collect()
+    }
+
+    /// Serializes the challenge in 32 big-endian bytes.
+    fn serialize_challenge(&self, challenge: &Self::Challenge) -> Vec<u8> {
+        challenge.0.to_repr().to_vec()
+    }
+
+    /// Serializes the response in 32 big-endian bytes.
+    fn serialize_response(&self, response: &Self::Response) -> Vec<u8> {
+        response.iter().flat_map(|s| s.to_repr()).collect()
+    }
+
+    /// Deserializes the commitment from the X coordinate of the nonce point,
+    /// which is lifted to the point with an even Y coordinate.
+    fn deserialize_commitment(&self, data: &[u8]) -> Result<Self::Commitment, Error> {
+        lift_x(data)
+            .map(|point| vec![point])
+            .ok_or(Error::VerificationFailure)
+    }
+
+    fn deserialize_challenge(&self, data: &[u8]) -> Result<Self::Challenge, Error> {
+        scalar_from_bytes(data)
+            .map(Bip340Challenge)
+            .ok_or(Error::VerificationFailure)
+    }
+
+    fn deserialize_response(&self, data: &[u8]) -> Result<Self::Response, Error> {
+        scalar_from_bytes(data)
+            .map(|s| vec![s])
+            .ok_or(Error::VerificationFailure)
+    }
+
+    /// The instance label is the x-only public key.
+    fn instance_label(&self) -> impl AsRef<[u8]> {
+        self.public_key
+    }
+
+    fn protocol_identifier(&self) -> impl AsRef<[u8]> {
+        CHALLENGE_TAG
+    }
+
+    fn serialize_witness(&self, witness: &Self::Witness) -> Option<Zeroizing<Vec<u8>>> {
+        Some(Zeroizing::new(self.serialize_response(witness)))
+    }
+}
+
+impl SignatureScheme for Bip340Schnorr {}
+
+impl sealed::Scheme for Bip340Schnorr {
+    type SecretKey = Scalar;
+    type Hash = Sha256;
+
+    const PROTOCOL_IDENTIFIER: &'static [u8] = CHALLENGE_TAG;
+    const CHALLENGE_DOMAIN: &'static [u8] = CHALLENGE_TAG;
+    const SQUEEZE_DOMAIN: &'static [u8] = b"sigma-proofs/bip340/squeeze";
+    const IV_DOMAIN: &'static [u8] = b"sigma-proofs/bip340/iv";
+
+    /// Returns the hasher of the tagged hash with the tag `domain`.
+    fn hasher(domain: &[u8]) -> Sha256 {
+        tagged_hasher(domain)
+    }
+
+    fn challenge_from_hash(hash: FieldBytes) -> Bip340Challenge {
+        Bip340Challenge(<Scalar as Reduce<U256>>::reduce_bytes(&hash))
+    }
+
+    fn from_public_key(public_key: &[u8; ENCODING_SIZE]) -> Result<Self, InvalidInstance> {
+        Self::from_public_key(public_key)
+    }
+
+    fn from_secret_key(secret_key: &Scalar) -> Result<(Self, Vec<Scalar>), InvalidInstance> {
+        Self::from_secret_key(secret_key)
+    }
+}
+
+impl SigmaProtocolBatchVerifier for Bip340Schnorr {
+    type Group = ProjectivePoint;
+
+    fn verification_equations(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Self::Challenge,
+        response: &Self::Response,
+    ) -> Result<Vec<VerificationEquation<ProjectivePoint>>, Error> {
+        if !commitment.iter().all(has_even_y) {
+            return Err(Error::VerificationFailure);
+        }
+        self.relation
+            .verification_equations(commitment, &challenge.0, response)
+    }
+}
+
+impl Nizk<Bip340Schnorr, Bip340Codec> {
+    /// Produces the BIP-340 signature `R.x || s`, the same bytes as [`Nizk::prove_batchable`].
+    pub fn prove_compact(
+        &self,
+        witness: &Vec<Scalar>,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Vec<u8>, Error> {
+        self.prove_batchable(witness, rng)
+    }
+
+    /// Produces the BIP-340 signature `R.x || s` of `message`,
+    /// the same bytes as [`Nizk::prove_batchable_with_message`].
+    ///
+    /// With the nonces of [`Bip340NonceRng`], this is the signature specified by BIP-340.
+    pub fn prove_compact_with_message(
+        &self,
+        witness: &Vec<Scalar>,
+        message: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Vec<u8>, Error> {
+        self.prove_batchable_with_message(witness, message, rng)
+    }
+
+    /// Verifies a BIP-340 signature `R.x || s`, as [`Nizk::verify_batchable`].
+    pub fn verify_compact(&self, proof: &[u8]) -> Result<(), Error> {
+        self.verify_batchable(proof)
+    }
+
+    /// Verifies the BIP-340 signature `R.x || s` of `message`, as [`Nizk::verify_batchable_with_message`].
+    pub fn verify_compact_with_message(&self, proof: &[u8], message: &[u8]) -> Result<(), Error> {
+        self.verify_batchable_with_message(proof, message)
+    }
+}
+
+impl SignatureNonceRng<Bip340Schnorr> {
+    /// Seeds the generator of the nonce signing `message` with `secret_key`.
+    pub fn new(secret_key: &Scalar, message: &[u8], aux_rand: &[u8; ENCODING_SIZE]) -> Self {
+        let (secret_key, public_key) = even_y_secret_key(secret_key);
+        let mut t = secret_key.to_repr();
+        for (t, mask) in t.iter_mut().zip(tagged_hash(AUX_TAG, &[aux_rand])) {
+            *t ^= mask;
+        }
+        let seed = tagged_hash(NONCE_TAG, &[&t, &public_key, message]);
+        t.zeroize();
+        Self::from_seed(seed)
+    }
+}
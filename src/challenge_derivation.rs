@@ -0,0 +1,250 @@
+//! Strategies deriving challenges from the output of a duplex sponge.
+//!
+//! A [`ChallengeDerivation`] maps the output stream of the sponge of a
+//! [`ByteSchnorrCodec`](crate::codec::ByteSchnorrCodec) to a scalar:
+//! - [`ModularReduction`] reduces 16 bytes more than the size of a scalar modulo the group order,
+//!   and is the default,
+//! - [`UniformBytesReduction`] computes the same challenges as [`ModularReduction`] with
+//!   [`FromUniformBytes`], for fields implementing it,
+//! - [`HashToField`] expands a seed with `expand_message_xmd` or `expand_message_xof`,
+//!   as `hash_to_field` in RFC 9380,
+//! - [`RejectionSampling`] reads scalars from the output until one is canonical.
+
+use crate::duplex_sponge::DuplexSpongeInterface;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use digest::core_api::BlockSizeUser;
+use digest::{Digest, ExtendableOutput, Update, XofReader};
+use ff::{FromUniformBytes, PrimeField};
+
+/// Domain separation tag of the challenges derived by [`HashToField`].
+pub const HASH_TO_FIELD_DST: &[u8] = b"sigma-proofs-challenge-V01";
+
+/// Size in bytes of the seed squeezed from the sponge by [`HashToField`].
+pub const HASH_TO_FIELD_SEED_SIZE: usize = 32;
+
+/// A strategy deriving challenges in the field `F` from the output of a duplex sponge.
+pub trait ChallengeDerivation<F: PrimeField> {
+    /// Identifies the strategy in the IV of the codec,
+    /// so that codecs deriving challenges differently never share an IV, nor deterministic nonces.
+    ///
+    /// The identifier of the default [`ModularReduction`] is empty, and leaves the IV unchanged.
+    fn identifier() -> Vec<u8>;
+
+    /// Squeezes bytes from `sponge` and maps them to a challenge.
+    fn derive_challenge<H: DuplexSpongeInterface>(sponge: &mut H) -> F;
+}
+
+/// Reduces 16 bytes more than the size of a scalar, read as a big-endian integer, modulo the group order.
+///
+/// This is the `hash_to_field` reduction of RFC 9380 applied directly to the output of the sponge.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ModularReduction;
+
+impl<F: PrimeField> ChallengeDerivation<F> for ModularReduction {
+    fn identifier() -> Vec<u8> {
+        Vec::new()
+    }
+
+    fn derive_challenge<H: DuplexSpongeInterface>(sponge: &mut H) -> F {
+        // The output is absorbed into the scalar 8 bytes at a time, without buffering it.
+        let length = uniform_scalar_length::<F>();
+        let shift = F::from_u128(1 << 64);
+        let mut chunk = [0u8; 8];
+        let first = length % 8;
+        sponge.squeeze_into(&mut chunk[8 - first..]);
+        let mut scalar = F::from(u64::from_be_bytes(chunk));
+        for _ in 0..length / 8 {
+            sponge.squeeze_into(&mut chunk);
+            scalar = scalar * shift + F::from(u64::from_be_bytes(chunk));
+        }
+        scalar
+    }
+}
+
+/// Computes the challenges of [`ModularReduction`] with [`FromUniformBytes::from_uniform_bytes`].
+///
+/// This avoids the generic reduction for fields with a fast wide reduction,
+/// and falls back to [`ModularReduction`] if a scalar needs more than the 64 uniform bytes it reduces.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UniformBytesReduction;
+
+impl<F: PrimeField + FromUniformBytes<64>> ChallengeDerivation<F> for UniformBytesReduction {
+    fn identifier() -> Vec<u8> {
+        b"uniform-bytes-reduction".to_vec()
+    }
+
+    fn derive_challenge<H: DuplexSpongeInterface>(sponge: &mut H) -> F {
+        let length = uniform_scalar_length::<F>();
+        if length > 64 {
+            return ModularReduction::derive_challenge(sponge);
+        }
+        let mut bytes = [0u8; 64];
+        sponge.squeeze_into(&mut bytes[..length]);
+        bytes[..length].reverse();
+        F::from_uniform_bytes(&bytes)
+    }
+}
+
+/// Derives challenges with `hash_to_field` of RFC 9380, with the expander `E`.
+///
+/// The message is a seed of [`HASH_TO_FIELD_SEED_SIZE`] bytes squeezed from the sponge,
+/// and the domain separation tag is [`HASH_TO_FIELD_DST`].
+pub struct HashToField<E: ExpandMessage>(PhantomData<E>);
+
+impl<E: ExpandMessage> Clone for HashToField<E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<E: ExpandMessage> Copy for HashToField<E> {}
+
+impl<E: ExpandMessage> Default for HashToField<E> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<E: ExpandMessage> core::fmt::Debug for HashToField<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("HashToField")
+    }
+}
+
+impl<F: PrimeField, E: ExpandMessage> ChallengeDerivation<F> for HashToField<E> {
+    /// Identifies the expander by its expansion of an empty message under [`HASH_TO_FIELD_DST`].
+    fn identifier() -> Vec<u8> {
+        let mut identifier = b"hash-to-field/".to_vec();
+        identifier.extend(E::expand_message(b"", HASH_TO_FIELD_DST, 32));
+        identifier
+    }
+
+    fn derive_challenge<H: DuplexSpongeInterface>(sponge: &mut H) -> F {
+        let mut seed = [0u8; HASH_TO_FIELD_SEED_SIZE];
+        sponge.squeeze_into(&mut seed);
+        hash_to_field::<F, E>(&seed, HASH_TO_FIELD_DST)
+    }
+}
+
+/// Reads scalars of the size of the group order from the output, masking the bits above the order,
+/// until one of them is smaller than the order.
+///
+/// Challenges are exactly uniform, but the number of bytes squeezed depends on their value.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RejectionSampling;
+
+impl<F: PrimeField> ChallengeDerivation<F> for RejectionSampling {
+    fn identifier() -> Vec<u8> {
+        b"rejection-sampling".to_vec()
+    }
+
+    fn derive_challenge<H: DuplexSpongeInterface>(sponge: &mut H) -> F {
+        let length = (F::NUM_BITS as usize).div_ceil(8);
+        let mask = 0xff >> (8 * length - F::NUM_BITS as usize);
+        loop {
+            let mut repr = F::Repr::default();
+            let bytes = &mut repr.as_mut()[..length];
+            sponge.squeeze_into(bytes);
+            bytes[0] &= mask;
+            bytes.reverse();
+            if let Some(scalar) = F::from_repr(repr).into() {
+                return scalar;
+            }
+        }
+    }
+}
+
+/// Number of uniform bytes reduced into a scalar, so that the result is statistically close to uniform.
+pub(crate) fn uniform_scalar_length<F: PrimeField>() -> usize {
+    (F::NUM_BITS as usize).div_ceil(8) + 16
+}
+
+/// Reduces big-endian bytes modulo the field order.
+pub(crate) fn reduce_be_bytes<F: PrimeField>(bytes: &[u8]) -> F {
+    let shift = F::from(256);
+    bytes.iter().fold(F::ZERO, |scalar, &byte| {
+        scalar * shift + F::from(byte as u64)
+    })
+}
+
+/// `hash_to_field` of RFC 9380, for a single element of a prime field.
+pub fn hash_to_field<F: PrimeField, E: ExpandMessage>(message: &[u8], dst: &[u8]) -> F {
+    let uniform_bytes = E::expand_message(message, dst, uniform_scalar_length::<F>());
+    reduce_be_bytes(&uniform_bytes)
+}
+
+/// An `expand_message` function of RFC 9380.
+pub trait ExpandMessage {
+    /// Expands `message` into `length` uniform bytes, under the domain separation tag `dst`.
+    ///
+    /// # Panics
+    /// Panics if `dst` is longer than 255 bytes, or if `length` is too large for the expander.
+    fn expand_message(message: &[u8], dst: &[u8], length: usize) -> Vec<u8>;
+}
+
+/// `expand_message_xmd` of RFC 9380, over the hash function `D`.
+pub struct ExpandMsgXmd<D>(PhantomData<D>);
+
+impl<D: Digest + BlockSizeUser> ExpandMessage for ExpandMsgXmd<D> {
+    fn expand_message(message: &[u8], dst: &[u8], length: usize) -> Vec<u8> {
+        let output_size = <D as Digest>::output_size();
+        let ell = length.div_ceil(output_size);
+        assert!(dst.len() <= 255, "the DST must be at most 255 bytes");
+        assert!(
+            ell <= 255 && length <= u16::MAX as usize,
+            "requested too many bytes"
+        );
+        let dst_length = [dst.len() as u8];
+
+        let b_0 = D::new()
+            .chain_update(vec![0u8; D::block_size()])
+            .chain_update(message)
+            .chain_update((length as u16).to_be_bytes())
+            .chain_update([0u8])
+            .chain_update(dst)
+            .chain_update(dst_length)
+            .finalize();
+        let mut b_i = D::new()
+            .chain_update(&b_0)
+            .chain_update([1u8])
+            .chain_update(dst)
+            .chain_update(dst_length)
+            .finalize();
+
+        let mut uniform_bytes = Vec::with_capacity(ell * output_size);
+        uniform_bytes.extend_from_slice(&b_i);
+        for i in 2..=ell {
+            let xored: Vec<u8> = b_0.iter().zip(b_i.iter()).map(|(a, b)| a ^ b).collect();
+            b_i = D::new()
+                .chain_update(xored)
+                .chain_update([i as u8])
+                .chain_update(dst)
+                .chain_update(dst_length)
+                .finalize();
+            uniform_bytes.extend_from_slice(&b_i);
+        }
+        uniform_bytes.truncate(length);
+        uniform_bytes
+    }
+}
+
+/// `expand_message_xof` of RFC 9380, over the extendable-output function `X`.
+pub struct ExpandMsgXof<X>(PhantomData<X>);
+
+impl<X: Default + Update + ExtendableOutput> ExpandMessage for ExpandMsgXof<X> {
+    fn expand_message(message: &[u8], dst: &[u8], length: usize) -> Vec<u8> {
+        assert!(dst.len() <= 255, "the DST must be at most 255 bytes");
+        assert!(length <= u16::MAX as usize, "requested too many bytes");
+
+        let mut xof = X::default();
+        xof.update(message);
+        xof.update(&(length as u16).to_be_bytes());
+        xof.update(dst);
+        xof.update(&[dst.len() as u8]);
+        let mut uniform_bytes = vec![0u8; length];
+        xof.finalize_xof().read(&mut uniform_bytes);
+        uniform_bytes
+    }
+}
@@ -1,29 +1,193 @@
 //! Encoding and decoding utilities for Fiat-Shamir and group operations.
 
+pub use crate::challenge_derivation::{
+    hash_to_field, ChallengeDerivation, ExpandMessage, ExpandMsgXmd, ExpandMsgXof, HashToField,
+    ModularReduction, RejectionSampling, UniformBytesReduction, HASH_TO_FIELD_DST,
+    HASH_TO_FIELD_SEED_SIZE,
+};
+use crate::challenge_derivation::{reduce_be_bytes, uniform_scalar_length};
 use crate::duplex_sponge::DuplexSpongeInterface;
-pub use crate::duplex_sponge::{keccak::KeccakDuplexSponge, shake::ShakeDuplexSponge};
-use alloc::vec;
+pub use crate::duplex_sponge::{
+    ascon::AsconDuplexSponge,
+    digest::DigestDuplexSponge,
+    keccak::KeccakDuplexSponge,
+    poseidon::{PoseidonDuplexSponge, PoseidonParameters},
+    shake::{ShakeDuplexSponge, ShakeRatchetDuplexSponge},
+    xof::XofDuplexSponge,
+};
+use crate::errors::Error;
+use crate::fiat_shamir::MESSAGE_DOMAIN_SEPARATOR;
+use crate::group::serialization::{serialize_elements, serialize_scalars};
+use alloc::vec::Vec;
 use ff::PrimeField;
 use group::prime::PrimeGroup;
 use num_bigint::BigUint;
 use num_traits::identities::One;
 
+#[cfg(feature = "std")]
+use rand::{CryptoRng, RngCore};
+#[cfg(not(feature = "std"))]
+use rand_core::{CryptoRng, RngCore};
+
+/// Size in bytes of a challenge in [`ChallengeSpace::Short`].
+pub const SHORT_CHALLENGE_SIZE: usize = 16;
+
+/// The set challenges are sampled from.
+///
+/// Challenges are always represented as scalars.
+/// The challenge space determines their distribution, their serialization,
+/// and how the challenges of the branches of an OR composition are combined.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChallengeSpace {
+    /// Challenges are uniformly random scalars, serialized as scalars.
+    ///
+    /// The challenges of the branches of an OR composition sum to the challenge of the composition.
+    #[default]
+    Full,
+    /// Challenges are uniformly random 128-bit integers, serialized in [`SHORT_CHALLENGE_SIZE`] big-endian bytes.
+    ///
+    /// This is enough for 128-bit security, and makes compact proofs and OR compositions smaller.
+    /// The challenges of the branches of an OR composition XOR to the challenge of the composition.
+    /// The scalar field must have more than 128 bits.
+    Short,
+}
+
+impl ChallengeSpace {
+    /// Returns the size in bytes of a serialized challenge.
+    pub fn challenge_size<F: PrimeField>(&self) -> usize {
+        match self {
+            ChallengeSpace::Full => F::Repr::default().as_ref().len(),
+            ChallengeSpace::Short => SHORT_CHALLENGE_SIZE,
+        }
+    }
+
+    /// Samples a challenge uniformly at random.
+    pub fn random<F: PrimeField>(&self, rng: &mut (impl RngCore + CryptoRng)) -> F {
+        match self {
+            ChallengeSpace::Full => F::random(rng),
+            ChallengeSpace::Short => {
+                let mut bytes = [0u8; SHORT_CHALLENGE_SIZE];
+                rng.fill_bytes(&mut bytes);
+                F::from_u128(u128::from_be_bytes(bytes))
+            }
+        }
+    }
+
+    /// Serializes a challenge to bytes.
+    ///
+    /// Full challenges are serialized as scalars, with the bytes of their representation reversed.
+    /// Short challenges are serialized as 128-bit big-endian integers, whatever the field.
+    pub fn serialize<F: PrimeField>(&self, challenge: &F) -> Vec<u8> {
+        match self {
+            ChallengeSpace::Full => {
+                let mut bytes = challenge.to_repr().as_ref().to_vec();
+                bytes.reverse();
+                bytes
+            }
+            ChallengeSpace::Short => short_challenge_bits(challenge).to_be_bytes().to_vec(),
+        }
+    }
+
+    /// Deserializes a challenge from the first [`ChallengeSpace::challenge_size`] bytes of `data`.
+    ///
+    /// Returns `None` if `data` is too short or does not encode a challenge.
+    pub fn deserialize<F: PrimeField>(&self, data: &[u8]) -> Option<F> {
+        let size = self.challenge_size::<F>();
+        let bytes = data.get(..size)?;
+        match self {
+            ChallengeSpace::Full => {
+                let mut repr = F::Repr::default();
+                repr.as_mut().copy_from_slice(bytes);
+                repr.as_mut().reverse();
+                F::from_repr(repr).into()
+            }
+            ChallengeSpace::Short => {
+                Some(F::from_u128(u128::from_be_bytes(bytes.try_into().ok()?)))
+            }
+        }
+    }
+
+    /// Combines two challenges: their sum for [`ChallengeSpace::Full`], their XOR for [`ChallengeSpace::Short`].
+    ///
+    /// Combining is its own inverse for short challenges: see [`ChallengeSpace::remove`].
+    pub(crate) fn combine<F: PrimeField>(&self, a: &F, b: &F) -> F {
+        match self {
+            ChallengeSpace::Full => *a + b,
+            ChallengeSpace::Short => {
+                F::from_u128(short_challenge_bits(a) ^ short_challenge_bits(b))
+            }
+        }
+    }
+
+    /// Removes challenge `b` from the combination `a`, such that `combine(remove(a, b), b) == a`.
+    pub(crate) fn remove<F: PrimeField>(&self, a: &F, b: &F) -> F {
+        match self {
+            ChallengeSpace::Full => *a - b,
+            ChallengeSpace::Short => {
+                F::from_u128(short_challenge_bits(a) ^ short_challenge_bits(b))
+            }
+        }
+    }
+}
+
+/// Returns the low 128 bits of a scalar, which are all of its bits for a short challenge.
+///
+/// The endianness of [`PrimeField::Repr`] depends on the field (e.g. little-endian for BLS12-381,
+/// big-endian for secp256k1): it is detected from the representation of one.
+fn short_challenge_bits<F: PrimeField>(challenge: &F) -> u128 {
+    let repr = challenge.to_repr();
+    let repr = repr.as_ref();
+    let mut bytes = [0u8; SHORT_CHALLENGE_SIZE];
+    if F::ONE.to_repr().as_ref()[0] == 1 {
+        bytes.copy_from_slice(&repr[..SHORT_CHALLENGE_SIZE]);
+        u128::from_le_bytes(bytes)
+    } else {
+        bytes.copy_from_slice(&repr[repr.len() - SHORT_CHALLENGE_SIZE..]);
+        u128::from_be_bytes(bytes)
+    }
+}
+
 /// A trait defining the behavior of a domain-separated codec hashing, which is typically used for [`crate::traits::SigmaProtocol`]s.
 ///
 /// A domain-separated hashing codec is a codec, identified by a domain, which is incremented with successive messages ("absorb"). The codec can then output a bit stream of any length, which is typically used to generate a challenge unique to the given codec ("squeeze"). (See Sponge Construction).
 ///
 /// The output is deterministic for a given set of input. Thus, both Prover and Verifier can generate the codec on their sides and ensure the same inputs have been used in both side of the protocol.
 ///
+/// Besides raw bytes, the codec can absorb group elements and scalars with the encodings of
+/// [`crate::group::serialization`], labels separating the messages of a protocol,
+/// and squeeze several scalars or uniform bytes, for protocols deriving more than one challenge.
+///
 /// ## Minimal Implementation
 /// Types implementing [`Codec`] must define:
-/// - `new`
+/// - `iv`
+/// - `from_iv`
 /// - `prover_message`
 /// - `verifier_challenge`
+/// - `verifier_challenge_in`
+/// - `squeeze_bytes`
 pub trait Codec {
     type Challenge;
 
     /// Generates an empty codec that can be identified by a domain separator.
-    fn new(protocol_identifier: &[u8], session_identifier: &[u8], instance_label: &[u8]) -> Self;
+    ///
+    /// The default implementation initializes the codec with the IV computed by [`Codec::iv`].
+    fn new(protocol_identifier: &[u8], session_identifier: &[u8], instance_label: &[u8]) -> Self
+    where
+        Self: Sized,
+    {
+        Self::from_iv(Self::iv(
+            protocol_identifier,
+            session_identifier,
+            instance_label,
+        ))
+    }
+
+    /// Computes the IV of the codec identified by a domain separator,
+    /// which can be precomputed and passed to [`Codec::from_iv`].
+    fn iv(protocol_identifier: &[u8], session_identifier: &[u8], instance_label: &[u8]) -> [u8; 64]
+    where
+        Self: Sized;
 
     /// Allows for precomputed initialization of the codec with a specific IV.
     fn from_iv(iv: [u8; 64]) -> Self;
@@ -33,25 +197,95 @@ pub trait Codec {
 
     /// Produces a scalar that can be used as a challenge from the codec.
     fn verifier_challenge(&mut self) -> Self::Challenge;
+
+    /// Produces a challenge in `challenge_space` from the codec.
+    ///
+    /// [`ChallengeSpace::Short`] challenges must fit in [`SHORT_CHALLENGE_SIZE`] bytes,
+    /// so that compact proofs and OR compositions can serialize them.
+    fn verifier_challenge_in(&mut self, challenge_space: ChallengeSpace) -> Self::Challenge;
+
+    /// Squeezes `length` uniformly random bytes from the codec.
+    fn squeeze_bytes(&mut self, length: usize) -> Vec<u8>;
+
+    /// Absorbs a label separating the messages of a protocol.
+    ///
+    /// The label is prefixed by its length as a 32-bit big-endian integer.
+    fn absorb_label(&mut self, label: &[u8]) {
+        self.prover_message(&length_to_bytes(label.len()));
+        self.prover_message(label);
+    }
+
+    /// Absorbs the message a proof is bound to, before any prover message.
+    ///
+    /// The default implementation absorbs the domain separator `"sigma-proofs/message"`,
+    /// the length of the message as a 64-bit big-endian integer, and the message.
+    /// Codecs of signature schemes override it to hash the message in the position of the scheme.
+    fn absorb_message(&mut self, message: &[u8]) {
+        self.prover_message(MESSAGE_DOMAIN_SEPARATOR);
+        self.prover_message(&(message.len() as u64).to_be_bytes());
+        self.prover_message(message);
+    }
+
+    /// Absorbs group elements, serialized with [`serialize_elements`].
+    fn absorb_elements<G: PrimeGroup>(&mut self, elements: &[G]) {
+        self.prover_message(&serialize_elements(elements));
+    }
+
+    /// Absorbs scalars, serialized with [`serialize_scalars`].
+    fn absorb_scalars<G: PrimeGroup>(&mut self, scalars: &[G::Scalar]) {
+        self.prover_message(&serialize_scalars::<G>(scalars));
+    }
+
+    /// Squeezes `count` uniformly random scalars from the codec.
+    ///
+    /// Each scalar is read from 16 bytes more than the size of a scalar,
+    /// interpreted as a big-endian integer and reduced modulo the group order.
+    fn squeeze_scalars<G: PrimeGroup>(&mut self, count: usize) -> Vec<G::Scalar> {
+        let length = uniform_scalar_length::<G::Scalar>();
+        self.squeeze_bytes(count * length)
+            .chunks_exact(length)
+            .map(reduce_be_bytes)
+            .collect()
+    }
+
+    /// Exports a versioned snapshot of the codec state, from which [`Codec::import_state`] resumes the codec.
+    ///
+    /// This allows a partially absorbed transcript to be persisted, and finished by another party.
+    /// Returns [`Error::UnsupportedSnapshot`] if the codec cannot export its state.
+    fn export_state(&self) -> Result<Vec<u8>, Error> {
+        Err(Error::UnsupportedSnapshot)
+    }
+
+    /// Restores a codec from a snapshot exported by [`Codec::export_state`].
+    ///
+    /// Returns [`Error::InvalidSnapshot`] if the snapshot is truncated, corrupted,
+    /// or was exported by another kind of codec.
+    fn import_state(_snapshot: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Err(Error::UnsupportedSnapshot)
+    }
 }
 
-fn cardinal<F: PrimeField>() -> BigUint {
+pub(crate) fn cardinal<F: PrimeField>() -> BigUint {
     let bytes = (F::ZERO - F::ONE).to_repr();
     BigUint::from_bytes_le(bytes.as_ref()) + BigUint::one()
 }
 
 /// A byte-level Schnorr codec that works with any duplex sponge.
 ///
-/// This codec is generic over both the group `G` and the hash function `H`.
+/// This codec is generic over the group `G`, the hash function `H`,
+/// and the strategy `D` deriving challenges from the output of the sponge (see [`ChallengeDerivation`]).
 /// It can be used with different duplex sponge implementations.
 #[derive(Clone)]
-pub struct ByteSchnorrCodec<G, H>
+pub struct ByteSchnorrCodec<G, H, D = ModularReduction>
 where
     G: PrimeGroup,
     H: DuplexSpongeInterface,
 {
     hasher: H,
-    _marker: core::marker::PhantomData<G>,
+    _marker: core::marker::PhantomData<(G, D)>,
 }
 
 const WORD_SIZE: usize = 4;
@@ -69,26 +303,35 @@ pub fn compute_iv<H: DuplexSpongeInterface>(
     session_id: &[u8],
     instance_label: &[u8],
 ) -> [u8; 64] {
+    iv_from_fields::<H>(&[protocol_id, session_id, instance_label])
+}
+
+/// Absorbs each field prefixed by its length into a fresh sponge, and squeezes the IV.
+fn iv_from_fields<H: DuplexSpongeInterface>(fields: &[&[u8]]) -> [u8; 64] {
     let mut tmp = H::new([0u8; 64]);
-    tmp.absorb(&length_to_bytes(protocol_id.len()));
-    tmp.absorb(protocol_id);
-    tmp.absorb(&length_to_bytes(session_id.len()));
-    tmp.absorb(session_id);
-    tmp.absorb(&length_to_bytes(instance_label.len()));
-    tmp.absorb(instance_label);
+    for field in fields {
+        tmp.absorb(&length_to_bytes(field.len()));
+        tmp.absorb(field);
+    }
     tmp.squeeze(64).try_into().unwrap()
 }
 
-impl<G, H> Codec for ByteSchnorrCodec<G, H>
+impl<G, H, D> Codec for ByteSchnorrCodec<G, H, D>
 where
     G: PrimeGroup,
     H: DuplexSpongeInterface,
+    D: ChallengeDerivation<G::Scalar>,
 {
     type Challenge = G::Scalar;
 
-    fn new(protocol_id: &[u8], session_id: &[u8], instance_label: &[u8]) -> Self {
-        let iv = compute_iv::<H>(protocol_id, session_id, instance_label);
-        Self::from_iv(iv)
+    /// Computes the IV with [`compute_iv`], followed by the identifier of `D` if it is not empty
+    /// (see [`ChallengeDerivation::identifier`]).
+    fn iv(protocol_id: &[u8], session_id: &[u8], instance_label: &[u8]) -> [u8; 64] {
+        let derivation = D::identifier();
+        if derivation.is_empty() {
+            return compute_iv::<H>(protocol_id, session_id, instance_label);
+        }
+        iv_from_fields::<H>(&[protocol_id, session_id, instance_label, &derivation])
     }
 
     fn from_iv(iv: [u8; 64]) -> Self {
@@ -103,23 +346,33 @@ where
     }
 
     fn verifier_challenge(&mut self) -> Self::Challenge {
-        #[allow(clippy::manual_div_ceil)]
-        let scalar_byte_length = (G::Scalar::NUM_BITS as usize + 7) / 8;
+        D::derive_challenge(&mut self.hasher)
+    }
 
-        let uniform_bytes = self.hasher.squeeze(scalar_byte_length + 16);
-        let scalar = BigUint::from_bytes_be(&uniform_bytes);
-        let reduced = scalar % cardinal::<G::Scalar>();
+    fn verifier_challenge_in(&mut self, challenge_space: ChallengeSpace) -> Self::Challenge {
+        match challenge_space {
+            ChallengeSpace::Full => self.verifier_challenge(),
+            ChallengeSpace::Short => {
+                let mut bytes = [0u8; SHORT_CHALLENGE_SIZE];
+                self.hasher.squeeze_into(&mut bytes);
+                G::Scalar::from_u128(u128::from_be_bytes(bytes))
+            }
+        }
+    }
 
-        let mut bytes = vec![0u8; scalar_byte_length];
-        let reduced_bytes = reduced.to_bytes_be();
-        let start = bytes.len() - reduced_bytes.len();
-        bytes[start..].copy_from_slice(&reduced_bytes);
-        bytes.reverse();
+    fn squeeze_bytes(&mut self, length: usize) -> Vec<u8> {
+        self.hasher.squeeze(length)
+    }
 
-        let mut repr = <G::Scalar as PrimeField>::Repr::default();
-        repr.as_mut().copy_from_slice(&bytes);
+    fn export_state(&self) -> Result<Vec<u8>, Error> {
+        self.hasher.export_state()
+    }
 
-        <G::Scalar as PrimeField>::from_repr(repr).expect("Error")
+    fn import_state(snapshot: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            hasher: H::import_state(snapshot)?,
+            _marker: core::marker::PhantomData,
+        })
     }
 }
 
@@ -129,3 +382,120 @@ pub type KeccakByteSchnorrCodec<G> = ByteSchnorrCodec<G, KeccakDuplexSponge>;
 
 /// Type alias for a SHAKE-based ByteSchnorrCodec.
 pub type Shake128DuplexSponge<G> = ByteSchnorrCodec<G, ShakeDuplexSponge>;
+
+/// Type alias for an Ascon-based ByteSchnorrCodec, for constrained devices.
+pub type AsconByteSchnorrCodec<G> = ByteSchnorrCodec<G, AsconDuplexSponge>;
+
+/// Converts scalars between two implementations of the same prime field.
+///
+/// Returns `None` if the fields differ.
+fn convert_scalars<F1: PrimeField, F2: PrimeField>(scalars: &[F1]) -> Option<Vec<F2>> {
+    if F1::MODULUS != F2::MODULUS {
+        return None;
+    }
+    scalars
+        .iter()
+        .map(|scalar| {
+            let bytes = scalar.to_repr();
+            let mut repr = F2::Repr::default();
+            if bytes.as_ref().len() != repr.as_ref().len() {
+                return None;
+            }
+            repr.as_mut().copy_from_slice(bytes.as_ref());
+            F2::from_repr(repr).into()
+        })
+        .collect()
+}
+
+/// A codec built on the [`PoseidonDuplexSponge`] over the scalar field of `G`.
+///
+/// Scalars of `G` are absorbed and challenges are squeezed as field elements of the sponge,
+/// which makes the challenges cheap to recompute inside arithmetic circuits over this field.
+/// Messages, including serialized group elements, are absorbed as bytes packed into field elements.
+///
+/// [`crate::fiat_shamir::Nizk`] absorbs the commitments of its proofs with [`Codec::prover_message`],
+/// so they are packed as bytes rather than absorbed as native field elements:
+/// `Nizk` proofs are not circuit-native, and only their challenges are squeezed as field elements.
+/// Protocols recomputing their transcripts in a circuit should drive the codec directly,
+/// with [`Codec::absorb_scalars`] and [`Codec::squeeze_scalars`].
+#[derive(Clone)]
+pub struct PoseidonCodec<G>
+where
+    G: PrimeGroup,
+{
+    sponge: PoseidonDuplexSponge<G::Scalar>,
+}
+
+impl<G> Codec for PoseidonCodec<G>
+where
+    G: PrimeGroup,
+{
+    type Challenge = G::Scalar;
+
+    fn iv(protocol_id: &[u8], session_id: &[u8], instance_label: &[u8]) -> [u8; 64] {
+        compute_iv::<PoseidonDuplexSponge<G::Scalar>>(protocol_id, session_id, instance_label)
+    }
+
+    fn from_iv(iv: [u8; 64]) -> Self {
+        Self {
+            sponge: PoseidonDuplexSponge::new(iv),
+        }
+    }
+
+    fn prover_message(&mut self, data: &[u8]) {
+        self.sponge.absorb(data);
+    }
+
+    fn verifier_challenge(&mut self) -> Self::Challenge {
+        self.sponge.squeeze_elements(1)[0]
+    }
+
+    fn verifier_challenge_in(&mut self, challenge_space: ChallengeSpace) -> Self::Challenge {
+        match challenge_space {
+            ChallengeSpace::Full => self.verifier_challenge(),
+            ChallengeSpace::Short => {
+                let bytes = self.squeeze_bytes(SHORT_CHALLENGE_SIZE);
+                G::Scalar::from_u128(u128::from_be_bytes(bytes.try_into().unwrap()))
+            }
+        }
+    }
+
+    fn squeeze_bytes(&mut self, length: usize) -> Vec<u8> {
+        self.sponge.squeeze(length)
+    }
+
+    fn export_state(&self) -> Result<Vec<u8>, Error> {
+        self.sponge.export_state()
+    }
+
+    fn import_state(snapshot: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            sponge: PoseidonDuplexSponge::import_state(snapshot)?,
+        })
+    }
+
+    /// Absorbs scalars as field elements if they belong to the scalar field of `G`,
+    /// and serialized with [`serialize_scalars`] otherwise.
+    fn absorb_scalars<H: PrimeGroup>(&mut self, scalars: &[H::Scalar]) {
+        match convert_scalars::<H::Scalar, G::Scalar>(scalars) {
+            Some(elements) => self.sponge.absorb_elements(&elements),
+            None => self.prover_message(&serialize_scalars::<H>(scalars)),
+        }
+    }
+
+    /// Squeezes scalars as field elements if they belong to the scalar field of `G`,
+    /// and from uniform bytes otherwise.
+    fn squeeze_scalars<H: PrimeGroup>(&mut self, count: usize) -> Vec<H::Scalar> {
+        if G::Scalar::MODULUS == H::Scalar::MODULUS {
+            let elements = self.sponge.squeeze_elements(count);
+            if let Some(scalars) = convert_scalars(&elements) {
+                return scalars;
+            }
+        }
+        let length = uniform_scalar_length::<H::Scalar>();
+        self.squeeze_bytes(count * length)
+            .chunks_exact(length)
+            .map(reduce_be_bytes)
+            .collect()
+    }
+}
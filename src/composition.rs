@@ -1,4 +1,4 @@
-//! # Protocol Composition with AND/OR Logic
+//! # Protocol Composition with AND/OR/Threshold Logic
 //!
 //! This module defines the [`ComposedRelation`] enum, which generalizes the [`CanonicalLinearRelation`]
 //! by enabling compositional logic between multiple proof instances.
@@ -7,6 +7,8 @@
 //! - Simple atomic proofs (e.g., discrete logarithm, Pedersen commitments)
 //! - Conjunctions (`And`) of multiple sub-protocols
 //! - Disjunctions (`Or`) of multiple sub-protocols
+//! - Thresholds (`Threshold`), proving `k` out of `n` sub-protocols
+//! - Conjunctions of linear relations sharing some of their scalars (`SharedAnd`)
 //!
 //! ## Example Composition
 //!
@@ -18,6 +20,8 @@
 //! )
 //! ```
 
+use alloc::format;
+use alloc::vec;
 use alloc::vec::Vec;
 use ff::{Field, PrimeField};
 use group::prime::PrimeGroup;
@@ -26,29 +30,54 @@ use rand::{CryptoRng, Rng};
 #[cfg(not(feature = "std"))]
 use rand_core::{CryptoRng, RngCore as Rng};
 use sha3::{Digest, Sha3_256};
-use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, ConstantTimeLess};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use crate::errors::InvalidInstance;
 use crate::group::serialization::{deserialize_scalars, serialize_scalars};
+use crate::group::zeroize_helpers::{
+    extend_secret_scalars, zeroize_choice, zeroize_scalar, zeroize_scalars,
+};
 use crate::{
-    codec::Shake128DuplexSponge,
+    codec::{ChallengeSpace, Shake128DuplexSponge},
     errors::Error,
     fiat_shamir::Nizk,
-    linear_relation::{CanonicalLinearRelation, LinearRelation},
-    traits::{SigmaProtocol, SigmaProtocolSimulator},
+    linear_relation::{CanonicalLinearRelation, LinearRelation, ScalarVar},
+    traits::{
+        sealed::TrialResponse, SigmaProtocol, SigmaProtocolBatchVerifier, SigmaProtocolSimulator,
+        SigmaProtocolTrialResponse, VerificationEquation,
+    },
 };
 
 /// A protocol proving knowledge of a witness for a composition of linear relations.
 ///
-/// This implementation generalizes [`CanonicalLinearRelation`] by using AND/OR links.
+/// This implementation generalizes [`CanonicalLinearRelation`] by using AND/OR links,
+/// and `k`-out-of-`n` thresholds.
+///
+/// A `Threshold(k, relations)` is proven with the technique of Cramer, Damgård and Schoenmakers:
+/// the challenges of the branches are the evaluations at `1..=n` of a polynomial of degree `n - k`
+/// whose evaluation at 0 is the challenge of the composition, so that the prover can simulate
+/// any `n - k` branches. The threshold must be between 1 and the number of relations.
+///
+/// A `SharedAnd(representatives, relations)` is an AND of linear relations whose scalars,
+/// numbered consecutively across the relations, are mapped to the first scalar they are equal to.
+/// Equal scalars share their nonce and their response, which is sent once.
+/// Its commitments, prover states, responses and witnesses are those of an AND of simple relations.
 ///
 /// # Type Parameters
 /// - `G`: A cryptographic group implementing [`group::Group`] and [`group::GroupEncoding`].
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub enum ComposedRelation<G: PrimeGroup> {
     Simple(CanonicalLinearRelation<G>),
     And(Vec<ComposedRelation<G>>),
     Or(Vec<ComposedRelation<G>>),
+    Threshold(usize, Vec<ComposedRelation<G>>),
+    SharedAnd(Vec<usize>, Vec<CanonicalLinearRelation<G>>),
 }
 
 impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> ComposedRelation<G> {
@@ -61,6 +90,60 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> ComposedRelation<
     pub fn or<T: Into<ComposedRelation<G>>>(witness: impl IntoIterator<Item = T>) -> Self {
         Self::Or(witness.into_iter().map(|x| x.into()).collect())
     }
+
+    /// Create a [ComposedRelation] for a threshold relation, satisfied by any `threshold`
+    /// of the given relations.
+    pub fn threshold<T: Into<ComposedRelation<G>>>(
+        threshold: usize,
+        relations: impl IntoIterator<Item = T>,
+    ) -> Self {
+        Self::Threshold(threshold, relations.into_iter().map(|x| x.into()).collect())
+    }
+
+    /// Create a [ComposedRelation] for an AND relation of linear relations sharing some of their scalars.
+    ///
+    /// Each equality `((i, x), (j, y))` states that the scalar `x` of the `i`-th relation
+    /// is the scalar `y` of the `j`-th relation. The witness is a [ComposedWitness::and]
+    /// of the witnesses of the relations, which must agree on the shared scalars.
+    ///
+    /// Returns an error if an equality refers to a relation or a scalar that does not exist.
+    pub fn and_with_shared_scalars(
+        relations: impl IntoIterator<Item = CanonicalLinearRelation<G>>,
+        equalities: impl IntoIterator<Item = ((usize, ScalarVar<G>), (usize, ScalarVar<G>))>,
+    ) -> Result<Self, InvalidInstance> {
+        let relations: Vec<_> = relations.into_iter().collect();
+        let mut offsets = Vec::with_capacity(relations.len());
+        let mut num_scalars = 0;
+        for relation in &relations {
+            offsets.push(num_scalars);
+            num_scalars += relation.num_scalars;
+        }
+        let index = |(i, var): (usize, ScalarVar<G>)| match relations.get(i) {
+            Some(relation) if var.index() < relation.num_scalars => Ok(offsets[i] + var.index()),
+            _ => Err(InvalidInstance::new(format!(
+                "Shared scalar {} of relation {i} does not exist.",
+                var.index()
+            ))),
+        };
+
+        // Each scalar points to a smaller scalar of its class, or to itself if it is the smallest.
+        let mut representatives: Vec<usize> = (0..num_scalars).collect();
+        let root = |representatives: &[usize], mut i: usize| {
+            while representatives[i] != i {
+                i = representatives[i];
+            }
+            i
+        };
+        for (lhs, rhs) in equalities {
+            let lhs = root(&representatives, index(lhs)?);
+            let rhs = root(&representatives, index(rhs)?);
+            representatives[lhs.max(rhs)] = lhs.min(rhs);
+        }
+        for i in 0..num_scalars {
+            representatives[i] = representatives[representatives[i]];
+        }
+        Ok(Self::SharedAnd(representatives, relations))
+    }
 }
 
 impl<G: PrimeGroup> From<CanonicalLinearRelation<G>> for ComposedRelation<G> {
@@ -79,10 +162,22 @@ impl<G: PrimeGroup> TryFrom<LinearRelation<G>> for ComposedRelation<G> {
 
 // Structure representing the Commitment type of Protocol as SigmaProtocol
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub enum ComposedCommitment<G: PrimeGroup> {
-    Simple(<CanonicalLinearRelation<G> as SigmaProtocol>::Commitment),
+    Simple(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "crate::group::serde_helpers::elements")
+        )]
+        <CanonicalLinearRelation<G> as SigmaProtocol>::Commitment,
+    ),
     And(Vec<ComposedCommitment<G>>),
     Or(Vec<ComposedCommitment<G>>),
+    Threshold(Vec<ComposedCommitment<G>>),
 }
 
 impl<G: PrimeGroup> ComposedCommitment<G>
@@ -121,6 +216,18 @@ where
                     .collect();
                 ComposedCommitment::Or(selected)
             }
+            (
+                ComposedCommitment::Threshold(a_commitments),
+                ComposedCommitment::Threshold(b_commitments),
+            ) => {
+                debug_assert_eq!(a_commitments.len(), b_commitments.len());
+                let selected: Vec<ComposedCommitment<G>> = a_commitments
+                    .iter()
+                    .zip(b_commitments.iter())
+                    .map(|(a, b)| ComposedCommitment::conditional_select(a, b, choice))
+                    .collect();
+                ComposedCommitment::Threshold(selected)
+            }
             _ => {
                 unreachable!("Mismatched ComposedCommitment variants in conditional_select");
             }
@@ -129,13 +236,36 @@ where
 }
 
 // Structure representing the ProverState type of Protocol as SigmaProtocol
+//
+// All the secrets it holds are overwritten with zeros when it is dropped.
 pub enum ComposedProverState<G: PrimeGroup + ConstantTimeEq> {
     Simple(<CanonicalLinearRelation<G> as SigmaProtocol>::ProverState),
     And(Vec<ComposedProverState<G>>),
     Or(ComposedOrProverState<G>),
+    Threshold(ComposedOrProverState<G>),
+}
+
+impl<G: PrimeGroup + ConstantTimeEq> Zeroize for ComposedProverState<G> {
+    fn zeroize(&mut self) {
+        match self {
+            ComposedProverState::Simple(state) => state.zeroize(),
+            ComposedProverState::And(states) => states.iter_mut().for_each(Zeroize::zeroize),
+            ComposedProverState::Or(entries) | ComposedProverState::Threshold(entries) => {
+                entries.iter_mut().for_each(Zeroize::zeroize)
+            }
+        }
+    }
 }
 
+impl<G: PrimeGroup + ConstantTimeEq> ZeroizeOnDrop for ComposedProverState<G> {}
+
 pub type ComposedOrProverState<G> = Vec<ComposedOrProverStateEntry<G>>;
+
+/// The prover state of a branch of an OR or threshold composition.
+///
+/// It holds whether the branch is a real one, the prover state of the real branch,
+/// and the simulated challenge and response of the other branches.
+/// It is overwritten with zeros when dropped, as it reveals which branch holds the witness.
 pub struct ComposedOrProverStateEntry<G: PrimeGroup + ConstantTimeEq>(
     Choice,
     ComposedProverState<G>,
@@ -143,15 +273,76 @@ pub struct ComposedOrProverStateEntry<G: PrimeGroup + ConstantTimeEq>(
     ComposedResponse<G>,
 );
 
+impl<G: PrimeGroup + ConstantTimeEq> Zeroize for ComposedOrProverStateEntry<G> {
+    fn zeroize(&mut self) {
+        zeroize_choice(&mut self.0);
+        self.1.zeroize();
+        zeroize_scalar(&mut self.2);
+        self.3.zeroize_scalars();
+    }
+}
+
+impl<G: PrimeGroup + ConstantTimeEq> Drop for ComposedOrProverStateEntry<G> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<G: PrimeGroup + ConstantTimeEq> ZeroizeOnDrop for ComposedOrProverStateEntry<G> {}
+
 // Structure representing the Response type of Protocol as SigmaProtocol
 #[derive(Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub enum ComposedResponse<G: PrimeGroup> {
-    Simple(<CanonicalLinearRelation<G> as SigmaProtocol>::Response),
+    Simple(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "crate::group::serde_helpers::scalars")
+        )]
+        <CanonicalLinearRelation<G> as SigmaProtocol>::Response,
+    ),
     And(Vec<ComposedResponse<G>>),
-    Or(Vec<ComposedChallenge<G>>, Vec<ComposedResponse<G>>),
+    Or(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "crate::group::serde_helpers::scalars")
+        )]
+        Vec<ComposedChallenge<G>>,
+        Vec<ComposedResponse<G>>,
+    ),
+    /// The challenges of the first `n - k` branches, and the responses of all branches.
+    Threshold(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "crate::group::serde_helpers::scalars")
+        )]
+        Vec<ComposedChallenge<G>>,
+        Vec<ComposedResponse<G>>,
+    ),
 }
 
 impl<G: PrimeGroup> ComposedResponse<G> {
+    /// Overwrites all the scalars of the response with zeros.
+    fn zeroize_scalars(&mut self) {
+        match self {
+            ComposedResponse::Simple(scalars) => zeroize_scalars(scalars),
+            ComposedResponse::And(responses) => responses
+                .iter_mut()
+                .for_each(ComposedResponse::zeroize_scalars),
+            ComposedResponse::Or(challenges, responses)
+            | ComposedResponse::Threshold(challenges, responses) => {
+                zeroize_scalars(challenges);
+                responses
+                    .iter_mut()
+                    .for_each(ComposedResponse::zeroize_scalars);
+            }
+        }
+    }
+
     /// Conditionally select between two ComposedResponse values.
     /// This function performs constant-time selection of the response values.
     pub fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
@@ -196,6 +387,27 @@ impl<G: PrimeGroup> ComposedResponse<G> {
 
                 ComposedResponse::Or(selected_challenges, selected_responses)
             }
+            (
+                ComposedResponse::Threshold(a_challenges, a_responses),
+                ComposedResponse::Threshold(b_challenges, b_responses),
+            ) => {
+                debug_assert_eq!(a_challenges.len(), b_challenges.len());
+                debug_assert_eq!(a_responses.len(), b_responses.len());
+
+                let selected_challenges: Vec<ComposedChallenge<G>> = a_challenges
+                    .iter()
+                    .zip(b_challenges.iter())
+                    .map(|(a, b)| G::Scalar::conditional_select(a, b, choice))
+                    .collect();
+
+                let selected_responses: Vec<ComposedResponse<G>> = a_responses
+                    .iter()
+                    .zip(b_responses.iter())
+                    .map(|(a, b)| ComposedResponse::conditional_select(a, b, choice))
+                    .collect();
+
+                ComposedResponse::Threshold(selected_challenges, selected_responses)
+            }
             _ => {
                 unreachable!("Mismatched ComposedResponse variants in conditional_select");
             }
@@ -204,11 +416,24 @@ impl<G: PrimeGroup> ComposedResponse<G> {
 }
 
 // Structure representing the Witness type of Protocol as SigmaProtocol
-#[derive(Clone)]
+//
+// The scalars of the witness are overwritten with zeros when it is dropped.
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(bound = "")
+)]
 pub enum ComposedWitness<G: PrimeGroup> {
-    Simple(<CanonicalLinearRelation<G> as SigmaProtocol>::Witness),
+    Simple(
+        #[cfg_attr(
+            feature = "serde",
+            serde(with = "crate::group::serde_helpers::scalars")
+        )]
+        <CanonicalLinearRelation<G> as SigmaProtocol>::Witness,
+    ),
     And(Vec<ComposedWitness<G>>),
     Or(Vec<ComposedWitness<G>>),
+    Threshold(Vec<ComposedWitness<G>>),
 }
 
 impl<G: PrimeGroup> ComposedWitness<G> {
@@ -221,8 +446,78 @@ impl<G: PrimeGroup> ComposedWitness<G> {
     pub fn or<T: Into<ComposedWitness<G>>>(witness: impl IntoIterator<Item = T>) -> Self {
         Self::Or(witness.into_iter().map(|x| x.into()).collect())
     }
+
+    /// Create a [ComposedWitness] for a threshold relation from the given list of witnesses.
+    ///
+    /// Witnesses of the branches that are not known can be arbitrary.
+    pub fn threshold<T: Into<ComposedWitness<G>>>(witness: impl IntoIterator<Item = T>) -> Self {
+        Self::Threshold(witness.into_iter().map(|x| x.into()).collect())
+    }
+
+    /// Returns the length of the serialization of the witness.
+    fn serialized_len(&self) -> usize {
+        match self {
+            ComposedWitness::Simple(scalars) => {
+                let scalar_len = <G::Scalar as PrimeField>::Repr::default().as_ref().len();
+                5 + scalars.len() * scalar_len
+            }
+            ComposedWitness::And(witnesses)
+            | ComposedWitness::Or(witnesses)
+            | ComposedWitness::Threshold(witnesses) => {
+                5 + witnesses.iter().map(Self::serialized_len).sum::<usize>()
+            }
+        }
+    }
+
+    /// Appends the serialization of the witness to `bytes`.
+    ///
+    /// Each node is encoded as a tag byte (0 for simple, 1 for AND, 2 for OR, 3 for threshold),
+    /// followed by its number of scalars or children as a 32-bit big-endian integer
+    /// and by its scalars or children.
+    fn serialize_into(&self, bytes: &mut Vec<u8>) {
+        let (tag, witnesses) = match self {
+            ComposedWitness::Simple(scalars) => {
+                bytes.push(0);
+                bytes.extend_from_slice(&(scalars.len() as u32).to_be_bytes());
+                extend_secret_scalars(bytes, scalars);
+                return;
+            }
+            ComposedWitness::And(witnesses) => (1, witnesses),
+            ComposedWitness::Or(witnesses) => (2, witnesses),
+            ComposedWitness::Threshold(witnesses) => (3, witnesses),
+        };
+        bytes.push(tag);
+        bytes.extend_from_slice(&(witnesses.len() as u32).to_be_bytes());
+        for witness in witnesses {
+            witness.serialize_into(bytes);
+        }
+    }
+}
+
+impl<G: PrimeGroup> Zeroize for ComposedWitness<G> {
+    fn zeroize(&mut self) {
+        match self {
+            ComposedWitness::Simple(scalars) => zeroize_scalars(scalars),
+            ComposedWitness::And(witnesses)
+            | ComposedWitness::Or(witnesses)
+            | ComposedWitness::Threshold(witnesses) => {
+                witnesses.iter_mut().for_each(Zeroize::zeroize)
+            }
+        }
+    }
+}
+
+impl<G: PrimeGroup> Drop for ComposedWitness<G> {
+    fn drop(&mut self) {
+        // Nested witnesses are zeroized when they are dropped in turn.
+        if let ComposedWitness::Simple(scalars) = self {
+            zeroize_scalars(scalars);
+        }
+    }
 }
 
+impl<G: PrimeGroup> ZeroizeOnDrop for ComposedWitness<G> {}
+
 impl<G: PrimeGroup> From<<CanonicalLinearRelation<G> as SigmaProtocol>::Witness>
     for ComposedWitness<G>
 {
@@ -233,8 +528,276 @@ impl<G: PrimeGroup> From<<CanonicalLinearRelation<G> as SigmaProtocol>::Witness>
 
 type ComposedChallenge<G> = <CanonicalLinearRelation<G> as SigmaProtocol>::Challenge;
 
-const fn composed_challenge_size<G: PrimeGroup>() -> usize {
-    (G::Scalar::NUM_BITS as usize).div_ceil(8)
+/// The challenges and the responses of the branches of an OR or threshold composition.
+type ChallengesAndResponses<G> = (Vec<ComposedChallenge<G>>, Vec<ComposedResponse<G>>);
+
+/// Recovers the challenge of the last branch of an OR composition,
+/// which is not part of the response, from the challenge of the composition and the other challenges.
+fn last_or_challenge<G: PrimeGroup>(
+    challenge_space: ChallengeSpace,
+    challenge: &ComposedChallenge<G>,
+    challenges: &[ComposedChallenge<G>],
+) -> ComposedChallenge<G> {
+    challenges
+        .iter()
+        .fold(*challenge, |acc, c| challenge_space.remove(&acc, c))
+}
+
+/// Returns whether `threshold` out of `n` branches is a valid threshold composition.
+fn is_valid_threshold(threshold: usize, n: usize) -> bool {
+    threshold >= 1 && threshold <= n
+}
+
+/// Evaluates at `x` the polynomial interpolating the points `(i, values[i])`
+/// for the indices `i` such that `mask[i]` is set.
+///
+/// The points are selected in constant time.
+fn interpolate<F: PrimeField>(values: &[F], mask: &[Choice], x: u64) -> F {
+    let x = F::from(x);
+    let mut result = F::ZERO;
+    for (i, (value, selected)) in values.iter().zip(mask).enumerate() {
+        let x_i = F::from(i as u64);
+        let mut basis = F::ONE;
+        for (j, other_selected) in mask.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            let x_j = F::from(j as u64);
+            // The interpolation points are distinct, so the difference is invertible.
+            let factor = (x - x_j) * (x_i - x_j).invert().unwrap();
+            basis = F::conditional_select(&basis, &(basis * factor), *other_selected);
+        }
+        result += F::conditional_select(&F::ZERO, &(*value * basis), *selected);
+    }
+    result
+}
+
+/// Recovers the challenges of all the branches of a threshold composition
+/// from the challenge of the composition and the challenges of the first `n - k` branches.
+///
+/// The challenges are the evaluations at `1..=n` of the polynomial of degree `n - k`
+/// whose evaluation at 0 is the challenge of the composition.
+fn threshold_challenges<G: PrimeGroup>(
+    challenge: &ComposedChallenge<G>,
+    challenges: &[ComposedChallenge<G>],
+    n: usize,
+) -> Vec<ComposedChallenge<G>> {
+    let mut values = Vec::with_capacity(challenges.len() + 1);
+    values.push(*challenge);
+    values.extend_from_slice(challenges);
+    let mask = vec![Choice::from(1); values.len()];
+    let mut all_challenges = challenges.to_vec();
+    all_challenges
+        .extend((challenges.len() + 1..=n).map(|i| interpolate(&values, &mask, i as u64)));
+    all_challenges
+}
+
+/// Returns whether `representatives` maps each scalar of `relations`,
+/// numbered consecutively across the relations, to the first scalar equal to it.
+fn is_valid_sharing<G: PrimeGroup>(
+    representatives: &[usize],
+    relations: &[CanonicalLinearRelation<G>],
+) -> bool {
+    representatives.len() == relations.iter().map(|p| p.num_scalars).sum::<usize>()
+        && representatives
+            .iter()
+            .enumerate()
+            .all(|(i, &r)| r <= i && representatives[r] == r)
+}
+
+/// Builds the scalars of a shared AND composition, drawing the scalars of the representatives
+/// from `fresh` and copying them to the scalars they are shared with.
+fn expand_shared_scalars<F: Copy>(
+    representatives: &[usize],
+    mut fresh: impl FnMut() -> F,
+) -> Vec<F> {
+    let mut scalars: Vec<F> = Vec::with_capacity(representatives.len());
+    for (i, &r) in representatives.iter().enumerate() {
+        let scalar = if r == i { fresh() } else { scalars[r] };
+        scalars.push(scalar);
+    }
+    scalars
+}
+
+/// Concatenates the scalars of the simple responses or witnesses of the relations
+/// of a shared AND composition, or returns `None` if they do not match the relations.
+fn concat_shared_scalars<G: PrimeGroup, T>(
+    relations: &[CanonicalLinearRelation<G>],
+    parts: &[T],
+    scalars_of: impl Fn(&T) -> Option<&[G::Scalar]>,
+) -> Option<Vec<G::Scalar>> {
+    if relations.len() != parts.len() {
+        return None;
+    }
+    let mut scalars = Vec::new();
+    for (relation, part) in relations.iter().zip(parts) {
+        let part = scalars_of(part).filter(|part| part.len() >= relation.num_scalars)?;
+        scalars.extend_from_slice(&part[..relation.num_scalars]);
+    }
+    Some(scalars)
+}
+
+/// Checks that the responses of a shared AND composition match its relations,
+/// and that the responses of shared scalars are equal.
+fn check_shared_responses<G: PrimeGroup>(
+    representatives: &[usize],
+    relations: &[CanonicalLinearRelation<G>],
+    responses: &[ComposedResponse<G>],
+) -> Result<(), Error> {
+    if !is_valid_sharing(representatives, relations) {
+        return Err(Error::InvalidInstanceWitnessPair);
+    }
+    let scalars = concat_shared_scalars(relations, responses, |r| match r {
+        ComposedResponse::Simple(r) => Some(r.as_slice()),
+        _ => None,
+    })
+    .ok_or(Error::InvalidInstanceWitnessPair)?;
+    if representatives
+        .iter()
+        .enumerate()
+        .all(|(i, &r)| scalars[i] == scalars[r])
+    {
+        Ok(())
+    } else {
+        Err(Error::VerificationFailure)
+    }
+}
+
+/// Splits the scalars of a shared AND composition into the simple responses of its relations.
+fn split_shared_responses<G: PrimeGroup>(
+    relations: &[CanonicalLinearRelation<G>],
+    scalars: &[G::Scalar],
+) -> Vec<ComposedResponse<G>> {
+    let mut offset = 0;
+    relations
+        .iter()
+        .map(|p| {
+            offset += p.num_scalars;
+            ComposedResponse::Simple(scalars[offset - p.num_scalars..offset].to_vec())
+        })
+        .collect()
+}
+
+impl<G: PrimeGroup> ComposedRelation<G> {
+    /// Sets the challenge space of all the linear relations of the composition.
+    ///
+    /// See [`CanonicalLinearRelation::with_challenge_space`].
+    pub fn with_challenge_space(self, challenge_space: ChallengeSpace) -> Self {
+        match self {
+            ComposedRelation::Simple(p) => {
+                ComposedRelation::Simple(p.with_challenge_space(challenge_space))
+            }
+            ComposedRelation::And(ps) => ComposedRelation::And(
+                ps.into_iter()
+                    .map(|p| p.with_challenge_space(challenge_space))
+                    .collect(),
+            ),
+            ComposedRelation::Or(ps) => ComposedRelation::Or(
+                ps.into_iter()
+                    .map(|p| p.with_challenge_space(challenge_space))
+                    .collect(),
+            ),
+            ComposedRelation::Threshold(threshold, ps) => ComposedRelation::Threshold(
+                threshold,
+                ps.into_iter()
+                    .map(|p| p.with_challenge_space(challenge_space))
+                    .collect(),
+            ),
+            ComposedRelation::SharedAnd(representatives, ps) => ComposedRelation::SharedAnd(
+                representatives,
+                ps.into_iter()
+                    .map(|p| p.with_challenge_space(challenge_space))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Returns the challenge space of the composition:
+    /// [`ChallengeSpace::Short`] if all its linear relations use short challenges,
+    /// [`ChallengeSpace::Full`] otherwise.
+    ///
+    /// Threshold compositions always use [`ChallengeSpace::Full`],
+    /// as the challenges of their branches are evaluations of a polynomial over the scalar field.
+    fn composed_challenge_space(&self) -> ChallengeSpace {
+        match self {
+            ComposedRelation::Simple(p) => p.challenge_space,
+            ComposedRelation::And(ps) | ComposedRelation::Or(ps) => {
+                let all_short = !ps.is_empty()
+                    && ps
+                        .iter()
+                        .all(|p| p.composed_challenge_space() == ChallengeSpace::Short);
+                if all_short {
+                    ChallengeSpace::Short
+                } else {
+                    ChallengeSpace::Full
+                }
+            }
+            ComposedRelation::SharedAnd(_, ps) => {
+                if !ps.is_empty()
+                    && ps
+                        .iter()
+                        .all(|p| p.challenge_space == ChallengeSpace::Short)
+                {
+                    ChallengeSpace::Short
+                } else {
+                    ChallengeSpace::Full
+                }
+            }
+            ComposedRelation::Threshold(..) => ChallengeSpace::Full,
+        }
+    }
+
+    /// Appends the structural encoding of a composition to `bytes`, with `leaf` encoding simple relations.
+    ///
+    /// Each node is encoded as a tag byte (0 for simple, 1 for AND, 2 for OR, 3 for threshold,
+    /// 4 for shared AND), as in the witness serialization for the first four. A simple relation
+    /// is followed by the length of its encoding as a 32-bit big-endian integer and by its encoding.
+    /// A threshold is followed by its threshold as a 32-bit big-endian integer. Composition nodes
+    /// are then followed by their number of children as a 32-bit big-endian integer and by their children.
+    /// A shared AND is finally followed by its number of scalars and by their representatives,
+    /// as 32-bit big-endian integers.
+    /// The encoding is injective: distinct compositions have distinct encodings.
+    fn encode_structure(
+        &self,
+        bytes: &mut Vec<u8>,
+        leaf: &impl Fn(&CanonicalLinearRelation<G>) -> Vec<u8>,
+    ) {
+        let encode_simple = |bytes: &mut Vec<u8>, p: &CanonicalLinearRelation<G>| {
+            let encoding = leaf(p);
+            bytes.push(0);
+            bytes.extend_from_slice(&(encoding.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(&encoding);
+        };
+        let (tag, children) = match self {
+            ComposedRelation::Simple(p) => {
+                encode_simple(bytes, p);
+                return;
+            }
+            ComposedRelation::SharedAnd(representatives, ps) => {
+                bytes.push(4);
+                bytes.extend_from_slice(&(ps.len() as u32).to_be_bytes());
+                for p in ps {
+                    encode_simple(bytes, p);
+                }
+                bytes.extend_from_slice(&(representatives.len() as u32).to_be_bytes());
+                for r in representatives {
+                    bytes.extend_from_slice(&(*r as u32).to_be_bytes());
+                }
+                return;
+            }
+            ComposedRelation::And(ps) => (1, ps),
+            ComposedRelation::Or(ps) => (2, ps),
+            ComposedRelation::Threshold(_, ps) => (3, ps),
+        };
+        bytes.push(tag);
+        if let ComposedRelation::Threshold(threshold, _) = self {
+            bytes.extend_from_slice(&(*threshold as u32).to_be_bytes());
+        }
+        bytes.extend_from_slice(&(children.len() as u32).to_be_bytes());
+        for child in children {
+            child.encode_structure(bytes, leaf);
+        }
+    }
 }
 
 impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> ComposedRelation<G> {
@@ -255,6 +818,48 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> ComposedRelation<
                 .fold(Choice::from(0), |bit, (instance, witness)| {
                     bit | instance.is_witness_valid(witness)
                 }),
+            (
+                ComposedRelation::Threshold(threshold, instances),
+                ComposedWitness::Threshold(witnesses),
+            ) => {
+                let valid_count =
+                    instances
+                        .iter()
+                        .zip(witnesses)
+                        .fold(0u64, |count, (instance, witness)| {
+                            count + instance.is_witness_valid(witness).unwrap_u8() as u64
+                        });
+                !valid_count.ct_lt(&(*threshold as u64))
+            }
+            (
+                ComposedRelation::SharedAnd(representatives, instances),
+                ComposedWitness::And(witnesses),
+            ) => {
+                let scalars = concat_shared_scalars(instances, witnesses, |w| match w {
+                    ComposedWitness::Simple(w) => Some(w.as_slice()),
+                    _ => None,
+                });
+                match scalars {
+                    Some(mut scalars) if is_valid_sharing(representatives, instances) => {
+                        let valid = instances.iter().zip(witnesses).fold(
+                            Choice::from(1),
+                            |bit, (instance, witness)| match witness {
+                                ComposedWitness::Simple(w) => bit & instance.is_witness_valid(w),
+                                _ => Choice::from(0),
+                            },
+                        );
+                        let shared = representatives
+                            .iter()
+                            .enumerate()
+                            .fold(Choice::from(1), |bit, (i, &r)| {
+                                bit & scalars[i].ct_eq(&scalars[r])
+                            });
+                        zeroize_scalars(&mut scalars);
+                        valid & shared
+                    }
+                    _ => Choice::from(0),
+                }
+            }
             _ => Choice::from(0),
         }
     }
@@ -274,11 +879,11 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> ComposedRelation<
 
     fn prover_response_simple(
         instance: &CanonicalLinearRelation<G>,
-        state: <CanonicalLinearRelation<G> as SigmaProtocol>::ProverState,
+        state: &<CanonicalLinearRelation<G> as SigmaProtocol>::ProverState,
         challenge: &<CanonicalLinearRelation<G> as SigmaProtocol>::Challenge,
     ) -> Result<ComposedResponse<G>, Error> {
         instance
-            .prover_response(state, challenge)
+            .prover_trial_response(state, challenge)
             .map(ComposedResponse::Simple)
     }
 
@@ -308,7 +913,59 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> ComposedRelation<
 
     fn prover_response_and(
         instances: &[ComposedRelation<G>],
-        prover_state: Vec<ComposedProverState<G>>,
+        prover_state: &[ComposedProverState<G>],
+        challenge: &ComposedChallenge<G>,
+    ) -> Result<ComposedResponse<G>, Error> {
+        if instances.len() != prover_state.len() {
+            return Err(Error::InvalidInstanceWitnessPair);
+        }
+
+        let responses: Result<Vec<_>, _> = instances
+            .iter()
+            .zip(prover_state)
+            .map(|(p, s)| p.prover_trial_response(s, challenge))
+            .collect();
+
+        Ok(ComposedResponse::And(responses?))
+    }
+
+    fn prover_commit_shared_and(
+        representatives: &[usize],
+        instances: &[CanonicalLinearRelation<G>],
+        witnesses: &[ComposedWitness<G>],
+        rng: &mut (impl Rng + CryptoRng),
+    ) -> Result<(ComposedCommitment<G>, ComposedProverState<G>), Error> {
+        if instances.len() != witnesses.len() || !is_valid_sharing(representatives, instances) {
+            return Err(Error::InvalidInstanceWitnessPair);
+        }
+
+        // If the shared scalars of the witness differ, so do their responses,
+        // and the proof does not verify.
+        let mut nonces = expand_shared_scalars(representatives, || G::Scalar::random(&mut *rng));
+        let mut commitments = Vec::with_capacity(instances.len());
+        let mut prover_states = Vec::with_capacity(instances.len());
+        let mut offset = 0;
+        for (p, w) in instances.iter().zip(witnesses) {
+            let ComposedWitness::Simple(w) = w else {
+                return Err(Error::InvalidInstanceWitnessPair);
+            };
+            let p_nonces = nonces[offset..offset + p.num_scalars].to_vec();
+            offset += p.num_scalars;
+            let (c, s) = p.commit_with_nonces(p_nonces, w)?;
+            commitments.push(ComposedCommitment::Simple(c));
+            prover_states.push(ComposedProverState::Simple(s));
+        }
+        zeroize_scalars(&mut nonces);
+
+        Ok((
+            ComposedCommitment::And(commitments),
+            ComposedProverState::And(prover_states),
+        ))
+    }
+
+    fn prover_response_shared_and(
+        instances: &[CanonicalLinearRelation<G>],
+        prover_state: &[ComposedProverState<G>],
         challenge: &ComposedChallenge<G>,
     ) -> Result<ComposedResponse<G>, Error> {
         if instances.len() != prover_state.len() {
@@ -318,7 +975,10 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> ComposedRelation<
         let responses: Result<Vec<_>, _> = instances
             .iter()
             .zip(prover_state)
-            .map(|(p, s)| p.prover_response(s, challenge))
+            .map(|(p, s)| match s {
+                ComposedProverState::Simple(s) => Self::prover_response_simple(p, s, challenge),
+                _ => Err(Error::InvalidInstanceWitnessPair),
+            })
             .collect();
 
         Ok(ComposedResponse::And(responses?))
@@ -378,54 +1038,188 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> ComposedRelation<
 
     fn prover_response_or(
         instances: &[ComposedRelation<G>],
-        prover_state: ComposedOrProverState<G>,
+        prover_state: &[ComposedOrProverStateEntry<G>],
         challenge: &ComposedChallenge<G>,
+        challenge_space: ChallengeSpace,
     ) -> Result<ComposedResponse<G>, Error> {
         let mut result_challenges = Vec::with_capacity(instances.len());
         let mut result_responses = Vec::with_capacity(instances.len());
 
         let mut witness_challenge = *challenge;
-        for ComposedOrProverStateEntry(
-            valid_witness,
-            _prover_state,
-            simulated_challenge,
-            _simulated_response,
-        ) in &prover_state
-        {
+        for ComposedOrProverStateEntry(valid_witness, _, simulated_challenge, _) in prover_state {
             let c = G::Scalar::conditional_select(
                 simulated_challenge,
                 &G::Scalar::ZERO,
                 *valid_witness,
             );
-            witness_challenge -= c;
+            witness_challenge = challenge_space.remove(&witness_challenge, &c);
         }
-        for (
-            instance,
-            ComposedOrProverStateEntry(
+        for (instance, entry) in instances.iter().zip(prover_state) {
+            let ComposedOrProverStateEntry(
                 valid_witness,
                 prover_state,
                 simulated_challenge,
                 simulated_response,
-            ),
-        ) in instances.iter().zip(prover_state)
-        {
+            ) = entry;
+
             let challenge_i = G::Scalar::conditional_select(
-                &simulated_challenge,
+                simulated_challenge,
                 &witness_challenge,
-                valid_witness,
+                *valid_witness,
             );
 
-            let response = instance.prover_response(prover_state, &challenge_i)?;
+            let response = instance.prover_trial_response(prover_state, &challenge_i)?;
             let response =
-                ComposedResponse::conditional_select(&simulated_response, &response, valid_witness);
+                ComposedResponse::conditional_select(simulated_response, &response, *valid_witness);
 
             result_challenges.push(challenge_i);
-            result_responses.push(response.clone());
+            result_responses.push(response);
         }
 
         result_challenges.pop();
         Ok(ComposedResponse::Or(result_challenges, result_responses))
     }
+
+    fn prover_commit_threshold(
+        threshold: usize,
+        instances: &[ComposedRelation<G>],
+        witnesses: &[ComposedWitness<G>],
+        rng: &mut (impl Rng + CryptoRng),
+    ) -> Result<(ComposedCommitment<G>, ComposedProverState<G>), Error>
+    where
+        G: ConditionallySelectable,
+    {
+        if instances.len() != witnesses.len() || !is_valid_threshold(threshold, instances.len()) {
+            return Err(Error::InvalidInstanceWitnessPair);
+        }
+
+        let mut commitments = Vec::new();
+        let mut prover_states = Vec::new();
+
+        // Number of valid witnesses selected so far: the first `threshold` ones are used.
+        let mut selected_count = 0u64;
+        for (i, w) in witnesses.iter().enumerate() {
+            let (commitment, prover_state) = instances[i].prover_commit(w, rng)?;
+            let (simulated_commitment, simulated_challenge, simulated_response) =
+                instances[i].simulate_transcript(rng)?;
+
+            let select_witness =
+                instances[i].is_witness_valid(w) & selected_count.ct_lt(&(threshold as u64));
+
+            let commitment = ComposedCommitment::conditional_select(
+                &simulated_commitment,
+                &commitment,
+                select_witness,
+            );
+
+            commitments.push(commitment);
+            prover_states.push(ComposedOrProverStateEntry(
+                select_witness,
+                prover_state,
+                simulated_challenge,
+                simulated_response,
+            ));
+
+            selected_count += select_witness.unwrap_u8() as u64;
+        }
+
+        if selected_count.ct_eq(&(threshold as u64)).unwrap_u8() == 0 {
+            Err(Error::InvalidInstanceWitnessPair)
+        } else {
+            Ok((
+                ComposedCommitment::Threshold(commitments),
+                ComposedProverState::Threshold(prover_states),
+            ))
+        }
+    }
+
+    fn prover_response_threshold(
+        threshold: usize,
+        instances: &[ComposedRelation<G>],
+        prover_state: &[ComposedOrProverStateEntry<G>],
+        challenge: &ComposedChallenge<G>,
+    ) -> Result<ComposedResponse<G>, Error> {
+        if instances.len() != prover_state.len() || !is_valid_threshold(threshold, instances.len())
+        {
+            return Err(Error::InvalidInstanceWitnessPair);
+        }
+
+        // The polynomial of the challenges interpolates the challenge of the composition at 0
+        // and the simulated challenges at the indices of the simulated branches.
+        let mut values = Vec::with_capacity(instances.len() + 1);
+        let mut mask = Vec::with_capacity(instances.len() + 1);
+        values.push(*challenge);
+        mask.push(Choice::from(1));
+        for ComposedOrProverStateEntry(valid_witness, _, simulated_challenge, _) in prover_state {
+            values.push(*simulated_challenge);
+            mask.push(!*valid_witness);
+        }
+
+        let mut result_challenges = Vec::with_capacity(instances.len());
+        let mut result_responses = Vec::with_capacity(instances.len());
+        for (i, (instance, entry)) in instances.iter().zip(prover_state).enumerate() {
+            let ComposedOrProverStateEntry(
+                valid_witness,
+                prover_state,
+                simulated_challenge,
+                simulated_response,
+            ) = entry;
+
+            let witness_challenge = interpolate(&values, &mask, i as u64 + 1);
+            let challenge_i = G::Scalar::conditional_select(
+                simulated_challenge,
+                &witness_challenge,
+                *valid_witness,
+            );
+
+            let response = instance.prover_trial_response(prover_state, &challenge_i)?;
+            let response =
+                ComposedResponse::conditional_select(simulated_response, &response, *valid_witness);
+
+            result_challenges.push(challenge_i);
+            result_responses.push(response);
+        }
+        mask.iter_mut().for_each(zeroize_choice);
+
+        result_challenges.truncate(instances.len() - threshold);
+        Ok(ComposedResponse::Threshold(
+            result_challenges,
+            result_responses,
+        ))
+    }
+
+    /// Deserializes `challenge_count` challenges followed by the responses of the branches `instances`,
+    /// as serialized in the responses of OR and threshold compositions.
+    fn deserialize_challenges_and_responses(
+        &self,
+        instances: &[ComposedRelation<G>],
+        challenge_count: usize,
+        data: &[u8],
+    ) -> Result<ChallengesAndResponses<G>, Error> {
+        let challenge_space = self.composed_challenge_space();
+        let ch_bytes_len = challenge_space.challenge_size::<G::Scalar>();
+        let challenges_size = challenge_count * ch_bytes_len;
+        if data.len() < challenges_size {
+            return Err(Error::VerificationFailure);
+        }
+        let challenges_bytes = &data[..challenges_size];
+        let response_bytes = &data[challenges_size..];
+        let challenges = challenges_bytes
+            .chunks_exact(ch_bytes_len)
+            .map(|bytes| challenge_space.deserialize(bytes))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(Error::VerificationFailure)?;
+
+        let mut cursor = 0;
+        let mut responses = Vec::with_capacity(instances.len());
+        for p in instances {
+            let r = p.deserialize_response(&response_bytes[cursor..])?;
+            let size = p.serialize_response(&r).len();
+            cursor += size;
+            responses.push(r);
+        }
+        Ok((challenges, responses))
+    }
 }
 
 impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocol
@@ -452,6 +1246,12 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocol
             (ComposedRelation::Or(ps), ComposedWitness::Or(witnesses)) => {
                 Self::prover_commit_or(ps, witnesses, rng)
             }
+            (ComposedRelation::Threshold(threshold, ps), ComposedWitness::Threshold(witnesses)) => {
+                Self::prover_commit_threshold(*threshold, ps, witnesses, rng)
+            }
+            (ComposedRelation::SharedAnd(representatives, ps), ComposedWitness::And(ws)) => {
+                Self::prover_commit_shared_and(representatives, ps, ws, rng)
+            }
             _ => Err(Error::InvalidInstanceWitnessPair),
         }
     }
@@ -461,18 +1261,8 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocol
         state: Self::ProverState,
         challenge: &Self::Challenge,
     ) -> Result<Self::Response, Error> {
-        match (self, state) {
-            (ComposedRelation::Simple(instance), ComposedProverState::Simple(state)) => {
-                Self::prover_response_simple(instance, state, challenge)
-            }
-            (ComposedRelation::And(instances), ComposedProverState::And(prover_state)) => {
-                Self::prover_response_and(instances, prover_state, challenge)
-            }
-            (ComposedRelation::Or(instances), ComposedProverState::Or(prover_state)) => {
-                Self::prover_response_or(instances, prover_state, challenge)
-            }
-            _ => Err(Error::InvalidInstanceWitnessPair),
-        }
+        // The state is zeroized when dropped at the end of this function.
+        self.prover_trial_response(&state, challenge)
     }
 
     fn verifier(
@@ -501,7 +1291,8 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocol
                 ComposedCommitment::Or(commitments),
                 ComposedResponse::Or(challenges, responses),
             ) => {
-                let last_challenge = *challenge - challenges.iter().sum::<G::Scalar>();
+                let last_challenge =
+                    last_or_challenge::<G>(self.composed_challenge_space(), challenge, challenges);
                 ps.iter()
                     .zip(commitments)
                     .zip(challenges.iter().chain(&Some(last_challenge)))
@@ -510,6 +1301,46 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocol
                         p.verifier(commitment, challenge, response)
                     })
             }
+            (
+                ComposedRelation::Threshold(threshold, ps),
+                ComposedCommitment::Threshold(commitments),
+                ComposedResponse::Threshold(challenges, responses),
+            ) => {
+                if !is_valid_threshold(*threshold, ps.len())
+                    || ps.len() != commitments.len()
+                    || ps.len() != responses.len()
+                    || ps.len() - threshold != challenges.len()
+                {
+                    return Err(Error::InvalidInstanceWitnessPair);
+                }
+                let challenges = threshold_challenges::<G>(challenge, challenges, ps.len());
+                ps.iter()
+                    .zip(commitments)
+                    .zip(&challenges)
+                    .zip(responses)
+                    .try_for_each(|(((p, commitment), challenge), response)| {
+                        p.verifier(commitment, challenge, response)
+                    })
+            }
+            (
+                ComposedRelation::SharedAnd(representatives, ps),
+                ComposedCommitment::And(commitments),
+                ComposedResponse::And(responses),
+            ) => {
+                if ps.len() != commitments.len() {
+                    return Err(Error::InvalidInstanceWitnessPair);
+                }
+                check_shared_responses(representatives, ps, responses)?;
+                ps.iter()
+                    .zip(commitments)
+                    .zip(responses)
+                    .try_for_each(|((p, c), r)| match (c, r) {
+                        (ComposedCommitment::Simple(c), ComposedResponse::Simple(r)) => {
+                            p.verifier(c, challenge, r)
+                        }
+                        _ => Err(Error::InvalidInstanceWitnessPair),
+                    })
+            }
             _ => Err(Error::InvalidInstanceWitnessPair),
         }
     }
@@ -520,68 +1351,57 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocol
                 p.serialize_commitment(c)
             }
             (ComposedRelation::And(ps), ComposedCommitment::And(commitments))
-            | (ComposedRelation::Or(ps), ComposedCommitment::Or(commitments)) => ps
+            | (ComposedRelation::Or(ps), ComposedCommitment::Or(commitments))
+            | (ComposedRelation::Threshold(_, ps), ComposedCommitment::Threshold(commitments)) => {
+                ps.iter()
+                    .zip(commitments)
+                    .flat_map(|(p, c)| p.serialize_commitment(c))
+                    .collect()
+            }
+            (ComposedRelation::SharedAnd(_, ps), ComposedCommitment::And(commitments)) => ps
                 .iter()
                 .zip(commitments)
-                .flat_map(|(p, c)| p.serialize_commitment(c))
+                .flat_map(|(p, c)| match c {
+                    ComposedCommitment::Simple(c) => p.serialize_commitment(c),
+                    _ => unreachable!(),
+                })
                 .collect(),
             _ => unreachable!(),
         }
     }
 
     fn serialize_challenge(&self, challenge: &Self::Challenge) -> Vec<u8> {
-        serialize_scalars::<G>(&[*challenge])
+        self.composed_challenge_space().serialize(challenge)
     }
 
+    /// The instance label is the structural encoding of the composition,
+    /// with the instance labels of its linear relations as leaves.
+    ///
+    /// Compositions of different shapes, such as an AND and an OR of the same relations,
+    /// have different instance labels.
     fn instance_label(&self) -> impl AsRef<[u8]> {
-        match self {
-            ComposedRelation::Simple(p) => {
-                let label = p.instance_label();
-                label.as_ref().to_vec()
-            }
-            ComposedRelation::And(ps) => {
-                let mut bytes = Vec::new();
-                for p in ps {
-                    bytes.extend(p.instance_label().as_ref());
-                }
-                bytes
-            }
-            ComposedRelation::Or(ps) => {
-                let mut bytes = Vec::new();
-                for p in ps {
-                    bytes.extend(p.instance_label().as_ref());
-                }
-                bytes
-            }
-        }
+        let mut bytes = Vec::new();
+        self.encode_structure(&mut bytes, &|p| p.label());
+        bytes
     }
 
+    /// The protocol identifier is the SHA3-256 hash of the structural encoding of the composition,
+    /// with the protocol identifiers of its linear relations as leaves.
     fn protocol_identifier(&self) -> impl AsRef<[u8]> {
-        let mut hasher = Sha3_256::new();
+        let mut bytes = Vec::new();
+        self.encode_structure(&mut bytes, &|p| p.protocol_identifier().as_ref().to_vec());
+        Sha3_256::digest(bytes)
+    }
 
-        match self {
-            ComposedRelation::Simple(p) => {
-                // take the digest of the simple protocol id
-                hasher.update([0u8; 32]);
-                hasher.update(p.protocol_identifier());
-            }
-            ComposedRelation::And(protocols) => {
-                let mut hasher = Sha3_256::new();
-                hasher.update([1u8; 32]);
-                for p in protocols {
-                    hasher.update(p.protocol_identifier());
-                }
-            }
-            ComposedRelation::Or(protocols) => {
-                let mut hasher = Sha3_256::new();
-                hasher.update([2u8; 32]);
-                for p in protocols {
-                    hasher.update(p.protocol_identifier());
-                }
-            }
-        }
+    fn challenge_space(&self) -> ChallengeSpace {
+        self.composed_challenge_space()
+    }
 
-        hasher.finalize()
+    fn serialize_witness(&self, witness: &Self::Witness) -> Option<Zeroizing<Vec<u8>>> {
+        // The buffer is allocated at its final size, so that no reallocation copies the witness.
+        let mut bytes = Zeroizing::new(Vec::with_capacity(witness.serialized_len()));
+        witness.serialize_into(&mut bytes);
+        Some(bytes)
     }
 
     fn serialize_response(&self, response: &Self::Response) -> Vec<u8> {
@@ -594,12 +1414,17 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocol
                 }
                 bytes
             }
-            (ComposedRelation::Or(instances), ComposedResponse::Or(challenges, responses)) => {
+            (ComposedRelation::Or(instances), ComposedResponse::Or(challenges, responses))
+            | (
+                ComposedRelation::Threshold(_, instances),
+                ComposedResponse::Threshold(challenges, responses),
+            ) => {
                 let mut bytes = Vec::new();
 
                 // write challenges first
-                for (x, c) in instances.iter().zip(challenges) {
-                    bytes.extend(x.serialize_challenge(c));
+                let challenge_space = self.composed_challenge_space();
+                for c in challenges {
+                    bytes.extend(challenge_space.serialize(c));
                 }
 
                 for (x, r) in instances.iter().zip(responses) {
@@ -608,6 +1433,34 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocol
 
                 bytes
             }
+            (
+                ComposedRelation::SharedAnd(representatives, ps),
+                ComposedResponse::And(responses),
+            ) => {
+                // Shared scalars have equal responses, only the one of their representative is sent.
+                let Some(scalars) = concat_shared_scalars(ps, responses, |r| match r {
+                    ComposedResponse::Simple(r) => Some(r.as_slice()),
+                    _ => None,
+                }) else {
+                    // Responses which do not match the relations cannot verify:
+                    // their simple responses are sent as they are, and the others are skipped.
+                    return ps
+                        .iter()
+                        .zip(responses)
+                        .flat_map(|(p, r)| match r {
+                            ComposedResponse::Simple(r) => p.serialize_response(r),
+                            _ => Vec::new(),
+                        })
+                        .collect();
+                };
+                let representative_scalars: Vec<_> = scalars
+                    .iter()
+                    .zip(representatives.iter().enumerate())
+                    .filter(|(_, (i, r))| i == *r)
+                    .map(|(scalar, _)| *scalar)
+                    .collect();
+                serialize_scalars::<G>(&representative_scalars)
+            }
             _ => unreachable!(),
         }
     }
@@ -618,7 +1471,9 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocol
                 let c = p.deserialize_commitment(data)?;
                 Ok(ComposedCommitment::Simple(c))
             }
-            ComposedRelation::And(ps) | ComposedRelation::Or(ps) => {
+            ComposedRelation::And(ps)
+            | ComposedRelation::Or(ps)
+            | ComposedRelation::Threshold(_, ps) => {
                 let mut cursor = 0;
                 let mut commitments = Vec::with_capacity(ps.len());
 
@@ -632,15 +1487,29 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocol
                 Ok(match self {
                     ComposedRelation::And(_) => ComposedCommitment::And(commitments),
                     ComposedRelation::Or(_) => ComposedCommitment::Or(commitments),
+                    ComposedRelation::Threshold(..) => ComposedCommitment::Threshold(commitments),
                     _ => unreachable!(),
                 })
             }
+            ComposedRelation::SharedAnd(_, ps) => {
+                let mut cursor = 0;
+                let mut commitments = Vec::with_capacity(ps.len());
+
+                for p in ps {
+                    let c = p.deserialize_commitment(&data[cursor..])?;
+                    cursor += p.serialize_commitment(&c).len();
+                    commitments.push(ComposedCommitment::Simple(c));
+                }
+
+                Ok(ComposedCommitment::And(commitments))
+            }
         }
     }
 
     fn deserialize_challenge(&self, data: &[u8]) -> Result<Self::Challenge, Error> {
-        let scalars = deserialize_scalars::<G>(data, 1).ok_or(Error::VerificationFailure)?;
-        Ok(scalars[0])
+        self.composed_challenge_space()
+            .deserialize(data)
+            .ok_or(Error::VerificationFailure)
     }
 
     fn deserialize_response(&self, data: &[u8]) -> Result<Self::Response, Error> {
@@ -661,23 +1530,35 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocol
                 Ok(ComposedResponse::And(responses))
             }
             ComposedRelation::Or(ps) => {
-                let ch_bytes_len = composed_challenge_size::<G>();
-                let challenges_size = (ps.len() - 1) * ch_bytes_len;
-                let challenges_bytes = &data[..challenges_size];
-                let response_bytes = &data[challenges_size..];
-                let challenges = deserialize_scalars::<G>(challenges_bytes, ps.len() - 1)
-                    .ok_or(Error::VerificationFailure)?;
-
-                let mut cursor = 0;
-                let mut responses = Vec::with_capacity(ps.len());
-                for p in ps {
-                    let r = p.deserialize_response(&response_bytes[cursor..])?;
-                    let size = p.serialize_response(&r).len();
-                    cursor += size;
-                    responses.push(r);
-                }
+                let (challenges, responses) =
+                    self.deserialize_challenges_and_responses(ps, ps.len() - 1, data)?;
                 Ok(ComposedResponse::Or(challenges, responses))
             }
+            ComposedRelation::Threshold(threshold, ps) => {
+                if !is_valid_threshold(*threshold, ps.len()) {
+                    return Err(Error::VerificationFailure);
+                }
+                let (challenges, responses) =
+                    self.deserialize_challenges_and_responses(ps, ps.len() - threshold, data)?;
+                Ok(ComposedResponse::Threshold(challenges, responses))
+            }
+            ComposedRelation::SharedAnd(representatives, ps) => {
+                if !is_valid_sharing(representatives, ps) {
+                    return Err(Error::VerificationFailure);
+                }
+                let count = representatives
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, r)| i == *r)
+                    .count();
+                let representative_scalars =
+                    deserialize_scalars::<G>(data, count).ok_or(Error::VerificationFailure)?;
+                let mut representative_scalars = representative_scalars.into_iter();
+                let scalars = expand_shared_scalars(representatives, || {
+                    representative_scalars.next().unwrap()
+                });
+                Ok(ComposedResponse::And(split_shared_responses(ps, &scalars)))
+            }
         }
     }
 }
@@ -703,7 +1584,8 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocolSimu
                 Ok(ComposedCommitment::And(commitments))
             }
             (ComposedRelation::Or(ps), ComposedResponse::Or(challenges, rs)) => {
-                let last_challenge = *challenge - challenges.iter().sum::<G::Scalar>();
+                let last_challenge =
+                    last_or_challenge::<G>(self.composed_challenge_space(), challenge, challenges);
                 let commitments = ps
                     .iter()
                     .zip(challenges.iter().chain(&Some(last_challenge)))
@@ -712,6 +1594,37 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocolSimu
                     .collect::<Result<Vec<_>, _>>()?;
                 Ok(ComposedCommitment::Or(commitments))
             }
+            (
+                ComposedRelation::Threshold(threshold, ps),
+                ComposedResponse::Threshold(challenges, rs),
+            ) => {
+                if !is_valid_threshold(*threshold, ps.len())
+                    || ps.len() - threshold != challenges.len()
+                {
+                    return Err(Error::InvalidInstanceWitnessPair);
+                }
+                let challenges = threshold_challenges::<G>(challenge, challenges, ps.len());
+                let commitments = ps
+                    .iter()
+                    .zip(&challenges)
+                    .zip(rs)
+                    .map(|((p, ch), r)| p.simulate_commitment(ch, r))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(ComposedCommitment::Threshold(commitments))
+            }
+            (ComposedRelation::SharedAnd(_, ps), ComposedResponse::And(rs)) => {
+                let commitments = ps
+                    .iter()
+                    .zip(rs)
+                    .map(|(p, r)| match r {
+                        ComposedResponse::Simple(r) => Ok(ComposedCommitment::Simple(
+                            p.simulate_commitment(challenge, r)?,
+                        )),
+                        _ => Err(Error::InvalidInstanceWitnessPair),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(ComposedCommitment::And(commitments))
+            }
             _ => unreachable!(),
         }
     }
@@ -725,14 +1638,28 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocolSimu
             ComposedRelation::Or(ps) => {
                 let mut challenges = Vec::with_capacity(ps.len());
                 let mut responses = Vec::with_capacity(ps.len());
-                for _ in 0..ps.len() {
-                    challenges.push(G::Scalar::random(&mut *rng));
+                let challenge_space = self.composed_challenge_space();
+                // The challenge of the last branch is not part of the response.
+                for _ in 1..ps.len() {
+                    challenges.push(challenge_space.random(&mut *rng));
                 }
                 for p in ps.iter() {
                     responses.push(p.simulate_response(&mut *rng));
                 }
                 ComposedResponse::Or(challenges, responses)
             }
+            ComposedRelation::Threshold(threshold, ps) => {
+                let challenges = (0..ps.len().saturating_sub(*threshold))
+                    .map(|_| G::Scalar::random(&mut *rng))
+                    .collect();
+                let responses = ps.iter().map(|p| p.simulate_response(&mut *rng)).collect();
+                ComposedResponse::Threshold(challenges, responses)
+            }
+            ComposedRelation::SharedAnd(representatives, ps) => {
+                let scalars =
+                    expand_shared_scalars(representatives, || G::Scalar::random(&mut *rng));
+                ComposedResponse::And(split_shared_responses(ps, &scalars))
+            }
         }
     }
 
@@ -750,7 +1677,7 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocolSimu
                 ))
             }
             ComposedRelation::And(ps) => {
-                let challenge = G::Scalar::random(&mut *rng);
+                let challenge = self.composed_challenge_space().random(&mut *rng);
                 let mut responses = Vec::with_capacity(ps.len());
                 for p in ps.iter() {
                     responses.push(p.simulate_response(&mut *rng));
@@ -778,13 +1705,374 @@ impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocolSimu
                     challenges.push(ch);
                     responses.push(r);
                 }
-                let challenge = challenges.iter().sum();
+                let challenge_space = self.composed_challenge_space();
+                let challenge = challenges
+                    .iter()
+                    .fold(G::Scalar::ZERO, |acc, c| challenge_space.combine(&acc, c));
+                // The challenge of the last branch is not part of the response.
+                challenges.pop();
                 Ok((
                     ComposedCommitment::Or(commitments),
                     challenge,
                     ComposedResponse::Or(challenges, responses),
                 ))
             }
+            ComposedRelation::Threshold(threshold, ps) => {
+                if !is_valid_threshold(*threshold, ps.len()) {
+                    return Err(Error::InvalidInstanceWitnessPair);
+                }
+                let challenge = G::Scalar::random(&mut *rng);
+                let response = self.simulate_response(&mut *rng);
+                let commitment = self.simulate_commitment(&challenge, &response)?;
+                Ok((commitment, challenge, response))
+            }
+            ComposedRelation::SharedAnd(representatives, ps) => {
+                if !is_valid_sharing(representatives, ps) {
+                    return Err(Error::InvalidInstanceWitnessPair);
+                }
+                let challenge = self.composed_challenge_space().random(&mut *rng);
+                let response = self.simulate_response(&mut *rng);
+                let commitment = self.simulate_commitment(&challenge, &response)?;
+                Ok((commitment, challenge, response))
+            }
+        }
+    }
+}
+
+impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> TrialResponse
+    for ComposedRelation<G>
+{
+    fn prover_trial_response(
+        &self,
+        state: &Self::ProverState,
+        challenge: &Self::Challenge,
+    ) -> Result<Self::Response, Error> {
+        match (self, state) {
+            (ComposedRelation::Simple(instance), ComposedProverState::Simple(state)) => {
+                Self::prover_response_simple(instance, state, challenge)
+            }
+            (ComposedRelation::And(instances), ComposedProverState::And(prover_state)) => {
+                Self::prover_response_and(instances, prover_state, challenge)
+            }
+            (ComposedRelation::Or(instances), ComposedProverState::Or(prover_state)) => {
+                Self::prover_response_or(
+                    instances,
+                    prover_state,
+                    challenge,
+                    self.composed_challenge_space(),
+                )
+            }
+            (
+                ComposedRelation::Threshold(threshold, instances),
+                ComposedProverState::Threshold(prover_state),
+            ) => Self::prover_response_threshold(*threshold, instances, prover_state, challenge),
+            (ComposedRelation::SharedAnd(_, instances), ComposedProverState::And(prover_state)) => {
+                Self::prover_response_shared_and(instances, prover_state, challenge)
+            }
+            _ => Err(Error::InvalidInstanceWitnessPair),
+        }
+    }
+}
+
+impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocolTrialResponse
+    for ComposedRelation<G>
+{
+}
+
+impl<G: PrimeGroup + ConstantTimeEq + ConditionallySelectable> SigmaProtocolBatchVerifier
+    for ComposedRelation<G>
+{
+    type Group = G;
+
+    fn verification_equations(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Self::Challenge,
+        response: &Self::Response,
+    ) -> Result<Vec<VerificationEquation<G>>, Error> {
+        match (self, commitment, response) {
+            (
+                ComposedRelation::Simple(p),
+                ComposedCommitment::Simple(c),
+                ComposedResponse::Simple(r),
+            ) => p.verification_equations(c, challenge, r),
+            (
+                ComposedRelation::And(ps),
+                ComposedCommitment::And(commitments),
+                ComposedResponse::And(responses),
+            ) => {
+                if ps.len() != commitments.len() || ps.len() != responses.len() {
+                    return Err(Error::InvalidInstanceWitnessPair);
+                }
+                let mut equations = Vec::new();
+                for ((p, c), r) in ps.iter().zip(commitments).zip(responses) {
+                    equations.extend(p.verification_equations(c, challenge, r)?);
+                }
+                Ok(equations)
+            }
+            (
+                ComposedRelation::Or(ps),
+                ComposedCommitment::Or(commitments),
+                ComposedResponse::Or(challenges, responses),
+            ) => {
+                if ps.len() != commitments.len()
+                    || ps.len() != responses.len()
+                    || ps.len() != challenges.len() + 1
+                {
+                    return Err(Error::InvalidInstanceWitnessPair);
+                }
+                let last_challenge =
+                    last_or_challenge::<G>(self.composed_challenge_space(), challenge, challenges);
+                let mut equations = Vec::new();
+                for (((p, c), ch), r) in ps
+                    .iter()
+                    .zip(commitments)
+                    .zip(challenges.iter().chain(&Some(last_challenge)))
+                    .zip(responses)
+                {
+                    equations.extend(p.verification_equations(c, ch, r)?);
+                }
+                Ok(equations)
+            }
+            (
+                ComposedRelation::Threshold(threshold, ps),
+                ComposedCommitment::Threshold(commitments),
+                ComposedResponse::Threshold(challenges, responses),
+            ) => {
+                if !is_valid_threshold(*threshold, ps.len())
+                    || ps.len() != commitments.len()
+                    || ps.len() != responses.len()
+                    || ps.len() - threshold != challenges.len()
+                {
+                    return Err(Error::InvalidInstanceWitnessPair);
+                }
+                let challenges = threshold_challenges::<G>(challenge, challenges, ps.len());
+                let mut equations = Vec::new();
+                for (((p, c), ch), r) in ps.iter().zip(commitments).zip(&challenges).zip(responses)
+                {
+                    equations.extend(p.verification_equations(c, ch, r)?);
+                }
+                Ok(equations)
+            }
+            (
+                ComposedRelation::SharedAnd(representatives, ps),
+                ComposedCommitment::And(commitments),
+                ComposedResponse::And(responses),
+            ) => {
+                if ps.len() != commitments.len() {
+                    return Err(Error::InvalidInstanceWitnessPair);
+                }
+                check_shared_responses(representatives, ps, responses)?;
+                let mut equations = Vec::new();
+                for ((p, c), r) in ps.iter().zip(commitments).zip(responses) {
+                    match (c, r) {
+                        (ComposedCommitment::Simple(c), ComposedResponse::Simple(r)) => {
+                            equations.extend(p.verification_equations(c, challenge, r)?)
+                        }
+                        _ => return Err(Error::InvalidInstanceWitnessPair),
+                    }
+                }
+                Ok(equations)
+            }
+            _ => Err(Error::InvalidInstanceWitnessPair),
+        }
+    }
+}
+
+/// Maps the witnesses of a composition to the witnesses of its optimized form.
+///
+/// It is returned by [`ComposedRelation::optimize`], and mirrors the optimized composition:
+/// each of its nodes builds the witness of a node of the optimized composition
+/// from the witness of the original one. Nodes refer to the original witness by paths,
+/// the indices of the children to follow from its root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WitnessTransformer {
+    /// The witness at the given path, unchanged.
+    Keep(Vec<usize>),
+    /// The concatenation of the simple witnesses at the given paths,
+    /// each truncated to the given number of scalars.
+    Merge(Vec<(Vec<usize>, usize)>),
+    And(Vec<WitnessTransformer>),
+    Or(Vec<WitnessTransformer>),
+    Threshold(Vec<WitnessTransformer>),
+}
+
+impl WitnessTransformer {
+    /// Builds the witness of the optimized composition from the witness of the original one.
+    ///
+    /// # Errors
+    /// - Returns [`Error::InvalidInstanceWitnessPair`] if the witness does not have the shape
+    ///   of the original composition.
+    pub fn transform<G: PrimeGroup>(
+        &self,
+        witness: &ComposedWitness<G>,
+    ) -> Result<ComposedWitness<G>, Error> {
+        let children = |transformers: &[WitnessTransformer]| {
+            transformers
+                .iter()
+                .map(|t| t.transform(witness))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        match self {
+            WitnessTransformer::Keep(path) => Ok(Self::copy(Self::find(witness, path)?)),
+            WitnessTransformer::Merge(parts) => {
+                let mut scalars = Vec::new();
+                for (path, num_scalars) in parts {
+                    match Self::find(witness, path)? {
+                        ComposedWitness::Simple(part) if part.len() >= *num_scalars => {
+                            scalars.extend_from_slice(&part[..*num_scalars])
+                        }
+                        _ => {
+                            zeroize_scalars(&mut scalars);
+                            return Err(Error::InvalidInstanceWitnessPair);
+                        }
+                    }
+                }
+                Ok(ComposedWitness::Simple(scalars))
+            }
+            WitnessTransformer::And(ts) => Ok(ComposedWitness::And(children(ts)?)),
+            WitnessTransformer::Or(ts) => Ok(ComposedWitness::Or(children(ts)?)),
+            WitnessTransformer::Threshold(ts) => Ok(ComposedWitness::Threshold(children(ts)?)),
+        }
+    }
+
+    /// Copies `witness`, which is not [`Clone`] so that its scalars are not copied by accident.
+    fn copy<G: PrimeGroup>(witness: &ComposedWitness<G>) -> ComposedWitness<G> {
+        let copy_all = |ws: &[ComposedWitness<G>]| ws.iter().map(Self::copy).collect();
+        match witness {
+            ComposedWitness::Simple(scalars) => ComposedWitness::Simple(scalars.clone()),
+            ComposedWitness::And(ws) => ComposedWitness::And(copy_all(ws)),
+            ComposedWitness::Or(ws) => ComposedWitness::Or(copy_all(ws)),
+            ComposedWitness::Threshold(ws) => ComposedWitness::Threshold(copy_all(ws)),
+        }
+    }
+
+    /// Returns the witness at `path` in `witness`.
+    fn find<'a, G: PrimeGroup>(
+        mut witness: &'a ComposedWitness<G>,
+        path: &[usize],
+    ) -> Result<&'a ComposedWitness<G>, Error> {
+        for &i in path {
+            witness = match witness {
+                ComposedWitness::And(ws)
+                | ComposedWitness::Or(ws)
+                | ComposedWitness::Threshold(ws) => {
+                    ws.get(i).ok_or(Error::InvalidInstanceWitnessPair)?
+                }
+                ComposedWitness::Simple(_) => return Err(Error::InvalidInstanceWitnessPair),
+            };
+        }
+        Ok(witness)
+    }
+}
+
+impl<G: PrimeGroup> ComposedRelation<G> {
+    /// Optimizes the composition, so that its proofs are shorter and faster to verify.
+    ///
+    /// Nested AND and OR nodes are flattened into their parent of the same kind,
+    /// the simple relations of each AND are merged into a single [`CanonicalLinearRelation`]
+    /// with disjoint variables, verified with a single multi-scalar multiplication,
+    /// and ANDs and ORs of a single relation are replaced by that relation.
+    /// The children of thresholds are optimized, and shared ANDs are kept as is.
+    ///
+    /// The optimized composition is a different protocol, with a different IV,
+    /// proving the same statement. Its witnesses are obtained from the witnesses
+    /// of the original composition with the returned [`WitnessTransformer`].
+    pub fn optimize(self) -> (Self, WitnessTransformer) {
+        self.optimize_at(&mut Vec::new())
+    }
+
+    /// Optimizes the composition found at `path` in the original composition.
+    fn optimize_at(self, path: &mut Vec<usize>) -> (Self, WitnessTransformer) {
+        let mut optimize_children = |ps: Vec<ComposedRelation<G>>| {
+            ps.into_iter()
+                .enumerate()
+                .map(|(i, p)| {
+                    path.push(i);
+                    let optimized = p.optimize_at(path);
+                    path.pop();
+                    optimized
+                })
+                .collect::<Vec<_>>()
+        };
+        match self {
+            ComposedRelation::Simple(_) | ComposedRelation::SharedAnd(..) => {
+                (self, WitnessTransformer::Keep(path.clone()))
+            }
+            ComposedRelation::And(ps) => {
+                let mut simple = Vec::new();
+                let mut parts = Vec::new();
+                let mut others = Vec::new();
+                let mut flattened = Vec::new();
+                for child in optimize_children(ps) {
+                    match child {
+                        (ComposedRelation::And(qs), WitnessTransformer::And(ts)) => {
+                            flattened.extend(qs.into_iter().zip(ts))
+                        }
+                        child => flattened.push(child),
+                    }
+                }
+                for child in flattened {
+                    match child {
+                        (ComposedRelation::Simple(p), WitnessTransformer::Keep(path)) => {
+                            parts.push((path, p.num_scalars));
+                            simple.push(p);
+                        }
+                        (ComposedRelation::Simple(p), WitnessTransformer::Merge(ps)) => {
+                            parts.extend(ps);
+                            simple.push(p);
+                        }
+                        child => others.push(child),
+                    }
+                }
+
+                let mut children = Vec::with_capacity(others.len() + 1);
+                match simple.len() {
+                    0 => {}
+                    1 => {
+                        let (path, _) = parts.pop().unwrap();
+                        children.push((
+                            ComposedRelation::Simple(simple.pop().unwrap()),
+                            WitnessTransformer::Keep(path),
+                        ));
+                    }
+                    _ => children.push((
+                        ComposedRelation::Simple(CanonicalLinearRelation::merge(&simple)),
+                        WitnessTransformer::Merge(parts),
+                    )),
+                }
+                children.extend(others);
+                if children.len() == 1 {
+                    children.pop().unwrap()
+                } else {
+                    let (ps, ts) = children.into_iter().unzip();
+                    (ComposedRelation::And(ps), WitnessTransformer::And(ts))
+                }
+            }
+            ComposedRelation::Or(ps) => {
+                let mut children = Vec::new();
+                for child in optimize_children(ps) {
+                    match child {
+                        (ComposedRelation::Or(qs), WitnessTransformer::Or(ts)) => {
+                            children.extend(qs.into_iter().zip(ts))
+                        }
+                        child => children.push(child),
+                    }
+                }
+                if children.len() == 1 {
+                    children.pop().unwrap()
+                } else {
+                    let (ps, ts) = children.into_iter().unzip();
+                    (ComposedRelation::Or(ps), WitnessTransformer::Or(ts))
+                }
+            }
+            ComposedRelation::Threshold(threshold, ps) => {
+                let (ps, ts) = optimize_children(ps).into_iter().unzip();
+                (
+                    ComposedRelation::Threshold(threshold, ps),
+                    WitnessTransformer::Threshold(ts),
+                )
+            }
         }
     }
 }
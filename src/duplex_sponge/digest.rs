@@ -0,0 +1,103 @@
+//! Hash-chain duplex sponge implementation
+//!
+//! This module implements a duplex sponge construction from any hash function
+//! implementing [`Digest`], for hash functions that are not built on a permutation,
+//! such as SHA-256 or BLAKE2.
+//!
+//! The sponge keeps a chaining value, and each byte string below is hashed with a one-byte tag:
+//! - the initial chaining value is `H(0x00 || iv)`,
+//! - an absorb phase hashes `0x01 || chaining value || squeezed || input`, where `squeezed`
+//!   is the number of bytes squeezed in the previous squeeze phase as a 64-bit big-endian integer,
+//!   and the result becomes the new chaining value when squeezing starts,
+//! - the output of a squeeze phase is `H(0x02 || chaining value || 0) || H(0x02 || chaining value || 1) || ...`,
+//!   with counters as 64-bit big-endian integers.
+
+use crate::duplex_sponge::DuplexSpongeInterface;
+use ::digest::{Digest, Output};
+use alloc::vec::Vec;
+
+const IV_TAG: u8 = 0x00;
+const ABSORB_TAG: u8 = 0x01;
+const SQUEEZE_TAG: u8 = 0x02;
+
+/// Duplex sponge construction chaining calls to the hash function `D`.
+#[derive(Clone)]
+pub struct DigestDuplexSponge<D: Digest> {
+    chaining_value: Output<D>,
+    hasher: D,
+    squeezing: bool,
+    counter: u64,
+    squeezed: u64,
+    output_buffer: Vec<u8>,
+}
+
+impl<D: Digest> DigestDuplexSponge<D> {
+    fn start_absorbing(&mut self) {
+        self.hasher = D::new()
+            .chain_update([ABSORB_TAG])
+            .chain_update(&self.chaining_value)
+            .chain_update(self.squeezed.to_be_bytes());
+        self.squeezing = false;
+    }
+
+    fn start_squeezing(&mut self) {
+        self.chaining_value = core::mem::replace(&mut self.hasher, D::new()).finalize();
+        self.squeezing = true;
+        self.counter = 0;
+        self.squeezed = 0;
+        self.output_buffer.clear();
+    }
+}
+
+impl<D: Digest> DuplexSpongeInterface for DigestDuplexSponge<D> {
+    fn new(iv: [u8; 64]) -> Self {
+        let mut sponge = Self {
+            chaining_value: D::new().chain_update([IV_TAG]).chain_update(iv).finalize(),
+            hasher: D::new(),
+            squeezing: false,
+            counter: 0,
+            squeezed: 0,
+            output_buffer: Vec::new(),
+        };
+        sponge.start_absorbing();
+        sponge
+    }
+
+    fn absorb(&mut self, input: &[u8]) {
+        if input.is_empty() {
+            return;
+        }
+        if self.squeezing {
+            self.start_absorbing();
+        }
+        self.hasher.update(input);
+    }
+
+    fn squeeze_into(&mut self, output: &mut [u8]) {
+        if output.is_empty() {
+            return;
+        }
+        if !self.squeezing {
+            self.start_squeezing();
+        }
+
+        let mut written = 0;
+        while written != output.len() {
+            if self.output_buffer.is_empty() {
+                self.output_buffer = D::new()
+                    .chain_update([SQUEEZE_TAG])
+                    .chain_update(&self.chaining_value)
+                    .chain_update(self.counter.to_be_bytes())
+                    .finalize()
+                    .to_vec();
+                self.counter += 1;
+            }
+            let chunk_size = usize::min(self.output_buffer.len(), output.len() - written);
+            output[written..written + chunk_size]
+                .copy_from_slice(&self.output_buffer[..chunk_size]);
+            self.output_buffer.drain(..chunk_size);
+            written += chunk_size;
+        }
+        self.squeezed += output.len() as u64;
+    }
+}
@@ -3,13 +3,18 @@
 //! This module implements a duplex sponge construction using the Keccak-f\[1600\] permutation.
 //! It is designed to match test vectors from the original Sage implementation.
 
+use crate::duplex_sponge::snapshot::{decode_snapshot, encode_snapshot};
 use crate::duplex_sponge::DuplexSpongeInterface;
+use crate::errors::Error;
 use alloc::vec::Vec;
 use zerocopy::IntoBytes;
 
 const RATE: usize = 136;
 const LENGTH: usize = 136 + 64;
 
+/// Kind of sponge recorded in snapshots.
+const SNAPSHOT_KIND: &[u8] = b"keccak-f1600-overwrite";
+
 /// Low-level Keccak-f\[1600\] state representation.
 #[derive(Clone, Default)]
 pub struct KeccakPermutationState([u64; LENGTH / 8]);
@@ -79,23 +84,46 @@ impl DuplexSpongeInterface for KeccakDuplexSponge {
         }
     }
 
-    fn squeeze(&mut self, mut length: usize) -> Vec<u8> {
-        let mut output = Vec::new();
-        while length != 0 {
+    fn squeeze_into(&mut self, output: &mut [u8]) {
+        let mut written = 0;
+        while written != output.len() {
             if self.squeeze_index == RATE {
                 self.state.permute();
                 self.squeeze_index = 0;
                 self.absorb_index = 0;
             }
 
-            let chunk_size = usize::min(RATE - self.squeeze_index, length);
-            output.extend_from_slice(
-                &self.state.as_mut()[self.squeeze_index..self.squeeze_index + chunk_size],
+            let chunk_size = usize::min(RATE - self.squeeze_index, output.len() - written);
+            output[written..written + chunk_size].copy_from_slice(
+                &self.state.as_ref()[self.squeeze_index..self.squeeze_index + chunk_size],
             );
             self.squeeze_index += chunk_size;
-            length -= chunk_size;
+            written += chunk_size;
         }
-        output
+    }
+
+    fn export_state(&self) -> Result<Vec<u8>, Error> {
+        let mut state = self.state.as_ref().to_vec();
+        state.extend_from_slice(&(self.absorb_index as u32).to_be_bytes());
+        state.extend_from_slice(&(self.squeeze_index as u32).to_be_bytes());
+        Ok(encode_snapshot(SNAPSHOT_KIND, &state))
+    }
+
+    fn import_state(snapshot: &[u8]) -> Result<Self, Error> {
+        let mut reader = decode_snapshot(SNAPSHOT_KIND, snapshot)?;
+        let mut state = KeccakPermutationState::default();
+        state.as_mut().copy_from_slice(reader.read_bytes(LENGTH)?);
+        let absorb_index = reader.read_u32()?;
+        let squeeze_index = reader.read_u32()?;
+        reader.finish()?;
+        if absorb_index > RATE || squeeze_index > RATE {
+            return Err(Error::InvalidSnapshot);
+        }
+        Ok(Self {
+            state,
+            absorb_index,
+            squeeze_index,
+        })
     }
 }
 
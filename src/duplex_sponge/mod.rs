@@ -4,10 +4,24 @@
 //! a generic interface for cryptographic sponge functions that support
 //! duplex operations: alternating absorb and squeeze phases.
 
+use crate::errors::Error;
+use alloc::vec;
 use alloc::vec::Vec;
 
+pub mod ascon;
+pub mod digest;
 pub mod keccak;
+pub mod poseidon;
 pub mod shake;
+pub(crate) mod snapshot;
+pub mod xof;
+
+/// Whether a sponge is absorbing or squeezing, and its position in the current block.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Phase {
+    Absorbing(usize),
+    Squeezing(usize),
+}
 
 /// A trait defining the behavior of a duplex sponge construction.
 ///
@@ -28,5 +42,33 @@ pub trait DuplexSpongeInterface {
     fn absorb(&mut self, input: &[u8]);
 
     /// Squeezes output data from the sponge state.
-    fn squeeze(&mut self, length: usize) -> Vec<u8>;
+    fn squeeze(&mut self, length: usize) -> Vec<u8> {
+        let mut output = vec![0u8; length];
+        self.squeeze_into(&mut output);
+        output
+    }
+
+    /// Squeezes `output.len()` bytes of output data from the sponge state into `output`.
+    ///
+    /// Unlike [`DuplexSpongeInterface::squeeze`], this does not allocate.
+    fn squeeze_into(&mut self, output: &mut [u8]);
+
+    /// Exports a versioned snapshot of the sponge state,
+    /// from which [`DuplexSpongeInterface::import_state`] resumes the sponge.
+    ///
+    /// Returns [`Error::UnsupportedSnapshot`] if the sponge cannot export its state.
+    fn export_state(&self) -> Result<Vec<u8>, Error> {
+        Err(Error::UnsupportedSnapshot)
+    }
+
+    /// Restores a sponge from a snapshot exported by [`DuplexSpongeInterface::export_state`].
+    ///
+    /// Returns [`Error::InvalidSnapshot`] if the snapshot is truncated, corrupted,
+    /// or was exported by another kind of sponge.
+    fn import_state(_snapshot: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Err(Error::UnsupportedSnapshot)
+    }
 }
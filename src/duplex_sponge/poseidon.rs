@@ -0,0 +1,568 @@
+//! Poseidon-based duplex sponge implementation
+//!
+//! This module implements a duplex sponge construction over a prime field,
+//! using the Poseidon permutation with the round constants and MDS matrix
+//! generated by the Grain LFSR of the Poseidon reference implementation.
+//!
+//! The sponge absorbs and squeezes field elements natively, which makes it cheap
+//! to recompute inside arithmetic circuits. Bytes are packed into field elements.
+
+use crate::codec::cardinal;
+use crate::duplex_sponge::snapshot::{decode_snapshot, encode_snapshot, write_phase};
+use crate::duplex_sponge::{DuplexSpongeInterface, Phase};
+use crate::errors::Error;
+use alloc::vec;
+use alloc::vec::Vec;
+use ff::PrimeField;
+use num_bigint::BigUint;
+
+/// Width of the default permutation, with a rate of 2 field elements and a capacity of 1.
+const WIDTH: usize = 3;
+
+/// Number of full rounds of the default permutation.
+const FULL_ROUNDS: usize = 8;
+
+/// Number of partial rounds of the default permutation.
+const PARTIAL_ROUNDS: usize = 57;
+
+/// Exponent of the S-box of the default permutation.
+const ALPHA: u64 = 5;
+
+/// Number of field elements of the state that are never absorbed into or squeezed from.
+const CAPACITY: usize = 1;
+
+/// Number of output bits of a squeezed field element discarded to make the remaining bytes uniform.
+const SQUEEZE_SECURITY_MARGIN: usize = 128;
+
+/// Largest width accepted when importing a snapshot.
+const MAX_SNAPSHOT_WIDTH: usize = 24;
+
+/// Largest number of rounds accepted when importing a snapshot.
+const MAX_SNAPSHOT_ROUNDS: usize = 256;
+
+/// The parameters of a Poseidon permutation over the field `F`.
+///
+/// The round constants and the MDS matrix are generated with the Grain LFSR,
+/// as in the Poseidon reference implementation, from the field size, the width
+/// and the number of rounds.
+/// The additional security checks on the MDS matrix of the reference implementation are not run:
+/// the parameters match the reference ones whenever the first matrix generated passes them.
+#[derive(Clone, Debug)]
+pub struct PoseidonParameters<F: PrimeField> {
+    width: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+    alpha: u64,
+    round_constants: Vec<F>,
+    mds: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> PoseidonParameters<F> {
+    /// Generates the parameters of a Poseidon permutation with the S-box `x^alpha`.
+    ///
+    /// # Panics
+    /// Panics if `full_rounds` is odd, or if `x^alpha` is not a permutation of the field,
+    /// i.e. if `alpha` and the order of the field minus one are not coprime.
+    pub fn new(width: usize, full_rounds: usize, partial_rounds: usize, alpha: u64) -> Self {
+        assert!(
+            full_rounds.is_multiple_of(2),
+            "the number of full rounds must be even"
+        );
+        assert!(
+            is_permutation_exponent::<F>(alpha),
+            "x^alpha must be a permutation of the field"
+        );
+        let modulus = cardinal::<F>();
+
+        let field_size = modulus.bits() as usize;
+        let mut lfsr = GrainLfsr::new(field_size, width, full_rounds, partial_rounds);
+        let round_constants = (0..(full_rounds + partial_rounds) * width)
+            .map(|_| loop {
+                let value = lfsr.next_integer(field_size);
+                if value < modulus {
+                    break integer_to_field(&value);
+                }
+            })
+            .collect();
+        let mds = loop {
+            let values = (0..2 * width)
+                .map(|_| integer_to_field::<F>(&(lfsr.next_integer(field_size) % &modulus)))
+                .collect::<Vec<_>>();
+            let distinct = values
+                .iter()
+                .enumerate()
+                .all(|(i, x)| values[i + 1..].iter().all(|y| x != y));
+            if !distinct {
+                continue;
+            }
+            // Cauchy matrix 1 / (x_i + y_j), regenerated if an entry is not invertible.
+            let (xs, ys) = values.split_at(width);
+            let mds = xs
+                .iter()
+                .map(|x| {
+                    ys.iter()
+                        .map(|y| Option::from((*x + y).invert()))
+                        .collect::<Option<Vec<F>>>()
+                })
+                .collect::<Option<Vec<_>>>();
+            if let Some(mds) = mds {
+                break mds;
+            }
+        };
+
+        Self {
+            width,
+            full_rounds,
+            partial_rounds,
+            alpha,
+            round_constants,
+            mds,
+        }
+    }
+
+    /// Returns the number of field elements of the state.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Applies the permutation to `state`.
+    ///
+    /// # Panics
+    /// Panics if the length of `state` is not the width of the permutation.
+    pub fn permute(&self, state: &mut [F]) {
+        assert_eq!(state.len(), self.width);
+        let first_partial_round = self.full_rounds / 2;
+        let last_partial_round = first_partial_round + self.partial_rounds;
+        for (round, constants) in self.round_constants.chunks_exact(self.width).enumerate() {
+            for (x, c) in state.iter_mut().zip(constants) {
+                *x += c;
+            }
+            if (first_partial_round..last_partial_round).contains(&round) {
+                state[0] = state[0].pow_vartime([self.alpha]);
+            } else {
+                for x in state.iter_mut() {
+                    *x = x.pow_vartime([self.alpha]);
+                }
+            }
+            let mixed = self
+                .mds
+                .iter()
+                .map(|row| row.iter().zip(state.iter()).map(|(m, x)| *m * x).sum())
+                .collect::<Vec<F>>();
+            state.copy_from_slice(&mixed);
+        }
+    }
+}
+
+impl<F: PrimeField> Default for PoseidonParameters<F> {
+    /// The parameters of the reference instance `x5_255_3`: a width of 3, 8 full rounds,
+    /// 57 partial rounds and the S-box `x^5`, for 128-bit security over fields of about 255 bits.
+    fn default() -> Self {
+        Self::new(WIDTH, FULL_ROUNDS, PARTIAL_ROUNDS, ALPHA)
+    }
+}
+
+/// Returns whether `x^alpha` is a permutation of the field `F`.
+fn is_permutation_exponent<F: PrimeField>(alpha: u64) -> bool {
+    if alpha == 0 {
+        return false;
+    }
+    let remainder = ((cardinal::<F>() - 1u64) % alpha)
+        .iter_u64_digits()
+        .next()
+        .unwrap_or(0);
+    gcd(alpha, remainder) == 1
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Converts an integer smaller than the field order to a field element.
+fn integer_to_field<F: PrimeField>(value: &BigUint) -> F {
+    let mut repr = F::Repr::default();
+    let bytes = value.to_bytes_le();
+    repr.as_mut()[..bytes.len()].copy_from_slice(&bytes);
+    F::from_repr(repr).expect("the integer is smaller than the field order")
+}
+
+/// The self-shrinking Grain LFSR generating the parameters of the Poseidon reference implementation.
+struct GrainLfsr {
+    /// The 80 bits of the register, the oldest in the least significant bit.
+    state: u128,
+}
+
+impl GrainLfsr {
+    fn new(field_size: usize, width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        // The field is a prime field (1 on 2 bits), the S-box is x^alpha (0 on 4 bits).
+        let fields = [
+            (1, 2),
+            (0, 4),
+            (field_size, 12),
+            (width, 12),
+            (full_rounds, 10),
+            (partial_rounds, 10),
+            ((1 << 30) - 1, 30),
+        ];
+        let mut state = 0u128;
+        let mut position = 0;
+        for (value, bits) in fields {
+            for i in (0..bits).rev() {
+                state |= (((value >> i) & 1) as u128) << position;
+                position += 1;
+            }
+        }
+
+        let mut lfsr = Self { state };
+        for _ in 0..160 {
+            lfsr.step();
+        }
+        lfsr
+    }
+
+    fn step(&mut self) -> bool {
+        let s = self.state;
+        let bit = ((s >> 62) ^ (s >> 51) ^ (s >> 38) ^ (s >> 23) ^ (s >> 13) ^ s) & 1;
+        self.state = (s >> 1) | (bit << 79);
+        bit == 1
+    }
+
+    fn next_bit(&mut self) -> bool {
+        while !self.step() {
+            self.step();
+        }
+        self.step()
+    }
+
+    /// Reads an integer of `bits` bits, most significant bit first.
+    fn next_integer(&mut self, bits: usize) -> BigUint {
+        let length = bits.div_ceil(8);
+        let mut bytes = vec![0u8; length];
+        for i in (0..bits).rev() {
+            if self.next_bit() {
+                bytes[length - 1 - i / 8] |= 1 << (i % 8);
+            }
+        }
+        BigUint::from_bytes_be(&bytes)
+    }
+}
+
+/// Duplex sponge construction using the Poseidon permutation over the field `F`.
+///
+/// Field elements are added to the rate of the state, and read from it.
+/// When the sponge switches from absorbing to squeezing, the element 1 is absorbed as padding.
+///
+/// Through [`DuplexSpongeInterface`], bytes are packed in big-endian order into field elements of
+/// `(F::NUM_BITS - 1) / 8` bytes, after padding them with a 0x01 byte and zeros.
+/// Each squeezed field element yields its `(F::NUM_BITS - 128) / 8` least significant bytes,
+/// in big-endian order, so that the output bytes are statistically close to uniform.
+#[derive(Clone)]
+pub struct PoseidonDuplexSponge<F: PrimeField> {
+    parameters: PoseidonParameters<F>,
+    state: Vec<F>,
+    phase: Phase,
+    /// Bytes absorbed since the last packed field element.
+    pending_bytes: Vec<u8>,
+    /// Whether bytes have been absorbed since the last padding.
+    absorbing_bytes: bool,
+}
+
+impl<F: PrimeField> PoseidonDuplexSponge<F> {
+    /// Creates a new sponge with the given parameters and initialization vector.
+    pub fn with_parameters(parameters: PoseidonParameters<F>, iv: [u8; 64]) -> Self {
+        let mut sponge = Self {
+            state: vec![F::ZERO; parameters.width],
+            parameters,
+            phase: Phase::Absorbing(0),
+            pending_bytes: Vec::new(),
+            absorbing_bytes: false,
+        };
+        sponge.absorb(&iv);
+        sponge
+    }
+
+    fn rate(&self) -> usize {
+        self.parameters.width - CAPACITY
+    }
+
+    /// Absorbs field elements into the sponge state.
+    pub fn absorb_elements(&mut self, elements: &[F]) {
+        self.pad_bytes();
+        for element in elements {
+            self.absorb_element(element);
+        }
+    }
+
+    /// Squeezes `count` field elements from the sponge state.
+    pub fn squeeze_elements(&mut self, count: usize) -> Vec<F> {
+        if count == 0 {
+            return Vec::new();
+        }
+
+        let mut index = match self.phase {
+            Phase::Squeezing(index) => index,
+            Phase::Absorbing(_) => {
+                self.pad_bytes();
+                self.absorb_element(&F::ONE);
+                self.parameters.permute(&mut self.state);
+                0
+            }
+        };
+
+        let mut output = Vec::with_capacity(count);
+        while output.len() < count {
+            if index == self.rate() {
+                self.parameters.permute(&mut self.state);
+                index = 0;
+            }
+            output.push(self.state[CAPACITY + index]);
+            index += 1;
+        }
+        self.phase = Phase::Squeezing(index);
+        output
+    }
+
+    fn absorb_element(&mut self, element: &F) {
+        // Absorbing after a squeeze adds the input over the last permuted state.
+        let mut index = match self.phase {
+            Phase::Absorbing(index) => index,
+            Phase::Squeezing(_) => 0,
+        };
+        if index == self.rate() {
+            self.parameters.permute(&mut self.state);
+            index = 0;
+        }
+        self.state[CAPACITY + index] += element;
+        self.phase = Phase::Absorbing(index + 1);
+    }
+
+    /// Absorbs the pending bytes, padded, if bytes have been absorbed since the last padding.
+    fn pad_bytes(&mut self) {
+        if !self.absorbing_bytes {
+            return;
+        }
+        let mut chunk = core::mem::take(&mut self.pending_bytes);
+        chunk.push(0x01);
+        chunk.resize(absorbed_bytes_per_element::<F>(), 0);
+        self.absorb_element(&bytes_to_field(&chunk));
+        self.absorbing_bytes = false;
+    }
+}
+
+/// Kind of sponge recorded in snapshots, followed by the modulus of the field.
+fn snapshot_kind<F: PrimeField>() -> Vec<u8> {
+    [b"poseidon/".as_slice(), F::MODULUS.as_bytes()].concat()
+}
+
+/// Number of bytes packed in a field element.
+fn absorbed_bytes_per_element<F: PrimeField>() -> usize {
+    (F::NUM_BITS as usize - 1) / 8
+}
+
+/// Number of bytes read from a squeezed field element.
+fn squeezed_bytes_per_element<F: PrimeField>() -> usize {
+    (F::NUM_BITS as usize - SQUEEZE_SECURITY_MARGIN) / 8
+}
+
+/// Interprets big-endian bytes, shorter than a field element, as a field element.
+fn bytes_to_field<F: PrimeField>(bytes: &[u8]) -> F {
+    let mut repr = F::Repr::default();
+    repr.as_mut()[..bytes.len()].copy_from_slice(bytes);
+    repr.as_mut()[..bytes.len()].reverse();
+    F::from_repr(repr).expect("the bytes are shorter than a field element")
+}
+
+impl<F: PrimeField> DuplexSpongeInterface for PoseidonDuplexSponge<F> {
+    /// Creates a new sponge with the [default parameters](PoseidonParameters::default).
+    fn new(iv: [u8; 64]) -> Self {
+        Self::with_parameters(PoseidonParameters::default(), iv)
+    }
+
+    fn absorb(&mut self, input: &[u8]) {
+        if input.is_empty() {
+            return;
+        }
+
+        self.absorbing_bytes = true;
+        self.pending_bytes.extend_from_slice(input);
+        let chunk_size = absorbed_bytes_per_element::<F>();
+        let pending_bytes = core::mem::take(&mut self.pending_bytes);
+        let chunks = pending_bytes.chunks_exact(chunk_size);
+        self.pending_bytes = chunks.remainder().to_vec();
+        for chunk in chunks {
+            self.absorb_element(&bytes_to_field(chunk));
+        }
+    }
+
+    /// Squeezes bytes from whole field elements: successive squeezes
+    /// do not continue the byte stream of the last field element.
+    fn squeeze_into(&mut self, output: &mut [u8]) {
+        let chunk_size = squeezed_bytes_per_element::<F>();
+        let elements = self.squeeze_elements(output.len().div_ceil(chunk_size));
+        for (chunk, element) in output.chunks_mut(chunk_size).zip(&elements) {
+            let repr = element.to_repr();
+            for (byte, element_byte) in chunk
+                .iter_mut()
+                .zip(repr.as_ref()[..chunk_size].iter().rev())
+            {
+                *byte = *element_byte;
+            }
+        }
+    }
+
+    fn export_state(&self) -> Result<Vec<u8>, Error> {
+        let parameters = &self.parameters;
+        let mut state = Vec::new();
+        for value in [
+            parameters.width,
+            parameters.full_rounds,
+            parameters.partial_rounds,
+        ] {
+            state.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        state.extend_from_slice(&parameters.alpha.to_be_bytes());
+        for element in &self.state {
+            state.extend_from_slice(element.to_repr().as_ref());
+        }
+        write_phase(&mut state, self.phase);
+        state.push(self.absorbing_bytes as u8);
+        state.extend_from_slice(&(self.pending_bytes.len() as u32).to_be_bytes());
+        state.extend_from_slice(&self.pending_bytes);
+        Ok(encode_snapshot(&snapshot_kind::<F>(), &state))
+    }
+
+    fn import_state(snapshot: &[u8]) -> Result<Self, Error> {
+        let mut reader = decode_snapshot(&snapshot_kind::<F>(), snapshot)?;
+        let width = reader.read_u32()?;
+        let full_rounds = reader.read_u32()?;
+        let partial_rounds = reader.read_u32()?;
+        let alpha = reader.read_u64()?;
+        if !(CAPACITY + 1..=MAX_SNAPSHOT_WIDTH).contains(&width)
+            || !full_rounds.is_multiple_of(2)
+            || full_rounds > MAX_SNAPSHOT_ROUNDS
+            || partial_rounds > MAX_SNAPSHOT_ROUNDS
+            || !is_permutation_exponent::<F>(alpha)
+        {
+            return Err(Error::InvalidSnapshot);
+        }
+
+        let repr_length = F::Repr::default().as_ref().len();
+        let state = (0..width)
+            .map(|_| {
+                let mut repr = F::Repr::default();
+                repr.as_mut()
+                    .copy_from_slice(reader.read_bytes(repr_length)?);
+                Option::from(F::from_repr(repr)).ok_or(Error::InvalidSnapshot)
+            })
+            .collect::<Result<Vec<F>, Error>>()?;
+        let phase = reader.read_phase(width - CAPACITY)?;
+        let absorbing_bytes = match reader.read_u8()? {
+            0 => false,
+            1 => true,
+            _ => return Err(Error::InvalidSnapshot),
+        };
+        let pending_length = reader.read_u32()?;
+        let pending_bytes = reader.read_bytes(pending_length)?.to_vec();
+        reader.finish()?;
+        // Pending bytes are only kept while absorbing bytes, and are padded before squeezing.
+        let squeezing = matches!(phase, Phase::Squeezing(_));
+        if pending_bytes.len() >= absorbed_bytes_per_element::<F>()
+            || (!absorbing_bytes && !pending_bytes.is_empty())
+            || (squeezing && absorbing_bytes)
+        {
+            return Err(Error::InvalidSnapshot);
+        }
+
+        Ok(Self {
+            parameters: PoseidonParameters::new(width, full_rounds, partial_rounds, alpha),
+            state,
+            phase,
+            pending_bytes,
+            absorbing_bytes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bls12_381::Scalar;
+    use ff::Field;
+
+    fn scalar_from_hex(hex: &str) -> Scalar {
+        let mut repr = hex::decode(hex).unwrap();
+        repr.reverse();
+        Scalar::from_repr(repr.try_into().unwrap()).unwrap()
+    }
+
+    // Known-answer tests from the test vectors of the Poseidon reference implementation,
+    // for the permutations poseidonperm_x5_255_3 and poseidonperm_x5_255_5 over the BLS12-381 scalar field.
+    #[test]
+    fn test_poseidon_permutation_x5_255_3() {
+        let parameters = PoseidonParameters::<Scalar>::default();
+        let mut state = [0u64, 1, 2].map(Scalar::from);
+        parameters.permute(&mut state);
+
+        let expected = [
+            "28ce19420fc246a05553ad1e8c98f5c9d67166be2c18e9e4cb4b4e317dd2a78a",
+            "51f3e312c95343a896cfd8945ea82ba956c1118ce9b9859b6ea56637b4b1ddc4",
+            "3b2b69139b235626a0bfb56c9527ae66a7bf486ad8c11c14d1da0c69bbe0f79a",
+        ]
+        .map(scalar_from_hex);
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn test_poseidon_permutation_x5_255_5() {
+        let parameters = PoseidonParameters::<Scalar>::new(5, 8, 60, 5);
+        let mut state = [0u64, 1, 2, 3, 4].map(Scalar::from);
+        parameters.permute(&mut state);
+
+        let expected = [
+            "2a918b9c9f9bd7bb509331c81e297b5707f6fc7393dcee1b13901a0b22202e18",
+            "65ebf8671739eeb11fb217f2d5c5bf4a0c3f210e3f3cd3b08b5db75675d797f7",
+            "2cc176fc26bc70737a696a9dfd1b636ce360ee76926d182390cdb7459cf585ce",
+            "4dc4e29d283afd2a491fe6aef122b9a968e74eff05341f3cc23fda1781dcb566",
+            "03ff622da276830b9451b88b85e6184fd6ae15c8ab3ee25a5667be8592cce3b1",
+        ]
+        .map(scalar_from_hex);
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_poseidon_invalid_alpha() {
+        // The order of the BLS12-381 scalar field minus one is divisible by 3.
+        PoseidonParameters::<Scalar>::new(3, 8, 57, 3);
+    }
+
+    #[test]
+    fn test_poseidon_sponge_padding() {
+        let mut rng = rand::thread_rng();
+        let x = Scalar::random(&mut rng);
+
+        // Trailing zeros are not absorbed away.
+        let mut sponge1 = PoseidonDuplexSponge::<Scalar>::new([0u8; 64]);
+        sponge1.absorb_elements(&[x]);
+        let mut sponge2 = PoseidonDuplexSponge::<Scalar>::new([0u8; 64]);
+        sponge2.absorb_elements(&[x, Scalar::ZERO]);
+        assert_ne!(sponge1.squeeze_elements(1), sponge2.squeeze_elements(1));
+
+        let mut sponge1 = PoseidonDuplexSponge::<Scalar>::new([0u8; 64]);
+        sponge1.absorb(b"message");
+        let mut sponge2 = PoseidonDuplexSponge::<Scalar>::new([0u8; 64]);
+        sponge2.absorb(b"message\0");
+        assert_ne!(sponge1.squeeze(32), sponge2.squeeze(32));
+
+        // Absorbing bytes is associative.
+        let mut sponge3 = PoseidonDuplexSponge::<Scalar>::new([0u8; 64]);
+        sponge3.absorb(b"mes");
+        sponge3.absorb(b"sage");
+        let mut sponge1 = PoseidonDuplexSponge::<Scalar>::new([0u8; 64]);
+        sponge1.absorb(b"message");
+        assert_eq!(sponge1.squeeze(32), sponge3.squeeze(32));
+    }
+}
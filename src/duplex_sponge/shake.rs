@@ -1,32 +1,301 @@
 //! SHAKE-based duplex sponge implementation
 //!
-//! This module implements a duplex sponge construction using SHAKE128.
+//! This module implements a duplex sponge construction on the Keccak-f\[1600\] permutation,
+//! with the rate and padding of SHAKE128.
+//!
+//! - [`ShakeDuplexSponge`] matches the duplex sponge test vectors: squeezing outputs SHAKE128
+//!   of all the data absorbed so far, successive squeezes continue the output stream,
+//!   and absorbing after a squeeze appends to the absorbed data and restarts the stream.
+//! - [`ShakeRatchetDuplexSponge`] is a duplex sponge whose absorbs after a squeeze
+//!   are XORed over the permuted output state, so that they ratchet the state.
+//!   Absorbing data and squeezing once yields the same output as [`ShakeDuplexSponge`].
 
-use crate::duplex_sponge::DuplexSpongeInterface;
-use alloc::vec;
+use crate::duplex_sponge::keccak::KeccakPermutationState;
+use crate::duplex_sponge::snapshot::{
+    decode_snapshot, encode_snapshot, write_phase, SnapshotReader,
+};
+use crate::duplex_sponge::{DuplexSpongeInterface, Phase};
+use crate::errors::Error;
 use alloc::vec::Vec;
-use sha3::digest::{ExtendableOutput, Update};
-use sha3::Shake128;
+
+/// Rate of SHAKE128, in bytes.
+const RATE: usize = 168;
+
+/// Domain separation and first padding bit of SHAKE.
+const SHAKE_PADDING: u8 = 0x1f;
+
+/// Kind of sponge recorded in snapshots.
+const SNAPSHOT_KIND: &[u8] = b"shake128";
+
+/// Kind of ratcheting sponge recorded in snapshots.
+const RATCHET_SNAPSHOT_KIND: &[u8] = b"shake128-ratchet";
 
 /// Duplex sponge construction using SHAKE128.
-#[derive(Clone, Debug)]
-pub struct ShakeDuplexSponge(Shake128);
+#[derive(Clone)]
+pub struct ShakeDuplexSponge {
+    /// State after absorbing all the data, before padding, and position in its current block.
+    state: KeccakPermutationState,
+    absorb_index: usize,
+    /// Output stream of the absorbed data, set by the first squeeze after an absorb,
+    /// and position in its current block.
+    output: Option<(KeccakPermutationState, usize)>,
+}
 
 impl DuplexSpongeInterface for ShakeDuplexSponge {
     fn new(iv: [u8; 64]) -> Self {
-        let mut hasher = Shake128::default();
-        let initial_block = [iv.to_vec(), vec![0u8; 168 - 64]].concat();
-        hasher.update(&initial_block);
-        Self(hasher)
+        let mut sponge = Self {
+            state: KeccakPermutationState::default(),
+            absorb_index: 0,
+            output: None,
+        };
+        sponge.absorb(&iv);
+        sponge.absorb(&[0u8; RATE - 64]);
+        sponge
+    }
+
+    fn absorb(&mut self, input: &[u8]) {
+        if input.is_empty() {
+            return;
+        }
+
+        for byte in input {
+            if self.absorb_index == RATE {
+                self.state.permute();
+                self.absorb_index = 0;
+            }
+            self.state.as_mut()[self.absorb_index] ^= byte;
+            self.absorb_index += 1;
+        }
+        self.output = None;
+    }
+
+    fn squeeze_into(&mut self, output: &mut [u8]) {
+        if output.is_empty() {
+            return;
+        }
+
+        let (state, index) = self.output.get_or_insert_with(|| {
+            let mut state = self.state.clone();
+            let mut index = self.absorb_index;
+            if index == RATE {
+                state.permute();
+                index = 0;
+            }
+            state.as_mut()[index] ^= SHAKE_PADDING;
+            state.as_mut()[RATE - 1] ^= 0x80;
+            state.permute();
+            (state, 0)
+        });
+
+        let mut written = 0;
+        while written != output.len() {
+            if *index == RATE {
+                state.permute();
+                *index = 0;
+            }
+            let chunk_size = usize::min(RATE - *index, output.len() - written);
+            output[written..written + chunk_size]
+                .copy_from_slice(&state.as_ref()[*index..*index + chunk_size]);
+            *index += chunk_size;
+            written += chunk_size;
+        }
+    }
+
+    /// The snapshot holds the absorbing state and, after a squeeze, the output stream.
+    fn export_state(&self) -> Result<Vec<u8>, Error> {
+        let mut state = self.state.as_ref().to_vec();
+        write_phase(&mut state, Phase::Absorbing(self.absorb_index));
+        if let Some((output, index)) = &self.output {
+            state.extend_from_slice(output.as_ref());
+            write_phase(&mut state, Phase::Squeezing(*index));
+        }
+        Ok(encode_snapshot(SNAPSHOT_KIND, &state))
+    }
+
+    fn import_state(snapshot: &[u8]) -> Result<Self, Error> {
+        let mut reader = decode_snapshot(SNAPSHOT_KIND, snapshot)?;
+        let state = read_permutation_state(&mut reader)?;
+        let Phase::Absorbing(absorb_index) = reader.read_phase(RATE)? else {
+            return Err(Error::InvalidSnapshot);
+        };
+        let output = if reader.is_empty() {
+            None
+        } else {
+            let output = read_permutation_state(&mut reader)?;
+            let Phase::Squeezing(index) = reader.read_phase(RATE)? else {
+                return Err(Error::InvalidSnapshot);
+            };
+            Some((output, index))
+        };
+        reader.finish()?;
+        Ok(Self {
+            state,
+            absorb_index,
+            output,
+        })
+    }
+}
+
+/// Duplex sponge construction using Keccak-f\[1600\] with the rate and padding of SHAKE128,
+/// ratcheting the state when absorbing after a squeeze.
+#[derive(Clone)]
+pub struct ShakeRatchetDuplexSponge {
+    state: KeccakPermutationState,
+    phase: Phase,
+}
+
+impl DuplexSpongeInterface for ShakeRatchetDuplexSponge {
+    fn new(iv: [u8; 64]) -> Self {
+        let mut sponge = Self {
+            state: KeccakPermutationState::default(),
+            phase: Phase::Absorbing(0),
+        };
+        sponge.absorb(&iv);
+        sponge.absorb(&[0u8; RATE - 64]);
+        sponge
     }
 
     fn absorb(&mut self, input: &[u8]) {
-        self.0.update(input);
+        if input.is_empty() {
+            return;
+        }
+
+        // Absorbing after a squeeze XORs the input over the last permuted state.
+        let mut index = match self.phase {
+            Phase::Absorbing(index) => index,
+            Phase::Squeezing(_) => 0,
+        };
+        for byte in input {
+            if index == RATE {
+                self.state.permute();
+                index = 0;
+            }
+            self.state.as_mut()[index] ^= byte;
+            index += 1;
+        }
+        self.phase = Phase::Absorbing(index);
     }
 
-    fn squeeze(&mut self, length: usize) -> Vec<u8> {
-        let mut output = vec![0u8; length];
-        self.0.clone().finalize_xof_into(&mut output);
-        output
+    fn squeeze_into(&mut self, output: &mut [u8]) {
+        if output.is_empty() {
+            return;
+        }
+
+        let mut index = match self.phase {
+            Phase::Squeezing(index) => index,
+            Phase::Absorbing(mut index) => {
+                if index == RATE {
+                    self.state.permute();
+                    index = 0;
+                }
+                self.state.as_mut()[index] ^= SHAKE_PADDING;
+                self.state.as_mut()[RATE - 1] ^= 0x80;
+                self.state.permute();
+                0
+            }
+        };
+
+        let mut written = 0;
+        while written != output.len() {
+            if index == RATE {
+                self.state.permute();
+                index = 0;
+            }
+            let chunk_size = usize::min(RATE - index, output.len() - written);
+            output[written..written + chunk_size]
+                .copy_from_slice(&self.state.as_ref()[index..index + chunk_size]);
+            index += chunk_size;
+            written += chunk_size;
+        }
+        self.phase = Phase::Squeezing(index);
+    }
+
+    fn export_state(&self) -> Result<Vec<u8>, Error> {
+        let mut state = self.state.as_ref().to_vec();
+        write_phase(&mut state, self.phase);
+        Ok(encode_snapshot(RATCHET_SNAPSHOT_KIND, &state))
+    }
+
+    fn import_state(snapshot: &[u8]) -> Result<Self, Error> {
+        let mut reader = decode_snapshot(RATCHET_SNAPSHOT_KIND, snapshot)?;
+        let state = read_permutation_state(&mut reader)?;
+        let phase = reader.read_phase(RATE)?;
+        reader.finish()?;
+        Ok(Self { state, phase })
+    }
+}
+
+fn read_permutation_state(reader: &mut SnapshotReader) -> Result<KeccakPermutationState, Error> {
+    let mut state = KeccakPermutationState::default();
+    let length = state.as_ref().len();
+    state.as_mut().copy_from_slice(reader.read_bytes(length)?);
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha3::digest::{ExtendableOutput, Update};
+    use sha3::Shake128;
+
+    #[test]
+    fn test_matches_shake128() {
+        let iv = [7u8; 64];
+        let data = [0xabu8; 500];
+
+        let mut sponge = ShakeDuplexSponge::new(iv);
+        sponge.absorb(&data[..100]);
+        sponge.absorb(&data[100..]);
+        let output = [sponge.squeeze(100), sponge.squeeze(300)].concat();
+
+        let mut hasher = Shake128::default();
+        hasher.update(&iv);
+        hasher.update(&[0u8; RATE - 64]);
+        hasher.update(&data);
+        let mut expected = [0u8; 400];
+        hasher.finalize_xof_into(&mut expected);
+
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_absorb_after_squeeze() {
+        let mut sponge1 = ShakeDuplexSponge::new([0u8; 64]);
+        sponge1.absorb(b"first");
+        let first_output = sponge1.squeeze(32);
+        assert_ne!(sponge1.clone().squeeze(32), first_output);
+        sponge1.absorb(b"second");
+
+        let mut sponge2 = ShakeDuplexSponge::new([0u8; 64]);
+        sponge2.absorb(b"firstsecond");
+
+        let output = sponge1.squeeze(32);
+        assert_ne!(output, first_output);
+        assert_eq!(output, sponge2.squeeze(32));
+    }
+
+    #[test]
+    fn test_ratchet() {
+        let mut sponge1 = ShakeRatchetDuplexSponge::new([0u8; 64]);
+        sponge1.absorb(b"first");
+        let mut shake = ShakeDuplexSponge::new([0u8; 64]);
+        shake.absorb(b"first");
+        let first_output = sponge1.squeeze(200);
+        assert_eq!(first_output, shake.squeeze(200));
+        sponge1.absorb(b"second");
+
+        let mut sponge2 = ShakeRatchetDuplexSponge::new([0u8; 64]);
+        sponge2.absorb(b"firstsecond");
+
+        // Absorbing after a squeeze depends on what was squeezed.
+        let mut sponge3 = ShakeRatchetDuplexSponge::new([0u8; 64]);
+        sponge3.absorb(b"first");
+        sponge3.squeeze(32);
+        sponge3.absorb(b"second");
+
+        let output = sponge1.squeeze(32);
+        assert_ne!(output, first_output[..32]);
+        assert_ne!(output, sponge2.squeeze(32));
+        assert_ne!(output, sponge3.squeeze(32));
     }
 }
@@ -0,0 +1,131 @@
+//! Snapshots of duplex sponge states
+//!
+//! A snapshot is the byte string:
+//! - the format version [`SNAPSHOT_VERSION`], on one byte,
+//! - the kind of sponge, and the state of the sponge, each prefixed by its length as a 32-bit big-endian integer,
+//! - a SHA3-256 checksum of the preceding bytes.
+//!
+//! The checksum detects truncated and corrupted snapshots.
+//! It is not a MAC: snapshots stored by an untrusted party must be authenticated separately.
+
+use crate::duplex_sponge::Phase;
+use crate::errors::Error;
+use alloc::vec;
+use alloc::vec::Vec;
+use sha3::{Digest, Sha3_256};
+
+/// Version of the snapshot format.
+pub(crate) const SNAPSHOT_VERSION: u8 = 1;
+
+/// Size in bytes of the checksum of a snapshot.
+const CHECKSUM_SIZE: usize = 32;
+
+/// Encodes the state of a sponge of the given kind in a snapshot.
+pub(crate) fn encode_snapshot(kind: &[u8], state: &[u8]) -> Vec<u8> {
+    let mut snapshot = vec![SNAPSHOT_VERSION];
+    snapshot.extend_from_slice(&(kind.len() as u32).to_be_bytes());
+    snapshot.extend_from_slice(kind);
+    snapshot.extend_from_slice(&(state.len() as u32).to_be_bytes());
+    snapshot.extend_from_slice(state);
+    let checksum = Sha3_256::digest(&snapshot);
+    snapshot.extend_from_slice(&checksum);
+    snapshot
+}
+
+/// Checks a snapshot and returns a reader over the state of the sponge.
+///
+/// Returns [`Error::InvalidSnapshot`] if the checksum, the version or the kind do not match.
+pub(crate) fn decode_snapshot<'a>(
+    kind: &[u8],
+    snapshot: &'a [u8],
+) -> Result<SnapshotReader<'a>, Error> {
+    let split = snapshot
+        .len()
+        .checked_sub(CHECKSUM_SIZE)
+        .ok_or(Error::InvalidSnapshot)?;
+    let (contents, checksum) = snapshot.split_at(split);
+    if Sha3_256::digest(contents).as_slice() != checksum {
+        return Err(Error::InvalidSnapshot);
+    }
+
+    let mut reader = SnapshotReader { data: contents };
+    if reader.read_u8()? != SNAPSHOT_VERSION {
+        return Err(Error::InvalidSnapshot);
+    }
+    let kind_length = reader.read_u32()?;
+    if reader.read_bytes(kind_length)? != kind {
+        return Err(Error::InvalidSnapshot);
+    }
+    let state_length = reader.read_u32()?;
+    let state = reader.read_bytes(state_length)?;
+    reader.finish()?;
+    Ok(SnapshotReader { data: state })
+}
+
+/// Reads the fields of a sponge state, failing with [`Error::InvalidSnapshot`] on truncated data.
+pub(crate) struct SnapshotReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> SnapshotReader<'a> {
+    pub(crate) fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], Error> {
+        if self.data.len() < length {
+            return Err(Error::InvalidSnapshot);
+        }
+        let (bytes, rest) = self.data.split_at(length);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    pub(crate) fn read_u8(&mut self) -> Result<u8, Error> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub(crate) fn read_u32(&mut self) -> Result<usize, Error> {
+        let bytes = self.read_bytes(4)?.try_into().unwrap();
+        Ok(u32::from_be_bytes(bytes) as usize)
+    }
+
+    pub(crate) fn read_u64(&mut self) -> Result<u64, Error> {
+        let bytes = self.read_bytes(8)?.try_into().unwrap();
+        Ok(u64::from_be_bytes(bytes))
+    }
+
+    /// Reads a [`Phase`] whose index is at most `rate`.
+    pub(crate) fn read_phase(&mut self, rate: usize) -> Result<Phase, Error> {
+        let tag = self.read_u8()?;
+        let index = self.read_u32()?;
+        if index > rate {
+            return Err(Error::InvalidSnapshot);
+        }
+        match tag {
+            0 => Ok(Phase::Absorbing(index)),
+            1 => Ok(Phase::Squeezing(index)),
+            _ => Err(Error::InvalidSnapshot),
+        }
+    }
+
+    /// Returns `true` if the whole state has been read.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Checks that the whole state has been read.
+    pub(crate) fn finish(self) -> Result<(), Error> {
+        if self.data.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidSnapshot)
+        }
+    }
+}
+
+/// Appends a [`Phase`] to a sponge state, as read by [`SnapshotReader::read_phase`].
+pub(crate) fn write_phase(state: &mut Vec<u8>, phase: Phase) {
+    let (tag, index) = match phase {
+        Phase::Absorbing(index) => (0u8, index),
+        Phase::Squeezing(index) => (1u8, index),
+    };
+    state.push(tag);
+    state.extend_from_slice(&(index as u32).to_be_bytes());
+}
@@ -0,0 +1,82 @@
+//! XOF-based duplex sponge implementation
+//!
+//! This module implements a duplex sponge construction from any extendable-output function,
+//! such as SHAKE256 or BLAKE3.
+//!
+//! The XOF is first fed the IV and the absorbed bytes.
+//! Squeezing reads the output stream of the XOF, and successive squeezes continue it.
+//! Absorbing after a squeeze reads 64 more bytes from the output stream,
+//! and feeds them to a new instance of the XOF before the absorbed bytes.
+
+use crate::duplex_sponge::DuplexSpongeInterface;
+use ::digest::{ExtendableOutput, Update, XofReader};
+
+/// Size in bytes of the chaining value carried from a squeeze phase to the next absorb phase.
+const CHAINING_VALUE_SIZE: usize = 64;
+
+enum Phase<X: ExtendableOutput> {
+    Absorbing(X),
+    Squeezing(X::Reader),
+}
+
+/// Duplex sponge construction using the extendable-output function `X`.
+pub struct XofDuplexSponge<X: ExtendableOutput> {
+    phase: Phase<X>,
+}
+
+impl<X> Clone for XofDuplexSponge<X>
+where
+    X: ExtendableOutput + Clone,
+    X::Reader: Clone,
+{
+    fn clone(&self) -> Self {
+        let phase = match &self.phase {
+            Phase::Absorbing(hasher) => Phase::Absorbing(hasher.clone()),
+            Phase::Squeezing(reader) => Phase::Squeezing(reader.clone()),
+        };
+        Self { phase }
+    }
+}
+
+impl<X> DuplexSpongeInterface for XofDuplexSponge<X>
+where
+    X: Default + Update + ExtendableOutput,
+{
+    fn new(iv: [u8; 64]) -> Self {
+        let mut hasher = X::default();
+        hasher.update(&iv);
+        Self {
+            phase: Phase::Absorbing(hasher),
+        }
+    }
+
+    fn absorb(&mut self, input: &[u8]) {
+        if input.is_empty() {
+            return;
+        }
+        if let Phase::Squeezing(reader) = &mut self.phase {
+            let mut chaining_value = [0u8; CHAINING_VALUE_SIZE];
+            reader.read(&mut chaining_value);
+            let mut hasher = X::default();
+            hasher.update(&chaining_value);
+            self.phase = Phase::Absorbing(hasher);
+        }
+        if let Phase::Absorbing(hasher) = &mut self.phase {
+            hasher.update(input);
+        }
+    }
+
+    fn squeeze_into(&mut self, output: &mut [u8]) {
+        if output.is_empty() {
+            return;
+        }
+        if let Phase::Absorbing(hasher) = &mut self.phase {
+            let reader = core::mem::take(hasher).finalize_xof();
+            self.phase = Phase::Squeezing(reader);
+        }
+
+        if let Phase::Squeezing(reader) = &mut self.phase {
+            reader.read(output);
+        }
+    }
+}
@@ -0,0 +1,414 @@
+//! # Ed25519 signatures
+//!
+//! This module implements the Ed25519 signatures of [RFC 8032] as a ciphersuite of the
+//! Fiat-Shamir transformation, so that existing Ed25519 verification can move to the same
+//! proof-verification pipeline as other proofs of knowledge.
+//!
+//! - [`Ed25519Schnorr`] is the Schnorr protocol for the discrete logarithm of an Ed25519 public key,
+//!   built on a [`CanonicalLinearRelation`] over the prime-order subgroup of Edwards25519.
+//!   Its points are serialized in their 32-byte compressed encoding, and its scalars in
+//!   32 little-endian bytes, as in RFC 8032.
+//! - [`Ed25519Codec`] derives the challenge `k` as `SHA-512(R || A || M)`,
+//!   read as a little-endian integer reduced modulo the group order.
+//! - [`Ed25519NonceRng`] derives the nonce of a signature from the prefix of the expanded
+//!   secret key and the message, as in RFC 8032.
+//!
+//! A batchable proof bound to a message is the Ed25519 signature `R || S` of the message:
+//! [`Ed25519Nizk::from_public_key`] and [`Ed25519Nizk::from_secret_key`] create the [`Nizk`]
+//! of a public key, [`Nizk::prove_batchable_with_message`] signs, and
+//! [`Nizk::verify_batchable_with_message`] verifies Ed25519 signatures.
+//! Compact proofs `k || S` are shorter proofs of the same statement, which Ed25519 verifiers do not accept.
+//!
+//! ## Verification
+//!
+//! By default, verification follows RFC 8032: points must be encoded canonically, `S` must be
+//! reduced modulo the group order, and the cofactored equation `[8][S]B = [8]R + [8][k]A` must hold.
+//! Public keys and nonces may have a small-order component, which the equation ignores.
+//! Public keys of small order are rejected, as they are the public key of no secret key.
+//!
+//! [`Ed25519Schnorr::from_public_key_strict`] creates instances which also reject public keys and
+//! nonces outside of the prime-order subgroup. With them, a signature verifies under the cofactored
+//! and the cofactorless equations alike, and cannot be made to verify by adding small-order points.
+//!
+//! [RFC 8032]: https://www.rfc-editor.org/rfc/rfc8032
+
+use crate::errors::{Error, InvalidInstance};
+use crate::fiat_shamir::Nizk;
+use crate::linear_relation::{CanonicalLinearRelation, LinearRelation};
+use crate::schnorr_protocol::SchnorrProverState;
+use crate::signature::{sealed, SignatureCodec, SignatureNonceRng, SignatureScheme, ENCODING_SIZE};
+use crate::traits::{
+    SigmaProtocol, SigmaProtocolBatchVerifier, SigmaProtocolSimulator, VerificationEquation,
+};
+use alloc::vec;
+use alloc::vec::Vec;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint, SubgroupPoint};
+use curve25519_dalek::Scalar;
+use ff::Field;
+use group::cofactor::CofactorGroup;
+use group::Group;
+use sha2::{Digest, Sha512};
+use zeroize::{Zeroize, Zeroizing};
+
+#[cfg(feature = "std")]
+use rand::{CryptoRng, Rng, RngCore};
+#[cfg(not(feature = "std"))]
+use rand_core::{CryptoRng, RngCore, RngCore as Rng};
+
+/// Size in bytes of the output of SHA-512.
+const HASH_SIZE: usize = 64;
+
+/// A [`Nizk`] producing and verifying Ed25519 signatures.
+pub type Ed25519Nizk = Nizk<Ed25519Schnorr, Ed25519Codec>;
+
+/// The codec of Ed25519 signatures, deriving the challenge `SHA-512(R || A || M)`
+/// of the nonce `R` and the public key `A`.
+///
+/// Bytes squeezed beyond challenges, and IVs bound to a session, are hashed after the
+/// domain separators `sigma-proofs/ed25519/squeeze` and `sigma-proofs/ed25519/iv`.
+pub type Ed25519Codec = SignatureCodec<Ed25519Schnorr>;
+
+/// The nonce generator of Ed25519 signatures.
+///
+/// The first 64 bytes of output are `SHA-512(prefix || M)`, where `prefix` is the second half
+/// of the hash of the secret key. Passed to [`Nizk::prove_batchable_with_message`], they make
+/// the signature the Ed25519 signature of `M`, which RFC 8032 derives deterministically.
+pub type Ed25519NonceRng = SignatureNonceRng<Ed25519Schnorr>;
+
+/// Decodes a point from its compressed encoding, as in RFC 8032.
+///
+/// Returns `None` if the encoding is not canonical.
+fn decode_point(bytes: &[u8]) -> Option<EdwardsPoint> {
+    let encoding = CompressedEdwardsY::from_slice(bytes.get(..ENCODING_SIZE)?).ok()?;
+    encoding
+        .decompress()
+        .filter(|point| point.compress() == encoding)
+}
+
+fn scalar_from_bytes(bytes: &[u8]) -> Option<Scalar> {
+    let bytes: [u8; ENCODING_SIZE] = bytes.get(..ENCODING_SIZE)?.try_into().ok()?;
+    Scalar::from_canonical_bytes(bytes).into()
+}
+
+/// Expands a secret key into the secret scalar and the prefix hashed into nonces.
+fn expand_secret_key(secret_key: &[u8; ENCODING_SIZE]) -> (Scalar, [u8; ENCODING_SIZE]) {
+    let mut hash: [u8; HASH_SIZE] = Sha512::digest(secret_key).into();
+    let mut scalar_bytes = [0u8; ENCODING_SIZE];
+    let mut prefix = [0u8; ENCODING_SIZE];
+    scalar_bytes.copy_from_slice(&hash[..ENCODING_SIZE]);
+    prefix.copy_from_slice(&hash[ENCODING_SIZE..]);
+    scalar_bytes[0] &= 0xf8;
+    scalar_bytes[31] &= 0x7f;
+    scalar_bytes[31] |= 0x40;
+    let scalar = Scalar::from_bytes_mod_order(scalar_bytes);
+    hash.zeroize();
+    scalar_bytes.zeroize();
+    (scalar, prefix)
+}
+
+/// The challenge `k` of an Ed25519 signature, a scalar of the prime-order subgroup.
+///
+/// It is derived by hashing the nonce `R` with [`Ed25519Codec`], and is not part of the signature:
+/// only compact proofs serialize it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ed25519Challenge(Scalar);
+
+/// The Schnorr protocol for the discrete logarithm of an Ed25519 public key.
+///
+/// The witness is the secret scalar expanded from the secret key by [`Ed25519Schnorr::from_secret_key`].
+/// Commitments are nonce points `R`, which are mapped to the prime-order subgroup
+/// by multiplying them by the cofactor before checking the equation of the relation,
+/// `[8][S]B = [8]R + [8][k]A`.
+#[derive(Clone, Debug)]
+pub struct Ed25519Schnorr {
+    relation: CanonicalLinearRelation<SubgroupPoint>,
+    public_key: [u8; ENCODING_SIZE],
+    point: EdwardsPoint,
+    strict: bool,
+}
+
+impl Ed25519Schnorr {
+    /// Creates the instance of an encoded public key, verifying signatures as in RFC 8032.
+    ///
+    /// # Errors
+    /// Returns an error if the public key is not the canonical encoding of a point,
+    /// or if the point has small order.
+    pub fn from_public_key(public_key: &[u8; ENCODING_SIZE]) -> Result<Self, InvalidInstance> {
+        let point = decode_point(public_key)
+            .ok_or_else(|| InvalidInstance::new("The public key is not a canonical point"))?;
+        if point.is_small_order() {
+            return Err(InvalidInstance::new("The public key has small order"));
+        }
+
+        let mut relation = LinearRelation::new();
+        let var_x = relation.allocate_scalar();
+        let var_B = relation.allocate_element();
+        let var_A = relation.allocate_eq(var_x * var_B);
+        relation.set_elements([
+            (var_B, SubgroupPoint::generator() * Scalar::from(8u8)),
+            (var_A, point.clear_cofactor()),
+        ]);
+
+        Ok(Self {
+            relation: relation.canonical()?,
+            public_key: *public_key,
+            point,
+            strict: false,
+        })
+    }
+
+    /// Creates the instance of an encoded public key, rejecting public keys and nonces
+    /// outside of the prime-order subgroup.
+    ///
+    /// # Errors
+    /// Returns an error if the public key is not the canonical encoding of a point of the prime-order subgroup,
+    /// or if the point is the identity.
+    pub fn from_public_key_strict(
+        public_key: &[u8; ENCODING_SIZE],
+    ) -> Result<Self, InvalidInstance> {
+        let instance = Self::from_public_key(public_key)?;
+        if !instance.point.is_torsion_free() {
+            return Err(InvalidInstance::new(
+                "The public key is not in the prime-order subgroup",
+            ));
+        }
+        Ok(Self {
+            strict: true,
+            ..instance
+        })
+    }
+
+    /// Creates the instance of the public key of the 32-byte `secret_key`, and the corresponding witness.
+    ///
+    /// # Errors
+    /// Returns an error if the secret scalar is zero modulo the group order.
+    pub fn from_secret_key(
+        secret_key: &[u8; ENCODING_SIZE],
+    ) -> Result<(Self, Vec<Scalar>), InvalidInstance> {
+        let (scalar, mut prefix) = expand_secret_key(secret_key);
+        prefix.zeroize();
+        if scalar == Scalar::ZERO {
+            return Err(InvalidInstance::new("The secret scalar is zero"));
+        }
+        let public_key = EdwardsPoint::mul_base(&scalar).compress().to_bytes();
+        Ok((Self::from_public_key(&public_key)?, vec![scalar]))
+    }
+
+    /// Returns the encoded public key.
+    pub fn public_key(&self) -> [u8; ENCODING_SIZE] {
+        self.public_key
+    }
+
+    /// Multiplies the nonce points by the cofactor,
+    /// after checking that they are in the prime-order subgroup if the instance is strict.
+    fn clear_cofactors(&self, commitment: &[EdwardsPoint]) -> Result<Vec<SubgroupPoint>, Error> {
+        if self.strict && !commitment.iter().all(EdwardsPoint::is_torsion_free) {
+            return Err(Error::VerificationFailure);
+        }
+        Ok(commitment
+            .iter()
+            .map(CofactorGroup::clear_cofactor)
+            .collect())
+    }
+}
+
+impl SigmaProtocol for Ed25519Schnorr {
+    type Commitment = Vec<EdwardsPoint>;
+    type ProverState = SchnorrProverState<SubgroupPoint>;
+    type Response = Vec<Scalar>;
+    type Witness = Vec<Scalar>;
+    type Challenge = Ed25519Challenge;
+
+    /// Commits to a nonce read from 64 bytes of `rng`,
+    /// as a little-endian integer reduced modulo the group order.
+    ///
+    /// # Errors
+    /// - [`Error::InvalidInstanceWitnessPair`] if the witness is not the secret scalar of the public key.
+    fn prover_commit(
+        &self,
+        witness: &Self::Witness,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(Self::Commitment, Self::ProverState), Error> {
+        if witness.len() != 1 || self.relation.evaluate(witness) != self.relation.image {
+            return Err(Error::InvalidInstanceWitnessPair);
+        }
+
+        let mut bytes = [0u8; HASH_SIZE];
+        let mut nonce = Scalar::ZERO;
+        while nonce == Scalar::ZERO {
+            rng.fill_bytes(&mut bytes);
+            nonce = Scalar::from_bytes_mod_order_wide(&bytes);
+        }
+        bytes.zeroize();
+
+        let commitment = vec![EdwardsPoint::mul_base(&nonce)];
+        Ok((
+            commitment,
+            SchnorrProverState::new(vec![nonce], witness.clone()),
+        ))
+    }
+
+    fn prover_response(
+        &self,
+        prover_state: Self::ProverState,
+        challenge: &Self::Challenge,
+    ) -> Result<Self::Response, Error> {
+        self.relation.prover_response(prover_state, &challenge.0)
+    }
+
+    /// Verifies the cofactored equation, rejecting nonces outside of the prime-order subgroup
+    /// if the instance is strict.
+    fn verifier(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Self::Challenge,
+        response: &Self::Response,
+    ) -> Result<(), Error> {
+        let commitment = self.clear_cofactors(commitment)?;
+        self.relation.verifier(&commitment, &challenge.0, response)
+    }
+
+    /// Serializes the commitment as the compressed nonce point.
+    fn serialize_commitment(&self, commitment: &Self::Commitment) -> Vec<u8> {
+        commitment
+            .iter()
+            .flat_map(|point| point.compress().to_bytes())
+            .collect()
+    }
+
+    /// Serializes the challenge in 32 little-endian bytes.
+    fn serialize_challenge(&self, challenge: &Self::Challenge) -> Vec<u8> {
+        challenge.0.to_bytes().to_vec()
+    }
+
+    /// Serializes the response in 32 little-endian bytes.
+    fn serialize_response(&self, response: &Self::Response) -> Vec<u8> {
+        response.iter().flat_map(|s| s.to_bytes()).collect()
+    }
+
+    /// Deserializes the commitment from the canonical encoding of a point.
+    fn deserialize_commitment(&self, data: &[u8]) -> Result<Self::Commitment, Error> {
+        decode_point(data)
+            .map(|point| vec![point])
+            .ok_or(Error::VerificationFailure)
+    }
+
+    fn deserialize_challenge(&self, data: &[u8]) -> Result<Self::Challenge, Error> {
+        scalar_from_bytes(data)
+            .map(Ed25519Challenge)
+            .ok_or(Error::VerificationFailure)
+    }
+
+    fn deserialize_response(&self, data: &[u8]) -> Result<Self::Response, Error> {
+        scalar_from_bytes(data)
+            .map(|s| vec![s])
+            .ok_or(Error::VerificationFailure)
+    }
+
+    /// The instance label is the encoded public key.
+    fn instance_label(&self) -> impl AsRef<[u8]> {
+        self.public_key
+    }
+
+    fn protocol_identifier(&self) -> impl AsRef<[u8]> {
+        <Self as sealed::Scheme>::PROTOCOL_IDENTIFIER
+    }
+
+    fn serialize_witness(&self, witness: &Self::Witness) -> Option<Zeroizing<Vec<u8>>> {
+        Some(Zeroizing::new(self.serialize_response(witness)))
+    }
+}
+
+impl SigmaProtocolSimulator for Ed25519Schnorr {
+    fn simulate_response<R: Rng + CryptoRng>(&self, rng: &mut R) -> Self::Response {
+        self.relation.simulate_response(rng)
+    }
+
+    fn simulate_transcript<R: Rng + CryptoRng>(
+        &self,
+        rng: &mut R,
+    ) -> Result<(Self::Commitment, Self::Challenge, Self::Response), Error> {
+        let response = self.simulate_response(&mut *rng);
+        let challenge = Ed25519Challenge(<Scalar as Field>::random(&mut *rng));
+        let commitment = self.simulate_commitment(&challenge, &response)?;
+        Ok((commitment, challenge, response))
+    }
+
+    /// Recomputes the nonce point `R = [S]B - [k]A`.
+    fn simulate_commitment(
+        &self,
+        challenge: &Self::Challenge,
+        response: &Self::Response,
+    ) -> Result<Self::Commitment, Error> {
+        let [response] = response.as_slice() else {
+            return Err(Error::InvalidInstanceWitnessPair);
+        };
+        Ok(vec![EdwardsPoint::vartime_double_scalar_mul_basepoint(
+            &-challenge.0,
+            &self.point,
+            response,
+        )])
+    }
+}
+
+impl SignatureScheme for Ed25519Schnorr {}
+
+impl sealed::Scheme for Ed25519Schnorr {
+    type SecretKey = [u8; ENCODING_SIZE];
+    type Hash = Sha512;
+
+    const PROTOCOL_IDENTIFIER: &'static [u8] = b"Ed25519";
+    const CHALLENGE_DOMAIN: &'static [u8] = b"";
+    const SQUEEZE_DOMAIN: &'static [u8] = b"sigma-proofs/ed25519/squeeze";
+    const IV_DOMAIN: &'static [u8] = b"sigma-proofs/ed25519/iv";
+
+    /// Returns SHA-512 with `domain` absorbed: challenges are hashed without a domain separator.
+    fn hasher(domain: &[u8]) -> Sha512 {
+        Sha512::new_with_prefix(domain)
+    }
+
+    fn challenge_from_hash(hash: digest::Output<Sha512>) -> Ed25519Challenge {
+        Ed25519Challenge(Scalar::from_bytes_mod_order_wide(&hash.into()))
+    }
+
+    fn from_public_key(public_key: &[u8; ENCODING_SIZE]) -> Result<Self, InvalidInstance> {
+        Self::from_public_key(public_key)
+    }
+
+    fn from_secret_key(
+        secret_key: &[u8; ENCODING_SIZE],
+    ) -> Result<(Self, Vec<Scalar>), InvalidInstance> {
+        Self::from_secret_key(secret_key)
+    }
+}
+
+impl SigmaProtocolBatchVerifier for Ed25519Schnorr {
+    type Group = SubgroupPoint;
+
+    /// Returns the cofactored equation, rejecting nonces outside of the prime-order subgroup
+    /// if the instance is strict.
+    fn verification_equations(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Self::Challenge,
+        response: &Self::Response,
+    ) -> Result<Vec<VerificationEquation<SubgroupPoint>>, Error> {
+        let commitment = self.clear_cofactors(commitment)?;
+        self.relation
+            .verification_equations(&commitment, &challenge.0, response)
+    }
+}
+
+impl SignatureNonceRng<Ed25519Schnorr> {
+    /// Seeds the generator of the nonce signing `message` with the 32-byte `secret_key`.
+    pub fn new(secret_key: &[u8; ENCODING_SIZE], message: &[u8]) -> Self {
+        let (mut scalar, mut prefix) = expand_secret_key(secret_key);
+        let seed = Sha512::new()
+            .chain_update(prefix)
+            .chain_update(message)
+            .finalize();
+        scalar.zeroize();
+        prefix.zeroize();
+        Self::from_seed(seed)
+    }
+}
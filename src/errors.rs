@@ -4,11 +4,17 @@
 //! encountered during the execution of interactive or non-interactive Sigma protocols.
 //!
 //! These errors include:
-//! - Failed proof verification,
+//! - Failed proof verification, possibly for some proofs of a batch,
 //! - Mismatched parameter lengths (e.g., during batch verification),
-//! - Access to unassigned group variables in constraint systems.
+//! - Access to unassigned group variables in constraint systems,
+//! - Out-of-order use of interactive protocol sessions,
+//! - Nonce modes not supported by a protocol,
+//! - Invalid or unsupported snapshots of sponge states,
+//! - IVs that do not match the instance they are pinned to,
+//! - Invalid parameters of a proof system.
 
 use alloc::string::String;
+use alloc::vec::Vec;
 #[cfg(not(feature = "std"))]
 use core::fmt;
 
@@ -58,6 +64,39 @@ pub enum Error {
         /// Debug representation of the unassigned variable.
         var_debug: String,
     },
+    /// Batch verification failed: some of the proofs in the batch are invalid.
+    #[cfg_attr(
+        feature = "std",
+        error("Batch verification failed for proofs at indices {indices:?}.")
+    )]
+    BatchVerificationFailure {
+        /// Indices of the invalid proofs in the batch.
+        indices: Vec<usize>,
+    },
+    /// An interactive session received a message it was not expecting at this point of the protocol.
+    #[cfg_attr(feature = "std", error("Invalid interactive protocol state."))]
+    InvalidProtocolState,
+    /// The protocol cannot serialize its witness, which is needed to derive nonces.
+    #[cfg_attr(
+        feature = "std",
+        error("The nonce mode is not supported by this protocol.")
+    )]
+    UnsupportedNonceMode,
+    /// The snapshot is truncated, corrupted, or was exported by another kind of sponge.
+    #[cfg_attr(feature = "std", error("Invalid snapshot."))]
+    InvalidSnapshot,
+    /// The sponge cannot export or import snapshots of its state.
+    #[cfg_attr(feature = "std", error("Snapshots are not supported by this sponge."))]
+    UnsupportedSnapshot,
+    /// The pinned IV was not computed for this protocol, session and instance.
+    #[cfg_attr(
+        feature = "std",
+        error("The IV does not match the protocol, session and instance.")
+    )]
+    IvMismatch,
+    /// The parameters of the proof system are invalid.
+    #[cfg_attr(feature = "std", error("Invalid proof system parameters."))]
+    InvalidParameters,
 }
 
 // Manual Display implementation for no_std compatibility
@@ -77,6 +116,28 @@ impl fmt::Display for Error {
             Error::UnassignedGroupVar { var_debug } => {
                 write!(f, "Uninitialized group element variable: {}", var_debug)
             }
+            Error::BatchVerificationFailure { indices } => {
+                write!(
+                    f,
+                    "Batch verification failed for proofs at indices {:?}.",
+                    indices
+                )
+            }
+            Error::InvalidProtocolState => write!(f, "Invalid interactive protocol state."),
+            Error::UnsupportedNonceMode => {
+                write!(f, "The nonce mode is not supported by this protocol.")
+            }
+            Error::InvalidSnapshot => write!(f, "Invalid snapshot."),
+            Error::UnsupportedSnapshot => {
+                write!(f, "Snapshots are not supported by this sponge.")
+            }
+            Error::IvMismatch => {
+                write!(
+                    f,
+                    "The IV does not match the protocol, session and instance."
+                )
+            }
+            Error::InvalidParameters => write!(f, "Invalid proof system parameters."),
         }
     }
 }
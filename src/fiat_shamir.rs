@@ -8,26 +8,261 @@
 //! via a cryptographic sponge function (Codec).
 //!
 //! # Usage
-//! This struct is generic over:
+//! Proofs can be handled as bytes, or parsed into [`BatchableProof`] and [`CompactProof`],
+//! which can be converted into one another without re-proving.
+//!
+//! [`Nizk`] is generic over:
 //! - `P`: the underlying Sigma protocol ([`SigmaProtocol`] trait).
 //! - `C`: the codec ([`Codec`] trait).
+//!
+//! # Nonces
+//! By default, the prover draws its nonces from the random number generator given by the caller.
+//! [`Nizk::with_nonce_mode`] selects hedged or deterministic nonces instead (see [`NonceMode`]).
 
 use crate::errors::Error;
-use crate::traits::SigmaProtocol;
+use crate::group::msm::VariableMultiScalarMul;
+use crate::nonce::{NonceMode, NonceRng};
+use crate::traits::{SigmaProtocol, SigmaProtocolBatchVerifier};
 use crate::{codec::Codec, traits::SigmaProtocolSimulator};
 use alloc::vec::Vec;
+use ff::Field;
+use group::Group;
 
 #[cfg(feature = "std")]
 use rand::{CryptoRng, RngCore};
 #[cfg(not(feature = "std"))]
 use rand_core::{CryptoRng, RngCore};
 
+/// Domain separator absorbed before a message bound into a proof.
+///
+/// Commitments are absorbed without any prefix, so the separator
+/// prevents a message from being confused with a commitment.
+pub(crate) const MESSAGE_DOMAIN_SEPARATOR: &[u8] = b"sigma-proofs/message";
+
 type Transcript<P> = (
     <P as SigmaProtocol>::Commitment,
     <P as SigmaProtocol>::Challenge,
     <P as SigmaProtocol>::Response,
 );
 
+/// A parsed batchable proof, made of the prover's commitment and response.
+///
+/// Batchable proofs can be verified in batch with [`Nizk::verify_batchable_many`].
+/// Their serialization is the one of [`Nizk::prove_batchable`].
+pub struct BatchableProof<P: SigmaProtocol> {
+    /// The prover's commitment.
+    pub commitment: P::Commitment,
+    /// The prover's response.
+    pub response: P::Response,
+}
+
+/// A parsed compact proof, made of the Fiat-Shamir challenge and the prover's response.
+///
+/// Its serialization is the one of [`Nizk::prove_compact`].
+pub struct CompactProof<P: SigmaProtocol> {
+    /// The challenge derived via Fiat-Shamir.
+    pub challenge: P::Challenge,
+    /// The prover's response.
+    pub response: P::Response,
+}
+
+impl<P> Clone for BatchableProof<P>
+where
+    P: SigmaProtocol,
+    P::Commitment: Clone,
+    P::Response: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            commitment: self.commitment.clone(),
+            response: self.response.clone(),
+        }
+    }
+}
+
+impl<P> core::fmt::Debug for BatchableProof<P>
+where
+    P: SigmaProtocol,
+    P::Commitment: core::fmt::Debug,
+    P::Response: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BatchableProof")
+            .field("commitment", &self.commitment)
+            .field("response", &self.response)
+            .finish()
+    }
+}
+
+impl<P> Clone for CompactProof<P>
+where
+    P: SigmaProtocol,
+    P::Challenge: Clone,
+    P::Response: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            challenge: self.challenge.clone(),
+            response: self.response.clone(),
+        }
+    }
+}
+
+impl<P> core::fmt::Debug for CompactProof<P>
+where
+    P: SigmaProtocol,
+    P::Challenge: core::fmt::Debug,
+    P::Response: core::fmt::Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CompactProof")
+            .field("challenge", &self.challenge)
+            .field("response", &self.response)
+            .finish()
+    }
+}
+
+impl<P: SigmaProtocol> BatchableProof<P> {
+    /// Parses a batchable proof for the instance of `nizk`.
+    ///
+    /// # Errors
+    /// - Returns [`Error::VerificationFailure`] if deserialization fails or the proof has trailing bytes.
+    pub fn from_bytes<C>(nizk: &Nizk<P, C>, proof: &[u8]) -> Result<Self, Error>
+    where
+        P::Challenge: PartialEq,
+        C: Codec<Challenge = P::Challenge>,
+    {
+        let protocol = &nizk.interactive_proof;
+        let commitment = protocol.deserialize_commitment(proof)?;
+        let commitment_size = protocol.serialize_commitment(&commitment).len();
+        let response = protocol.deserialize_response(&proof[commitment_size..])?;
+        let response_size = protocol.serialize_response(&response).len();
+
+        // Proof size check
+        if proof.len() != commitment_size + response_size {
+            return Err(Error::VerificationFailure);
+        }
+
+        Ok(Self {
+            commitment,
+            response,
+        })
+    }
+
+    /// Serializes the proof as `commitment || response`.
+    pub fn to_bytes<C>(&self, nizk: &Nizk<P, C>) -> Vec<u8>
+    where
+        P::Challenge: PartialEq,
+        C: Codec<Challenge = P::Challenge>,
+    {
+        let mut bytes = nizk
+            .interactive_proof
+            .serialize_commitment(&self.commitment);
+        bytes.extend_from_slice(&nizk.interactive_proof.serialize_response(&self.response));
+        bytes
+    }
+
+    /// Converts the proof into a compact proof, recomputing the challenge through the codec of `nizk`.
+    ///
+    /// The conversion does not check the proof:
+    /// the compact proof verifies if and only if the batchable one does.
+    pub fn to_compact<C>(&self, nizk: &Nizk<P, C>) -> CompactProof<P>
+    where
+        P::Challenge: PartialEq,
+        P::Response: Clone,
+        C: Codec<Challenge = P::Challenge> + Clone,
+    {
+        self.to_compact_with_hash_state(nizk, nizk.hash_state.clone())
+    }
+
+    /// Converts a proof bound to `message` into a compact proof.
+    ///
+    /// See [`BatchableProof::to_compact`] and [`Nizk::prove_batchable_with_message`].
+    pub fn to_compact_with_message<C>(&self, nizk: &Nizk<P, C>, message: &[u8]) -> CompactProof<P>
+    where
+        P::Challenge: PartialEq,
+        P::Response: Clone,
+        C: Codec<Challenge = P::Challenge> + Clone,
+    {
+        self.to_compact_with_hash_state(nizk, nizk.hash_state_with_message(message))
+    }
+
+    fn to_compact_with_hash_state<C>(&self, nizk: &Nizk<P, C>, mut hash_state: C) -> CompactProof<P>
+    where
+        P::Challenge: PartialEq,
+        P::Response: Clone,
+        C: Codec<Challenge = P::Challenge>,
+    {
+        let serialized_commitment = nizk
+            .interactive_proof
+            .serialize_commitment(&self.commitment);
+        hash_state.prover_message(&serialized_commitment);
+        CompactProof {
+            challenge: hash_state.verifier_challenge_in(nizk.interactive_proof.challenge_space()),
+            response: self.response.clone(),
+        }
+    }
+}
+
+impl<P: SigmaProtocol> CompactProof<P> {
+    /// Parses a compact proof for the instance of `nizk`.
+    ///
+    /// # Errors
+    /// - Returns [`Error::VerificationFailure`] if deserialization fails or the proof has trailing bytes.
+    pub fn from_bytes<C>(nizk: &Nizk<P, C>, proof: &[u8]) -> Result<Self, Error>
+    where
+        P::Challenge: PartialEq,
+        C: Codec<Challenge = P::Challenge>,
+    {
+        let protocol = &nizk.interactive_proof;
+        let challenge = protocol.deserialize_challenge(proof)?;
+        let challenge_size = protocol.serialize_challenge(&challenge).len();
+        let response = protocol.deserialize_response(&proof[challenge_size..])?;
+        let response_size = protocol.serialize_response(&response).len();
+
+        // Proof size check
+        if proof.len() != challenge_size + response_size {
+            return Err(Error::VerificationFailure);
+        }
+
+        Ok(Self {
+            challenge,
+            response,
+        })
+    }
+
+    /// Serializes the proof as `challenge || response`.
+    pub fn to_bytes<C>(&self, nizk: &Nizk<P, C>) -> Vec<u8>
+    where
+        P::Challenge: PartialEq,
+        C: Codec<Challenge = P::Challenge>,
+    {
+        let mut bytes = nizk.interactive_proof.serialize_challenge(&self.challenge);
+        bytes.extend_from_slice(&nizk.interactive_proof.serialize_response(&self.response));
+        bytes
+    }
+
+    /// Converts the proof into a batchable proof, recomputing the commitment from the challenge and response.
+    ///
+    /// # Errors
+    /// - Returns an error if the commitment cannot be recomputed, e.g. if the response is malformed.
+    pub fn to_batchable<C>(&self, nizk: &Nizk<P, C>) -> Result<BatchableProof<P>, Error>
+    where
+        P: SigmaProtocolSimulator,
+        P::Challenge: PartialEq,
+        P::Response: Clone,
+        C: Codec<Challenge = P::Challenge>,
+    {
+        let commitment = nizk
+            .interactive_proof
+            .simulate_commitment(&self.challenge, &self.response)?;
+        Ok(BatchableProof {
+            commitment,
+            response: self.response.clone(),
+        })
+    }
+}
+
 /// A Fiat-Shamir transformation of a [`SigmaProtocol`] into a non-interactive proof.
 ///
 /// [`Nizk`] wraps an interactive Sigma protocol `P`
@@ -36,6 +271,8 @@ type Transcript<P> = (
 /// It manages the domain separation, codec reset,
 /// proof generation, and proof verification.
 ///
+/// The prover nonces are sampled according to [`Nizk::nonce_mode`].
+///
 /// # Type Parameters
 /// - `P`: the Sigma protocol implementation.
 /// - `C`: the codec used for Fiat-Shamir.
@@ -50,6 +287,10 @@ where
     pub hash_state: C,
     /// Underlying interactive proof.
     pub interactive_proof: P,
+    /// How the prover samples its nonces.
+    pub nonce_mode: NonceMode,
+    /// The IV of the codec, which binds proofs and derived nonces to the session and the instance.
+    iv: [u8; 64],
 }
 
 impl<P, C> Nizk<P, C>
@@ -67,31 +308,85 @@ where
     /// # Returns
     /// A new [`Nizk`] that can generate and verify non-interactive proofs.
     pub fn new(session_identifier: &[u8], interactive_proof: P) -> Self {
-        let hash_state = C::new(
+        let iv = Self::compute_iv(session_identifier, &interactive_proof);
+        Self::from_iv(iv, interactive_proof)
+    }
+
+    /// Computes the IV of the codec for `interactive_proof` in a session,
+    /// from its protocol identifier and its instance label.
+    ///
+    /// Hashing the instance label can be expensive for large instances:
+    /// the IV can be computed once, persisted (see [`IvRegistry`](crate::IvRegistry)),
+    /// and passed to [`Nizk::from_iv`] or [`Nizk::from_pinned_iv`].
+    pub fn compute_iv(session_identifier: &[u8], interactive_proof: &P) -> [u8; 64] {
+        C::iv(
             interactive_proof.protocol_identifier().as_ref(),
             session_identifier,
             interactive_proof.instance_label().as_ref(),
-        );
-        Self {
-            hash_state,
-            interactive_proof,
-        }
+        )
     }
 
+    /// Constructs a new [`Nizk`] instance from a precomputed IV.
+    ///
+    /// The IV is trusted to match the instance: proofs for another instance,
+    /// or computed with another IV, fail to verify with [`Error::VerificationFailure`].
     pub fn from_iv(iv: [u8; 64], interactive_proof: P) -> Self {
         let hash_state = C::from_iv(iv);
         Self {
             hash_state,
             interactive_proof,
+            nonce_mode: NonceMode::default(),
+            iv,
         }
     }
 
+    /// Constructs a new [`Nizk`] instance from a pinned IV,
+    /// checking that the IV is the one of `interactive_proof` in the session.
+    ///
+    /// # Errors
+    /// Returns [`Error::IvMismatch`] if the instance, the protocol or the session
+    /// differ from the ones the IV was computed for.
+    pub fn from_pinned_iv(
+        iv: [u8; 64],
+        session_identifier: &[u8],
+        interactive_proof: P,
+    ) -> Result<Self, Error> {
+        if Self::compute_iv(session_identifier, &interactive_proof) != iv {
+            return Err(Error::IvMismatch);
+        }
+        Ok(Self::from_iv(iv, interactive_proof))
+    }
+
+    /// Returns the IV of the codec.
+    pub fn iv(&self) -> [u8; 64] {
+        self.iv
+    }
+
+    /// Sets how the prover samples its nonces.
+    ///
+    /// With [`NonceMode::Hedged`] and [`NonceMode::Deterministic`], proving fails with
+    /// [`Error::UnsupportedNonceMode`] if the protocol cannot serialize its witness
+    /// (see [`SigmaProtocol::serialize_witness`]).
+    pub fn with_nonce_mode(mut self, nonce_mode: NonceMode) -> Self {
+        self.nonce_mode = nonce_mode;
+        self
+    }
+
+    /// Returns the codec state with `message` absorbed after the instance IV,
+    /// with [`Codec::absorb_message`].
+    fn hash_state_with_message(&self, message: &[u8]) -> C {
+        let mut hash_state = self.hash_state.clone();
+        hash_state.absorb_message(message);
+        hash_state
+    }
+
     /// Generates a non-interactive proof for a witness.
     ///
     /// Executes the interactive protocol steps (commit, derive challenge via hash, respond),
     /// and checks the result locally for consistency.
     ///
     /// # Parameters
+    /// - `message`: The message the proof is bound to, if any.
     /// - `witness`: The secret witness for the Sigma protocol.
     /// - `rng`: A cryptographically secure random number generator.
     ///
@@ -105,16 +400,31 @@ where
     /// Panics if local verification fails.
     fn prove(
         &self,
+        message: Option<&[u8]>,
         witness: &P::Witness,
         rng: &mut (impl RngCore + CryptoRng),
     ) -> Result<Transcript<P>, Error> {
-        let mut hash_state = self.hash_state.clone();
-
-        let (commitment, prover_state) = self.interactive_proof.prover_commit(witness, rng)?;
+        let mut hash_state = match message {
+            Some(message) => self.hash_state_with_message(message),
+            None => self.hash_state.clone(),
+        };
+        let (commitment, prover_state) = match self.nonce_mode {
+            NonceMode::Random => self.interactive_proof.prover_commit(witness, rng)?,
+            nonce_mode => {
+                let serialized_witness = self
+                    .interactive_proof
+                    .serialize_witness(witness)
+                    .ok_or(Error::UnsupportedNonceMode)?;
+                let mut nonce_rng =
+                    NonceRng::new(nonce_mode, &self.iv, message, &serialized_witness, rng);
+                self.interactive_proof
+                    .prover_commit(witness, &mut nonce_rng)?
+            }
+        };
         // Fiat Shamir challenge
         let serialized_commitment = self.interactive_proof.serialize_commitment(&commitment);
         hash_state.prover_message(&serialized_commitment);
-        let challenge = hash_state.verifier_challenge();
+        let challenge = hash_state.verifier_challenge_in(self.interactive_proof.challenge_space());
         // Prover's response
         let response = self
             .interactive_proof
@@ -126,6 +436,7 @@ where
     /// Verifies a non-interactive proof using the Fiat-Shamir transformation.
     ///
     /// # Parameters
+    /// - `hash_state`: The codec state the commitment is absorbed into.
     /// - `commitment`: The commitment(s) sent by the prover.
     /// - `challenge`: The challenge allegedly derived via Fiat-Shamir.
     /// - `response`: The prover's response to the challenge.
@@ -140,16 +451,16 @@ where
     ///   - The response fails verification under the Sigma protocol.
     fn verify(
         &self,
+        mut hash_state: C,
         commitment: &P::Commitment,
         challenge: &P::Challenge,
         response: &P::Response,
     ) -> Result<(), Error> {
-        let mut hash_state = self.hash_state.clone();
-
         // Recompute the challenge
         let serialized_commitment = self.interactive_proof.serialize_commitment(commitment);
         hash_state.prover_message(&serialized_commitment);
-        let expected_challenge = hash_state.verifier_challenge();
+        let expected_challenge =
+            hash_state.verifier_challenge_in(self.interactive_proof.challenge_space());
         // Verification of the proof
         match *challenge == expected_challenge {
             true => self
@@ -158,6 +469,7 @@ where
             false => Err(Error::VerificationFailure),
         }
     }
+
     /// Generates a batchable, serialized non-interactive proof.
     ///
     /// # Parameters
@@ -174,11 +486,43 @@ where
         witness: &P::Witness,
         rng: &mut (impl RngCore + CryptoRng),
     ) -> Result<Vec<u8>, Error> {
-        let (commitment, _challenge, response) = self.prove(witness, rng)?;
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.interactive_proof.serialize_commitment(&commitment));
-        bytes.extend_from_slice(&self.interactive_proof.serialize_response(&response));
-        Ok(bytes)
+        self.prove_batchable_with_optional_message(None, witness, rng)
+    }
+
+    /// Generates a batchable, serialized non-interactive proof bound to `message`.
+    ///
+    /// The message is absorbed into the codec after the instance IV,
+    /// turning the proof into a signature of knowledge on `message`.
+    ///
+    /// # Parameters
+    /// - `witness`: The secret witness.
+    /// - `message`: The message to sign.
+    /// - `rng`: A cryptographically secure random number generator.
+    ///
+    /// # Returns
+    /// A serialized proof suitable for batch verification,
+    /// to be verified with [`Nizk::verify_batchable_with_message`].
+    pub fn prove_batchable_with_message(
+        &self,
+        witness: &P::Witness,
+        message: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Vec<u8>, Error> {
+        self.prove_batchable_with_optional_message(Some(message), witness, rng)
+    }
+
+    fn prove_batchable_with_optional_message(
+        &self,
+        message: Option<&[u8]>,
+        witness: &P::Witness,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Vec<u8>, Error> {
+        let (commitment, _challenge, response) = self.prove(message, witness, rng)?;
+        let proof = BatchableProof {
+            commitment,
+            response,
+        };
+        Ok(proof.to_bytes(self))
     }
 
     /// Verifies a batchable non-interactive proof.
@@ -195,40 +539,179 @@ where
     ///   - The challenge doesn't match the recomputed one from the commitment.
     ///   - The response fails verification under the Sigma protocol.
     pub fn verify_batchable(&self, proof: &[u8]) -> Result<(), Error> {
-        let commitment = self.interactive_proof.deserialize_commitment(proof)?;
-        let commitment_size = self
-            .interactive_proof
-            .serialize_commitment(&commitment)
-            .len();
-        let response = self
-            .interactive_proof
-            .deserialize_response(&proof[commitment_size..])?;
-        let response_size = self.interactive_proof.serialize_response(&response).len();
-
-        // Proof size check
-        if proof.len() != commitment_size + response_size {
-            return Err(Error::VerificationFailure);
-        }
+        self.verify_batchable_with_hash_state(self.hash_state.clone(), proof)
+    }
 
-        // Assert correct proof size
-        let total_expected_len =
-            commitment_size + self.interactive_proof.serialize_response(&response).len();
-        if proof.len() != total_expected_len {
-            return Err(Error::VerificationFailure);
-        }
+    /// Verifies a batchable non-interactive proof bound to `message`.
+    ///
+    /// # Parameters
+    /// - `proof`: A serialized batchable proof.
+    /// - `message`: The message the proof was generated for.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the proof is valid.
+    /// - `Err(Error)` if deserialization or verification fails.
+    ///
+    /// # Errors
+    /// - Returns [`Error::VerificationFailure`] if:
+    ///   - The challenge doesn't match the recomputed one from the message and the commitment.
+    ///   - The response fails verification under the Sigma protocol.
+    pub fn verify_batchable_with_message(&self, proof: &[u8], message: &[u8]) -> Result<(), Error> {
+        self.verify_batchable_with_hash_state(self.hash_state_with_message(message), proof)
+    }
 
-        let mut hash_state = self.hash_state.clone();
+    fn verify_batchable_with_hash_state(
+        &self,
+        mut hash_state: C,
+        proof: &[u8],
+    ) -> Result<(), Error> {
+        let BatchableProof {
+            commitment,
+            response,
+        } = BatchableProof::from_bytes(self, proof)?;
 
         // Recompute the challenge
         let serialized_commitment = self.interactive_proof.serialize_commitment(&commitment);
         hash_state.prover_message(&serialized_commitment);
-        let challenge = hash_state.verifier_challenge();
+        let challenge = hash_state.verifier_challenge_in(self.interactive_proof.challenge_space());
         // Verification of the proof
         self.interactive_proof
             .verifier(&commitment, &challenge, &response)
     }
 }
 
+/// The index of a proof in a batch, and the weighted scalars and bases of its verification equations.
+type WeightedEquations<G> = (usize, Vec<<G as Group>::Scalar>, Vec<G>);
+
+impl<P, C> Nizk<P, C>
+where
+    P: SigmaProtocolBatchVerifier,
+    P::Challenge: PartialEq,
+    C: Codec<Challenge = P::Challenge> + Clone,
+{
+    /// Verifies many batchable proofs at once.
+    ///
+    /// The verification equations of all proofs are combined with random weights
+    /// into a single multi-scalar multiplication. The proofs may be for different instances,
+    /// as long as their verification equations are expressed in the same group.
+    ///
+    /// # Parameters
+    /// - `proofs`: A list of pairs `(nizk, proof)`, where `proof` is a serialized batchable proof for `nizk`.
+    /// - `rng`: A cryptographically secure random number generator, used to sample the weights.
+    ///
+    /// # Returns
+    /// - `Ok(())` if all the proofs are valid.
+    /// - `Err(Error)` otherwise.
+    ///
+    /// # Errors
+    /// - Returns [`Error::BatchVerificationFailure`] with the indices of the invalid proofs if the batch does not verify.
+    ///   In that case, the failing batch is bisected to identify the culprits: with `k` invalid proofs
+    ///   out of `n`, this costs `O(k log n)` multi-scalar multiplications over parts of the batch.
+    pub fn verify_batchable_many(
+        proofs: &[(&Self, &[u8])],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(), Error> {
+        let proofs = proofs
+            .iter()
+            .map(|&(nizk, proof)| (nizk, proof, nizk.hash_state.clone()))
+            .collect::<Vec<_>>();
+        Self::verify_batchable_many_with_hash_states(&proofs, rng)
+    }
+
+    /// Verifies many batchable proofs bound to messages at once.
+    ///
+    /// This is [`Nizk::verify_batchable_many`] for proofs generated with
+    /// [`Nizk::prove_batchable_with_message`].
+    ///
+    /// # Parameters
+    /// - `proofs`: A list of triples `(nizk, proof, message)`, where `proof` is a serialized batchable proof
+    ///   for `nizk` bound to `message`.
+    /// - `rng`: A cryptographically secure random number generator, used to sample the weights.
+    ///
+    /// # Errors
+    /// - Returns [`Error::BatchVerificationFailure`] with the indices of the invalid proofs if the batch does not verify.
+    pub fn verify_batchable_many_with_messages(
+        proofs: &[(&Self, &[u8], &[u8])],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(), Error> {
+        let proofs = proofs
+            .iter()
+            .map(|&(nizk, proof, message)| (nizk, proof, nizk.hash_state_with_message(message)))
+            .collect::<Vec<_>>();
+        Self::verify_batchable_many_with_hash_states(&proofs, rng)
+    }
+
+    fn verify_batchable_many_with_hash_states(
+        proofs: &[(&Self, &[u8], C)],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<(), Error> {
+        // The weighted terms of the verification equations of each well-formed proof.
+        let mut batch = Vec::with_capacity(proofs.len());
+        let mut indices = Vec::new();
+
+        for (i, (nizk, proof, hash_state)) in proofs.iter().enumerate() {
+            let equations = BatchableProof::from_bytes(nizk, proof).and_then(|proof| {
+                let mut hash_state = hash_state.clone();
+                let serialized_commitment = nizk
+                    .interactive_proof
+                    .serialize_commitment(&proof.commitment);
+                hash_state.prover_message(&serialized_commitment);
+                let challenge =
+                    hash_state.verifier_challenge_in(nizk.interactive_proof.challenge_space());
+                nizk.interactive_proof.verification_equations(
+                    &proof.commitment,
+                    &challenge,
+                    &proof.response,
+                )
+            });
+            match equations {
+                Ok(equations) => {
+                    let mut scalars = Vec::new();
+                    let mut bases = Vec::new();
+                    for (equation_scalars, equation_bases) in equations {
+                        let weight = <P::Group as Group>::Scalar::random(&mut *rng);
+                        scalars.extend(equation_scalars.into_iter().map(|s| s * weight));
+                        bases.extend(equation_bases);
+                    }
+                    batch.push((i, scalars, bases));
+                }
+                Err(_) => indices.push(i),
+            }
+        }
+
+        Self::find_invalid_proofs(&batch, &mut indices);
+        if indices.is_empty() {
+            Ok(())
+        } else {
+            indices.sort_unstable();
+            Err(Error::BatchVerificationFailure { indices })
+        }
+    }
+
+    /// Appends to `indices` the indices of the proofs of `batch` whose weighted equations do not hold,
+    /// checking the whole batch at once and bisecting it when it fails.
+    fn find_invalid_proofs(batch: &[WeightedEquations<P::Group>], indices: &mut Vec<usize>) {
+        let scalars = batch
+            .iter()
+            .flat_map(|(_, scalars, _)| scalars.iter().copied())
+            .collect::<Vec<_>>();
+        let bases = batch
+            .iter()
+            .flat_map(|(_, _, bases)| bases.iter().copied())
+            .collect::<Vec<_>>();
+        if batch.is_empty() || bool::from(P::Group::msm(&scalars, &bases).is_identity()) {
+            return;
+        }
+        if let [(i, _, _)] = batch {
+            indices.push(*i);
+            return;
+        }
+        let (left, right) = batch.split_at(batch.len() / 2);
+        Self::find_invalid_proofs(left, indices);
+        Self::find_invalid_proofs(right, indices);
+    }
+}
+
 impl<P, C> Nizk<P, C>
 where
     P: SigmaProtocol + SigmaProtocolSimulator,
@@ -253,11 +736,42 @@ where
         witness: &P::Witness,
         rng: &mut (impl RngCore + CryptoRng),
     ) -> Result<Vec<u8>, Error> {
-        let (_commitment, challenge, response) = self.prove(witness, rng)?;
-        let mut bytes = Vec::new();
-        bytes.extend_from_slice(&self.interactive_proof.serialize_challenge(&challenge));
-        bytes.extend_from_slice(&self.interactive_proof.serialize_response(&response));
-        Ok(bytes)
+        self.prove_compact_with_optional_message(None, witness, rng)
+    }
+
+    /// Generates a compact serialized proof bound to `message`.
+    ///
+    /// The message is absorbed into the codec after the instance IV,
+    /// turning the proof into a signature of knowledge on `message`.
+    ///
+    /// # Parameters
+    /// - `witness`: The secret witness.
+    /// - `message`: The message to sign.
+    /// - `rng`: A cryptographically secure random number generator.
+    ///
+    /// # Returns
+    /// A compact, serialized proof, to be verified with [`Nizk::verify_compact_with_message`].
+    pub fn prove_compact_with_message(
+        &self,
+        witness: &P::Witness,
+        message: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Vec<u8>, Error> {
+        self.prove_compact_with_optional_message(Some(message), witness, rng)
+    }
+
+    fn prove_compact_with_optional_message(
+        &self,
+        message: Option<&[u8]>,
+        witness: &P::Witness,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Vec<u8>, Error> {
+        let (_commitment, challenge, response) = self.prove(message, witness, rng)?;
+        let proof = CompactProof {
+            challenge,
+            response,
+        };
+        Ok(proof.to_bytes(self))
     }
 
     /// Verifies a compact proof.
@@ -276,31 +790,175 @@ where
     ///   - Deserialization fails.
     ///   - The recomputed commitment or response is invalid under the Sigma protocol.
     pub fn verify_compact(&self, proof: &[u8]) -> Result<(), Error> {
-        // Deserialize challenge and response from compact proof
-        let challenge = self.interactive_proof.deserialize_challenge(proof)?;
-        let challenge_size = self.interactive_proof.serialize_challenge(&challenge).len();
-        let response = self
-            .interactive_proof
-            .deserialize_response(&proof[challenge_size..])?;
-        let response_size = self.interactive_proof.serialize_response(&response).len();
+        self.verify_compact_with_hash_state(self.hash_state.clone(), proof)
+    }
 
-        // Proof size check
-        if proof.len() != challenge_size + response_size {
-            return Err(Error::VerificationFailure);
-        }
+    /// Verifies a compact proof bound to `message`.
+    ///
+    /// # Parameters
+    /// - `proof`: A compact serialized proof.
+    /// - `message`: The message the proof was generated for.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the proof is valid.
+    /// - `Err(Error)` if deserialization or verification fails.
+    ///
+    /// # Errors
+    /// - Returns [`Error::VerificationFailure`] if:
+    ///   - Deserialization fails.
+    ///   - The challenge doesn't match the recomputed one from the message and the commitment.
+    pub fn verify_compact_with_message(&self, proof: &[u8], message: &[u8]) -> Result<(), Error> {
+        self.verify_compact_with_hash_state(self.hash_state_with_message(message), proof)
+    }
 
-        // Assert correct proof size
-        let total_expected_len =
-            challenge_size + self.interactive_proof.serialize_response(&response).len();
-        if proof.len() != total_expected_len {
-            return Err(Error::VerificationFailure);
-        }
+    fn verify_compact_with_hash_state(&self, hash_state: C, proof: &[u8]) -> Result<(), Error> {
+        // Deserialize challenge and response from compact proof
+        let CompactProof {
+            challenge,
+            response,
+        } = CompactProof::from_bytes(self, proof)?;
 
         // Compute the commitments
         let commitment = self
             .interactive_proof
             .simulate_commitment(&challenge, &response)?;
         // Verify the proof
-        self.verify(&commitment, &challenge, &response)
+        self.verify(hash_state, &commitment, &challenge, &response)
+    }
+}
+
+/// Serde support for the proofs of [`CanonicalLinearRelation`](crate::linear_relation::CanonicalLinearRelation)
+/// and [`ComposedRelation`](crate::composition::ComposedRelation).
+///
+/// Proofs are serialized as structs with fields `commitment` and `response` (batchable proofs),
+/// or `challenge` and `response` (compact proofs).
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use alloc::vec::Vec;
+    use group::prime::PrimeGroup;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use subtle::{ConditionallySelectable, ConstantTimeEq};
+
+    use super::{BatchableProof, CompactProof};
+    use crate::composition::{ComposedCommitment, ComposedRelation, ComposedResponse};
+    use crate::group::serde_helpers::{Element, Scalar};
+    use crate::linear_relation::CanonicalLinearRelation;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "BatchableProof")]
+    struct BatchableProofRepr<C, R> {
+        commitment: C,
+        response: R,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(rename = "CompactProof")]
+    struct CompactProofRepr<C, R> {
+        challenge: C,
+        response: R,
+    }
+
+    type CanonicalBatchableProofRepr<G> =
+        BatchableProofRepr<Vec<Element<G>>, Vec<Scalar<<G as group::Group>::Scalar>>>;
+    type CanonicalCompactProofRepr<G> = CompactProofRepr<
+        Scalar<<G as group::Group>::Scalar>,
+        Vec<Scalar<<G as group::Group>::Scalar>>,
+    >;
+
+    impl<G: PrimeGroup> Serialize for BatchableProof<CanonicalLinearRelation<G>> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            CanonicalBatchableProofRepr::<G> {
+                commitment: self.commitment.iter().copied().map(Element).collect(),
+                response: self.response.iter().copied().map(Scalar).collect(),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de, G: PrimeGroup> Deserialize<'de> for BatchableProof<CanonicalLinearRelation<G>> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = CanonicalBatchableProofRepr::<G>::deserialize(deserializer)?;
+            Ok(Self {
+                commitment: repr.commitment.into_iter().map(|e| e.0).collect(),
+                response: repr.response.into_iter().map(|s| s.0).collect(),
+            })
+        }
+    }
+
+    impl<G: PrimeGroup> Serialize for CompactProof<CanonicalLinearRelation<G>> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            CanonicalCompactProofRepr::<G> {
+                challenge: Scalar(self.challenge),
+                response: self.response.iter().copied().map(Scalar).collect(),
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de, G: PrimeGroup> Deserialize<'de> for CompactProof<CanonicalLinearRelation<G>> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = CanonicalCompactProofRepr::<G>::deserialize(deserializer)?;
+            Ok(Self {
+                challenge: repr.challenge.0,
+                response: repr.response.into_iter().map(|s| s.0).collect(),
+            })
+        }
+    }
+
+    impl<G> Serialize for BatchableProof<ComposedRelation<G>>
+    where
+        G: PrimeGroup + ConstantTimeEq + ConditionallySelectable,
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            BatchableProofRepr {
+                commitment: &self.commitment,
+                response: &self.response,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de, G> Deserialize<'de> for BatchableProof<ComposedRelation<G>>
+    where
+        G: PrimeGroup + ConstantTimeEq + ConditionallySelectable,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr =
+                BatchableProofRepr::<ComposedCommitment<G>, ComposedResponse<G>>::deserialize(
+                    deserializer,
+                )?;
+            Ok(Self {
+                commitment: repr.commitment,
+                response: repr.response,
+            })
+        }
+    }
+
+    impl<G> Serialize for CompactProof<ComposedRelation<G>>
+    where
+        G: PrimeGroup + ConstantTimeEq + ConditionallySelectable,
+    {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            CompactProofRepr {
+                challenge: Scalar(self.challenge),
+                response: &self.response,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de, G> Deserialize<'de> for CompactProof<ComposedRelation<G>>
+    where
+        G: PrimeGroup + ConstantTimeEq + ConditionallySelectable,
+    {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let repr = CompactProofRepr::<Scalar<G::Scalar>, ComposedResponse<G>>::deserialize(
+                deserializer,
+            )?;
+            Ok(Self {
+                challenge: repr.challenge.0,
+                response: repr.response,
+            })
+        }
     }
 }
@@ -0,0 +1,269 @@
+//! Randomized Fischlin transformation for [`SigmaProtocol`]s.
+//!
+//! This module defines [`FischlinNizk`], an alternative to [`Nizk`](crate::Nizk)
+//! producing non-interactive proofs that are straight-line extractable,
+//! as required for instance by proofs in the UC model.
+//!
+//! It implements the randomized Fischlin transform of Kondi and shelat
+//! ("Improved Straight-Line Extraction in the Random Oracle Model With Applications to Signature Aggregation", 2022).
+
+use crate::codec::compute_iv;
+use crate::duplex_sponge::DuplexSpongeInterface;
+use crate::errors::Error;
+use crate::traits::{SigmaProtocol, SigmaProtocolSimulator, SigmaProtocolTrialResponse};
+use alloc::vec::Vec;
+use ff::PrimeField;
+
+#[cfg(feature = "std")]
+use rand::{CryptoRng, RngCore};
+#[cfg(not(feature = "std"))]
+use rand_core::{CryptoRng, RngCore};
+
+/// Domain separator absorbed after the instance IV.
+const FISCHLIN_DOMAIN_SEPARATOR: &[u8] = b"sigma-proofs/randomized-fischlin";
+
+/// Parameters of the randomized Fischlin transform.
+///
+/// The soundness error is about `2^-(repetitions * work_bits)`,
+/// while the prover computes on average `repetitions * 2^work_bits` responses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FischlinParameters {
+    repetitions: u32,
+    work_bits: u32,
+}
+
+impl FischlinParameters {
+    /// Creates parameters with `repetitions` parallel executions of the Sigma protocol,
+    /// each requiring `work_bits` leading zero bits from its hash.
+    ///
+    /// # Errors
+    /// - [`Error::InvalidParameters`] if `repetitions` or `work_bits` is zero,
+    ///   since the verifier would then accept trivial proofs.
+    pub fn new(repetitions: u32, work_bits: u32) -> Result<Self, Error> {
+        if repetitions == 0 || work_bits == 0 {
+            return Err(Error::InvalidParameters);
+        }
+        Ok(Self {
+            repetitions,
+            work_bits,
+        })
+    }
+
+    /// Returns the number of parallel executions of the Sigma protocol.
+    pub fn repetitions(&self) -> u32 {
+        self.repetitions
+    }
+
+    /// Returns the number of leading zero bits required from the hash of each execution.
+    pub fn work_bits(&self) -> u32 {
+        self.work_bits
+    }
+}
+
+impl Default for FischlinParameters {
+    /// 16 repetitions of 8 bits of work, for 128 bits of security.
+    fn default() -> Self {
+        Self {
+            repetitions: 16,
+            work_bits: 8,
+        }
+    }
+}
+
+/// A randomized Fischlin transformation of a [`SigmaProtocol`] into a non-interactive proof.
+///
+/// The prover runs `repetitions` parallel executions of the Sigma protocol.
+/// For each execution, it answers challenges sampled at random until the hash of
+/// `(all commitments, index, challenge, response)` starts with `work_bits` zero bits.
+///
+/// # Proof format
+/// A proof is the concatenation, for each repetition `i` in order, of
+/// `challenge_i || response_i`, serialized with the methods of the Sigma protocol.
+/// The commitments are recomputed by the verifier with [`SigmaProtocolSimulator::simulate_commitment`].
+///
+/// # Hashing
+/// The hash is a duplex sponge initialized with the IV of the instance
+/// (see [`compute_iv`]), which then absorbs:
+/// - the domain separator `"sigma-proofs/randomized-fischlin"`,
+/// - `repetitions` and `work_bits`, as 32-bit big-endian integers,
+/// - the serialized commitments of all the repetitions.
+///
+/// For each trial, a copy of this state absorbs the index of the repetition (32-bit big-endian),
+/// the serialized challenge and the serialized response, and squeezes `⌈work_bits / 8⌉` bytes.
+///
+/// # Type Parameters
+/// - `P`: the Sigma protocol implementation.
+/// - `H`: the duplex sponge used as random oracle.
+pub struct FischlinNizk<P, H>
+where
+    P: SigmaProtocol + SigmaProtocolSimulator,
+    H: DuplexSpongeInterface,
+{
+    /// Sponge state after absorbing the instance IV.
+    pub hash_state: H,
+    /// Underlying interactive proof.
+    pub interactive_proof: P,
+    /// Repetition and work parameters.
+    pub parameters: FischlinParameters,
+}
+
+impl<P, H> FischlinNizk<P, H>
+where
+    P: SigmaProtocol + SigmaProtocolSimulator + SigmaProtocolTrialResponse,
+    P::Challenge: PrimeField,
+    H: DuplexSpongeInterface + Clone,
+{
+    /// Constructs a new [`FischlinNizk`] instance.
+    ///
+    /// # Parameters
+    /// - `session_identifier`: Domain separation tag for the hash function (e.g., protocol name or context).
+    /// - `interactive_proof`: An instance of the interactive Sigma protocol.
+    /// - `parameters`: The repetition and work parameters.
+    pub fn new(
+        session_identifier: &[u8],
+        interactive_proof: P,
+        parameters: FischlinParameters,
+    ) -> Self {
+        let iv = compute_iv::<H>(
+            interactive_proof.protocol_identifier().as_ref(),
+            session_identifier,
+            interactive_proof.instance_label().as_ref(),
+        );
+        Self {
+            hash_state: H::new(iv),
+            interactive_proof,
+            parameters,
+        }
+    }
+
+    /// Returns the sponge state after absorbing the parameters and the commitments.
+    fn commitments_hash_state(&self, commitments: &[P::Commitment]) -> H {
+        let mut hash_state = self.hash_state.clone();
+        hash_state.absorb(FISCHLIN_DOMAIN_SEPARATOR);
+        hash_state.absorb(&self.parameters.repetitions.to_be_bytes());
+        hash_state.absorb(&self.parameters.work_bits.to_be_bytes());
+        for commitment in commitments {
+            hash_state.absorb(&self.interactive_proof.serialize_commitment(commitment));
+        }
+        hash_state
+    }
+
+    /// Checks that the hash of a trial starts with `work_bits` zero bits.
+    fn is_valid_trial(
+        &self,
+        hash_state: &H,
+        index: u32,
+        challenge: &P::Challenge,
+        response: &P::Response,
+    ) -> bool {
+        let work_bits = self.parameters.work_bits as usize;
+        let mut hash_state = hash_state.clone();
+        hash_state.absorb(&index.to_be_bytes());
+        hash_state.absorb(&self.interactive_proof.serialize_challenge(challenge));
+        hash_state.absorb(&self.interactive_proof.serialize_response(response));
+        let digest = hash_state.squeeze(work_bits.div_ceil(8));
+
+        digest.iter().enumerate().all(|(i, byte)| {
+            let bits = work_bits.saturating_sub(8 * i).min(8);
+            // Check the `bits` most significant bits of the byte.
+            bits == 0 || byte >> (8 - bits) == 0
+        })
+    }
+
+    /// Generates a non-interactive proof for a witness.
+    ///
+    /// # Parameters
+    /// - `witness`: The secret witness for the Sigma protocol.
+    /// - `rng`: A cryptographically secure random number generator.
+    ///
+    /// # Returns
+    /// The serialized proof, in the format described in the [`FischlinNizk`] documentation.
+    ///
+    /// # Errors
+    /// - Returns an error if the witness is invalid for the instance.
+    pub fn prove(
+        &self,
+        witness: &P::Witness,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Vec<u8>, Error> {
+        let mut commitments = Vec::with_capacity(self.parameters.repetitions as usize);
+        let mut prover_states = Vec::with_capacity(self.parameters.repetitions as usize);
+        for _ in 0..self.parameters.repetitions {
+            let (commitment, prover_state) = self.interactive_proof.prover_commit(witness, rng)?;
+            commitments.push(commitment);
+            prover_states.push(prover_state);
+        }
+        let hash_state = self.commitments_hash_state(&commitments);
+
+        let challenge_space = self.interactive_proof.challenge_space();
+        let mut proof = Vec::new();
+        for (index, prover_state) in (0..self.parameters.repetitions).zip(prover_states) {
+            // Each trial succeeds with probability 2^-work_bits.
+            loop {
+                let challenge = challenge_space.random(&mut *rng);
+                let response = self
+                    .interactive_proof
+                    .prover_trial_response(&prover_state, &challenge)?;
+                if self.is_valid_trial(&hash_state, index, &challenge, &response) {
+                    proof
+                        .extend_from_slice(&self.interactive_proof.serialize_challenge(&challenge));
+                    proof.extend_from_slice(&self.interactive_proof.serialize_response(&response));
+                    break;
+                }
+            }
+        }
+        Ok(proof)
+    }
+
+    /// Verifies a non-interactive proof.
+    ///
+    /// # Parameters
+    /// - `proof`: A serialized proof, in the format described in the [`FischlinNizk`] documentation.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the proof is valid.
+    /// - `Err(Error)` if deserialization or verification fails.
+    ///
+    /// # Errors
+    /// - Returns [`Error::VerificationFailure`] if:
+    ///   - The proof is malformed or has trailing bytes.
+    ///   - The hash of some repetition does not start with `work_bits` zero bits.
+    ///   - Some repetition fails verification under the Sigma protocol.
+    pub fn verify(&self, proof: &[u8]) -> Result<(), Error> {
+        let mut commitments = Vec::with_capacity(self.parameters.repetitions as usize);
+        let mut transcripts = Vec::with_capacity(self.parameters.repetitions as usize);
+        let mut offset = 0;
+        for _ in 0..self.parameters.repetitions {
+            let challenge = self
+                .interactive_proof
+                .deserialize_challenge(&proof[offset..])?;
+            offset += self.interactive_proof.serialize_challenge(&challenge).len();
+            let response = self
+                .interactive_proof
+                .deserialize_response(&proof[offset..])?;
+            offset += self.interactive_proof.serialize_response(&response).len();
+            let commitment = self
+                .interactive_proof
+                .simulate_commitment(&challenge, &response)?;
+            commitments.push(commitment);
+            transcripts.push((challenge, response));
+        }
+
+        // Proof size check
+        if offset != proof.len() {
+            return Err(Error::VerificationFailure);
+        }
+
+        let hash_state = self.commitments_hash_state(&commitments);
+        for (index, (commitment, (challenge, response))) in
+            (0..).zip(commitments.iter().zip(&transcripts))
+        {
+            if !self.is_valid_trial(&hash_state, index, challenge, response) {
+                return Err(Error::VerificationFailure);
+            }
+            self.interactive_proof
+                .verifier(commitment, challenge, response)?;
+        }
+        Ok(())
+    }
+}
@@ -3,3 +3,10 @@ pub mod msm;
 
 /// Implementation of batch serialization functions for scalars and points.
 pub mod serialization;
+
+/// Serde helpers for scalars and points.
+#[cfg(feature = "serde")]
+pub mod serde_helpers;
+
+/// Zeroization of scalars for types holding secrets.
+pub mod zeroize_helpers;
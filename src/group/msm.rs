@@ -84,11 +84,17 @@ fn msm_internal<G: PrimeGroup>(bases: &[G], scalars: &[G::Scalar]) -> G {
         window_buckets.push((window, vec![G::identity(); buckets_num]));
     }
 
+    // Scalar representations are little-endian for most fields, but big-endian for some (e.g. secp256k1).
+    let big_endian = <G::Scalar as ff::Field>::ONE.to_repr().as_ref()[0] != 1;
+
     for (scalar, base) in scalars.iter().zip(bases) {
-        for (w, bucket) in window_buckets.iter_mut() {
-            let scalar_repr = scalar.to_repr();
-            let scalar_bytes = scalar_repr.as_ref();
+        let mut scalar_repr = scalar.to_repr();
+        if big_endian {
+            scalar_repr.as_mut().reverse();
+        }
+        let scalar_bytes = scalar_repr.as_ref();
 
+        for (w, bucket) in window_buckets.iter_mut() {
             // Extract the relevant bits for this window
             let window_start = *w;
             let window_end = (window_start + c).min(scalar_bytes.len() * 8);
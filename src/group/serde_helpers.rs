@@ -0,0 +1,214 @@
+//! Serde helpers for group elements and scalars.
+//!
+//! Group elements and scalars are encoded with the same canonical encodings as
+//! [`super::serialization`]: compressed group elements, and big-endian scalars.
+//! Human-readable formats (e.g. JSON) encode them as hex strings,
+//! binary formats (e.g. CBOR) as byte strings.
+//!
+//! The submodules are meant to be used with `#[serde(with = "...")]`.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use ff::PrimeField;
+use group::GroupEncoding;
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a byte string or a hex string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        hex::decode(v).map_err(E::custom)
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        self.visit_str(&v)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+/// Byte strings, encoded as hex in human-readable formats.
+pub mod bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&hex::encode(bytes))
+        } else {
+            serializer.serialize_bytes(bytes)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(BytesVisitor)
+        } else {
+            deserializer.deserialize_bytes(BytesVisitor)
+        }
+    }
+}
+
+/// A single group element, in its canonical compressed encoding.
+pub mod element {
+    use super::*;
+
+    pub fn serialize<G: GroupEncoding, S: Serializer>(
+        element: &G,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        bytes::serialize(element.to_bytes().as_ref(), serializer)
+    }
+
+    pub fn deserialize<'de, G: GroupEncoding, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<G, D::Error> {
+        let data = bytes::deserialize(deserializer)?;
+        let mut repr = G::Repr::default();
+        if data.len() != repr.as_ref().len() {
+            return Err(de::Error::invalid_length(data.len(), &"a group element"));
+        }
+        repr.as_mut().copy_from_slice(&data);
+        Option::from(G::from_bytes(&repr))
+            .ok_or_else(|| de::Error::custom("invalid group element encoding"))
+    }
+}
+
+/// A single scalar, in its canonical big-endian encoding.
+pub mod scalar {
+    use super::*;
+
+    pub fn serialize<F: PrimeField, S: Serializer>(
+        scalar: &F,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut data = scalar.to_repr().as_ref().to_vec();
+        data.reverse();
+        bytes::serialize(&data, serializer)
+    }
+
+    pub fn deserialize<'de, F: PrimeField, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<F, D::Error> {
+        let mut data = bytes::deserialize(deserializer)?;
+        let mut repr = F::Repr::default();
+        if data.len() != repr.as_ref().len() {
+            return Err(de::Error::invalid_length(data.len(), &"a scalar"));
+        }
+        data.reverse();
+        repr.as_mut().copy_from_slice(&data);
+        Option::from(F::from_repr(repr)).ok_or_else(|| de::Error::custom("non-canonical scalar"))
+    }
+}
+
+/// Wrapper giving a group element serde support.
+pub struct Element<G>(pub G);
+
+impl<G: GroupEncoding> Serialize for Element<G> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        element::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, G: GroupEncoding> Deserialize<'de> for Element<G> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        element::deserialize(deserializer).map(Element)
+    }
+}
+
+/// Wrapper giving a scalar serde support.
+pub struct Scalar<F>(pub F);
+
+impl<F: PrimeField> Serialize for Scalar<F> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        scalar::serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, F: PrimeField> Deserialize<'de> for Scalar<F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        scalar::deserialize(deserializer).map(Scalar)
+    }
+}
+
+/// A list of group elements.
+pub mod elements {
+    use super::*;
+
+    pub fn serialize<G: GroupEncoding + Copy, S: Serializer>(
+        elements: &[G],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(elements.iter().map(|element| Element(*element)))
+    }
+
+    pub fn deserialize<'de, G: GroupEncoding, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<G>, D::Error> {
+        let elements = Vec::<Element<G>>::deserialize(deserializer)?;
+        Ok(elements.into_iter().map(|element| element.0).collect())
+    }
+}
+
+/// A list of group elements, some of which may be unassigned.
+pub mod optional_elements {
+    use super::*;
+
+    pub fn serialize<G: GroupEncoding + Copy, S: Serializer>(
+        elements: &[Option<G>],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(elements.iter().map(|element| element.map(Element)))
+    }
+
+    pub fn deserialize<'de, G: GroupEncoding, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Option<G>>, D::Error> {
+        let elements = Vec::<Option<Element<G>>>::deserialize(deserializer)?;
+        Ok(elements
+            .into_iter()
+            .map(|element| element.map(|element| element.0))
+            .collect())
+    }
+}
+
+/// A list of scalars.
+pub mod scalars {
+    use super::*;
+
+    pub fn serialize<F: PrimeField, S: Serializer>(
+        scalars: &[F],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(scalars.iter().map(|scalar| Scalar(*scalar)))
+    }
+
+    pub fn deserialize<'de, F: PrimeField, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<F>, D::Error> {
+        let scalars = Vec::<Scalar<F>>::deserialize(deserializer)?;
+        Ok(scalars.into_iter().map(|scalar| scalar.0).collect())
+    }
+}
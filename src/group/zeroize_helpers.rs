@@ -0,0 +1,52 @@
+//! Zeroization of generic scalars.
+//!
+//! The [`zeroize`] crate cannot be implemented for the scalars of a generic [`group::Group`],
+//! so this module overwrites them with volatile writes of [`Field::ZERO`],
+//! following the approach of the [`zeroize`] crate.
+
+use alloc::vec::Vec;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use ff::{Field, PrimeField};
+use subtle::Choice;
+use zeroize::Zeroize;
+
+/// Overwrites `value` with `zero`, without the write being optimized away.
+fn volatile_write<T: Copy>(value: &mut T, zero: T) {
+    // SAFETY: `value` is a valid, aligned and exclusive reference, and `T: Copy` has no destructor to skip.
+    unsafe { core::ptr::write_volatile(value, zero) };
+}
+
+/// Overwrites a scalar with zero.
+pub fn zeroize_scalar<F: Field>(scalar: &mut F) {
+    volatile_write(scalar, F::ZERO);
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Overwrites all the scalars of the vector with zero and clears it.
+pub fn zeroize_scalars<F: Field>(scalars: &mut Vec<F>) {
+    for scalar in scalars.iter_mut() {
+        volatile_write(scalar, F::ZERO);
+    }
+    compiler_fence(Ordering::SeqCst);
+    scalars.clear();
+}
+
+/// Overwrites a choice with `0`.
+pub fn zeroize_choice(choice: &mut Choice) {
+    volatile_write(choice, Choice::from(0));
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Appends secret scalars to `bytes`, in the format of
+/// [`serialize_scalars`](crate::group::serialization::serialize_scalars).
+///
+/// The representations of the scalars are overwritten with zeros once copied.
+/// `bytes` should have enough spare capacity, so that no reallocation leaves a copy of them behind.
+pub fn extend_secret_scalars<F: PrimeField>(bytes: &mut Vec<u8>, scalars: &[F]) {
+    for scalar in scalars {
+        let mut repr = scalar.to_repr();
+        bytes.extend(repr.as_ref().iter().rev());
+        repr.as_mut().zeroize();
+    }
+}
@@ -0,0 +1,232 @@
+//! Interactive execution of [`SigmaProtocol`]s.
+//!
+//! This module defines [`InteractiveProver`] and [`InteractiveVerifier`],
+//! two state machines running the 3-move protocol between two parties:
+//!
+//! 1. the prover sends a commitment,
+//! 2. the verifier answers with a challenge sampled at random,
+//! 3. the prover sends its response, which the verifier checks.
+//!
+//! Unlike [`Nizk`](crate::Nizk) proofs, interactive transcripts are not transferable:
+//! they only convince the verifier that chose the challenge.
+//!
+//! Messages are framed as `tag || length || payload`, where `tag` is one byte identifying
+//! the message type and `length` is the payload length as a 32-bit big-endian integer.
+//! The payload is the output of the `serialize_*` methods of the protocol.
+//!
+//! Each session runs the protocol exactly once. Once a message has been consumed,
+//! the session moves forward even if the message was malformed,
+//! so that the prover never answers two challenges for the same commitment.
+
+use crate::errors::Error;
+use crate::traits::SigmaProtocol;
+use alloc::vec::Vec;
+use core::mem;
+use ff::PrimeField;
+
+#[cfg(feature = "std")]
+use rand::{CryptoRng, RngCore};
+#[cfg(not(feature = "std"))]
+use rand_core::{CryptoRng, RngCore};
+
+/// Tag of the prover's commitment message.
+const COMMITMENT_TAG: u8 = 1;
+/// Tag of the verifier's challenge message.
+const CHALLENGE_TAG: u8 = 2;
+/// Tag of the prover's response message.
+const RESPONSE_TAG: u8 = 3;
+
+/// Frames `payload` as `tag || length || payload`.
+fn encode_message(tag: u8, payload: &[u8]) -> Result<Vec<u8>, Error> {
+    let length = u32::try_from(payload.len()).map_err(|_| Error::InvalidProtocolState)?;
+    let mut message = Vec::with_capacity(5 + payload.len());
+    message.push(tag);
+    message.extend_from_slice(&length.to_be_bytes());
+    message.extend_from_slice(payload);
+    Ok(message)
+}
+
+/// Extracts the payload of a framed message, checking its tag and length.
+///
+/// # Errors
+/// - Returns [`Error::VerificationFailure`] if the message is not a well-formed message with tag `tag`.
+fn decode_message(tag: u8, message: &[u8]) -> Result<&[u8], Error> {
+    match message {
+        [message_tag, l0, l1, l2, l3, payload @ ..]
+            if *message_tag == tag
+                && u32::from_be_bytes([*l0, *l1, *l2, *l3]) as usize == payload.len() =>
+        {
+            Ok(payload)
+        }
+        _ => Err(Error::VerificationFailure),
+    }
+}
+
+enum ProverStage<S> {
+    Initial,
+    Committed(S),
+    Finished,
+}
+
+/// The prover side of an interactive [`SigmaProtocol`] session.
+///
+/// The prover first sends a commitment with [`InteractiveProver::commit`],
+/// then answers the verifier's challenge with [`InteractiveProver::respond`].
+pub struct InteractiveProver<P: SigmaProtocol> {
+    /// Underlying interactive proof.
+    pub interactive_proof: P,
+    stage: ProverStage<P::ProverState>,
+}
+
+impl<P: SigmaProtocol> InteractiveProver<P> {
+    /// Creates a new prover session for the given protocol.
+    pub fn new(interactive_proof: P) -> Self {
+        Self {
+            interactive_proof,
+            stage: ProverStage::Initial,
+        }
+    }
+
+    /// Computes the commitment for `witness` and returns the framed commitment message.
+    ///
+    /// # Errors
+    /// - Returns [`Error::InvalidProtocolState`] if the prover already committed.
+    /// - Returns any error raised by the protocol when committing.
+    pub fn commit(
+        &mut self,
+        witness: &P::Witness,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Vec<u8>, Error> {
+        if !matches!(self.stage, ProverStage::Initial) {
+            return Err(Error::InvalidProtocolState);
+        }
+        self.stage = ProverStage::Finished;
+
+        let (commitment, prover_state) = self.interactive_proof.prover_commit(witness, rng)?;
+        let message = encode_message(
+            COMMITMENT_TAG,
+            &self.interactive_proof.serialize_commitment(&commitment),
+        )?;
+        self.stage = ProverStage::Committed(prover_state);
+        Ok(message)
+    }
+
+    /// Answers the framed challenge message and returns the framed response message.
+    ///
+    /// The prover state is consumed: the session cannot answer another challenge.
+    ///
+    /// # Errors
+    /// - Returns [`Error::InvalidProtocolState`] if the prover did not commit, or already responded.
+    /// - Returns [`Error::VerificationFailure`] if the challenge message is malformed.
+    pub fn respond(&mut self, challenge_message: &[u8]) -> Result<Vec<u8>, Error> {
+        let prover_state = match mem::replace(&mut self.stage, ProverStage::Finished) {
+            ProverStage::Committed(prover_state) => prover_state,
+            stage => {
+                self.stage = stage;
+                return Err(Error::InvalidProtocolState);
+            }
+        };
+
+        let payload = decode_message(CHALLENGE_TAG, challenge_message)?;
+        let challenge = self.interactive_proof.deserialize_challenge(payload)?;
+        if self.interactive_proof.serialize_challenge(&challenge).len() != payload.len() {
+            return Err(Error::VerificationFailure);
+        }
+        let response = self
+            .interactive_proof
+            .prover_response(prover_state, &challenge)?;
+        encode_message(
+            RESPONSE_TAG,
+            &self.interactive_proof.serialize_response(&response),
+        )
+    }
+}
+
+enum VerifierStage<P: SigmaProtocol> {
+    Initial,
+    Challenged(P::Commitment, P::Challenge),
+    Finished,
+}
+
+/// The verifier side of an interactive [`SigmaProtocol`] session.
+///
+/// The verifier answers the prover's commitment with a random challenge using
+/// [`InteractiveVerifier::challenge`], then checks the prover's response with
+/// [`InteractiveVerifier::verify`].
+pub struct InteractiveVerifier<P: SigmaProtocol> {
+    /// Underlying interactive proof.
+    pub interactive_proof: P,
+    stage: VerifierStage<P>,
+}
+
+impl<P> InteractiveVerifier<P>
+where
+    P: SigmaProtocol,
+    P::Challenge: PrimeField,
+{
+    /// Creates a new verifier session for the given protocol.
+    pub fn new(interactive_proof: P) -> Self {
+        Self {
+            interactive_proof,
+            stage: VerifierStage::Initial,
+        }
+    }
+
+    /// Reads the framed commitment message and returns a framed challenge message,
+    /// with the challenge sampled uniformly at random from `rng` in the challenge space of the protocol.
+    ///
+    /// # Errors
+    /// - Returns [`Error::InvalidProtocolState`] if the verifier already received a commitment.
+    /// - Returns [`Error::VerificationFailure`] if the commitment message is malformed.
+    pub fn challenge(
+        &mut self,
+        commitment_message: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Vec<u8>, Error> {
+        if !matches!(self.stage, VerifierStage::Initial) {
+            return Err(Error::InvalidProtocolState);
+        }
+        self.stage = VerifierStage::Finished;
+
+        let payload = decode_message(COMMITMENT_TAG, commitment_message)?;
+        let commitment = self.interactive_proof.deserialize_commitment(payload)?;
+        if self
+            .interactive_proof
+            .serialize_commitment(&commitment)
+            .len()
+            != payload.len()
+        {
+            return Err(Error::VerificationFailure);
+        }
+        let challenge = self.interactive_proof.challenge_space().random(rng);
+        let message = encode_message(
+            CHALLENGE_TAG,
+            &self.interactive_proof.serialize_challenge(&challenge),
+        )?;
+        self.stage = VerifierStage::Challenged(commitment, challenge);
+        Ok(message)
+    }
+
+    /// Reads the framed response message and checks the transcript.
+    ///
+    /// # Errors
+    /// - Returns [`Error::InvalidProtocolState`] if no challenge was sent, or the response was already checked.
+    /// - Returns [`Error::VerificationFailure`] if the response message is malformed or the transcript is invalid.
+    pub fn verify(&mut self, response_message: &[u8]) -> Result<(), Error> {
+        let (commitment, challenge) = match mem::replace(&mut self.stage, VerifierStage::Finished) {
+            VerifierStage::Challenged(commitment, challenge) => (commitment, challenge),
+            stage => {
+                self.stage = stage;
+                return Err(Error::InvalidProtocolState);
+            }
+        };
+
+        let payload = decode_message(RESPONSE_TAG, response_message)?;
+        let response = self.interactive_proof.deserialize_response(payload)?;
+        if self.interactive_proof.serialize_response(&response).len() != payload.len() {
+            return Err(Error::VerificationFailure);
+        }
+        self.interactive_proof
+            .verifier(&commitment, &challenge, &response)
+    }
+}
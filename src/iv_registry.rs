@@ -0,0 +1,90 @@
+//! Registry of precomputed IVs.
+//!
+//! Computing the IV of a [`Nizk`](crate::Nizk) hashes its protocol identifier,
+//! the session identifier and the instance label, which is costly for large instances.
+//! An [`IvRegistry`] stores IVs computed once, under names chosen by the application,
+//! so that verifiers can be constructed from them with [`Nizk::from_iv`](crate::Nizk::from_iv)
+//! or [`Nizk::from_pinned_iv`](crate::Nizk::from_pinned_iv).
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// A set of named, precomputed IVs.
+///
+/// The registry is serialized as the number of entries, followed by each entry in
+/// increasing order of names: the length of the name, the name and the 64-byte IV.
+/// Integers are 32-bit big-endian.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IvRegistry {
+    ivs: BTreeMap<Vec<u8>, [u8; 64]>,
+}
+
+impl IvRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `iv` under `name`, returning the IV previously stored under this name, if any.
+    pub fn insert(&mut self, name: &[u8], iv: [u8; 64]) -> Option<[u8; 64]> {
+        self.ivs.insert(name.to_vec(), iv)
+    }
+
+    /// Returns the IV stored under `name`.
+    pub fn get(&self, name: &[u8]) -> Option<[u8; 64]> {
+        self.ivs.get(name).copied()
+    }
+
+    /// Returns the number of stored IVs.
+    pub fn len(&self) -> usize {
+        self.ivs.len()
+    }
+
+    /// Returns `true` if the registry stores no IV.
+    pub fn is_empty(&self) -> bool {
+        self.ivs.is_empty()
+    }
+
+    /// Serializes the registry.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&(self.ivs.len() as u32).to_be_bytes());
+        for (name, iv) in &self.ivs {
+            bytes.extend_from_slice(&(name.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(name);
+            bytes.extend_from_slice(iv);
+        }
+        bytes
+    }
+
+    /// Deserializes a registry produced by [`IvRegistry::to_bytes`].
+    ///
+    /// Returns `None` if the bytes are truncated, have trailing data,
+    /// or store the same name twice.
+    pub fn from_bytes(mut bytes: &[u8]) -> Option<Self> {
+        fn take<'a>(bytes: &mut &'a [u8], length: usize) -> Option<&'a [u8]> {
+            if bytes.len() < length {
+                return None;
+            }
+            let (head, tail) = bytes.split_at(length);
+            *bytes = tail;
+            Some(head)
+        }
+        fn take_u32(bytes: &mut &[u8]) -> Option<usize> {
+            let length = take(bytes, 4)?.try_into().ok()?;
+            Some(u32::from_be_bytes(length) as usize)
+        }
+
+        let mut registry = Self::new();
+        let count = take_u32(&mut bytes)?;
+        for _ in 0..count {
+            let name_length = take_u32(&mut bytes)?;
+            let name = take(&mut bytes, name_length)?;
+            let iv = take(&mut bytes, 64)?.try_into().ok()?;
+            if registry.insert(name, iv).is_some() {
+                return None;
+            }
+        }
+        bytes.is_empty().then_some(registry)
+    }
+}
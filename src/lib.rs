@@ -53,7 +53,11 @@
 //! - **[`traits::SigmaProtocol`]**: The fundamental three-move protocol interface
 //! - **[`linear_relation::LinearRelation`]**: Express mathematical relations over groups
 //! - **[`fiat_shamir::Nizk`]**: Convert interactive proofs to standalone proofs
+//! - **[`FischlinNizk`]**: Straight-line extractable alternative to [`fiat_shamir::Nizk`]
+//! - **[`ProofSession`]**: Prove several statements in sequence, each bound to the previous ones
 //! - **[`composition::ComposedRelation`]**: Combine multiple proofs together
+//! - **[`policy`]**: Write compositions as textual policies over named relations
+//! - **[`interactive`]**: Run the three-move protocol between two parties
 //! - **[`codec`]**: Mapping from and to the hash function domain
 //!
 //! ---
@@ -69,22 +73,39 @@
 
 extern crate alloc;
 
+#[cfg(feature = "bip340")]
+pub mod bip340;
 pub mod codec;
 pub mod composition;
+#[cfg(feature = "ed25519")]
+pub mod ed25519;
 pub mod errors;
+pub mod interactive;
 pub mod linear_relation;
+pub mod policy;
+#[cfg(any(feature = "bip340", feature = "ed25519"))]
+pub mod signature;
 pub mod traits;
 
+pub(crate) mod challenge_derivation;
 pub(crate) mod duplex_sponge;
 pub(crate) mod fiat_shamir;
+pub(crate) mod fischlin;
 pub(crate) mod group;
+pub(crate) mod iv_registry;
+pub(crate) mod nonce;
 pub(crate) mod schnorr_protocol;
+pub(crate) mod session;
 
 #[cfg(test)]
 pub mod tests;
 
-pub use fiat_shamir::Nizk;
+pub use fiat_shamir::{BatchableProof, CompactProof, Nizk};
+pub use fischlin::{FischlinNizk, FischlinParameters};
+pub use iv_registry::IvRegistry;
 pub use linear_relation::LinearRelation;
+pub use nonce::NonceMode;
+pub use session::ProofSession;
 
 #[deprecated = "Use sigma_proofs::group::serialization instead"]
 pub use group::serialization;
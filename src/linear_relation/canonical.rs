@@ -15,6 +15,7 @@ use group::prime::PrimeGroup;
 use subtle::{Choice, ConstantTimeEq};
 
 use super::{GroupMap, GroupVar, LinearCombination, LinearRelation, ScalarTerm, ScalarVar};
+use crate::codec::ChallengeSpace;
 use crate::errors::{Error, InvalidInstance};
 use crate::group::msm::VariableMultiScalarMul;
 
@@ -36,6 +37,8 @@ pub struct CanonicalLinearRelation<G: PrimeGroup> {
     pub group_elements: GroupMap<G>,
     /// Number of scalar variables
     pub num_scalars: usize,
+    /// The set challenges are sampled from
+    pub challenge_space: ChallengeSpace,
 }
 
 /// Private type alias used to simplify function signatures below.
@@ -60,9 +63,58 @@ impl<G: PrimeGroup> CanonicalLinearRelation<G> {
             linear_combinations: Vec::new(),
             group_elements: GroupMap::default(),
             num_scalars: 0,
+            challenge_space: ChallengeSpace::Full,
         }
     }
 
+    /// Sets the set challenges are sampled from.
+    ///
+    /// The challenge space is bound to the protocol identifier:
+    /// proofs only verify with the challenge space they were generated with.
+    pub fn with_challenge_space(mut self, challenge_space: ChallengeSpace) -> Self {
+        self.challenge_space = challenge_space;
+        self
+    }
+
+    /// Merges linear relations into a single relation holding all their equations,
+    /// with disjoint scalar and group element variables.
+    ///
+    /// The scalars of the merged relation are those of the relations, one after the other.
+    /// It uses short challenges if all the relations do.
+    pub(crate) fn merge(relations: &[Self]) -> Self {
+        let mut merged = Self::new();
+        let all_short = !relations.is_empty()
+            && relations
+                .iter()
+                .all(|relation| relation.challenge_space == ChallengeSpace::Short);
+        if all_short {
+            merged.challenge_space = ChallengeSpace::Short;
+        }
+        for relation in relations {
+            let scalar_offset = merged.num_scalars;
+            let group_offset = merged.group_elements.len();
+            merged.image.extend_from_slice(&relation.image);
+            merged
+                .linear_combinations
+                .extend(relation.linear_combinations.iter().map(|lc| {
+                    lc.iter()
+                        .map(|(scalar_var, group_var)| {
+                            (
+                                ScalarVar(scalar_var.index() + scalar_offset, PhantomData),
+                                GroupVar(group_var.index() + group_offset, PhantomData),
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                }));
+            merged
+                .group_elements
+                .0
+                .extend_from_slice(&relation.group_elements.0);
+            merged.num_scalars += relation.num_scalars;
+        }
+        merged
+    }
+
     /// Evaluate the canonical linear relation with the provided scalars
     ///
     /// This returns a list of image points produced by evaluating each linear combination in the
@@ -28,8 +28,13 @@ mod ops;
 
 /// Implementation of canonical linear relation.
 mod canonical;
+pub use crate::schnorr_protocol::SchnorrProverState;
 pub use canonical::CanonicalLinearRelation;
 
+/// Implementations of serde traits for linear relations.
+#[cfg(feature = "serde")]
+mod serde_impls;
+
 /// A wrapper representing an index for a scalar variable.
 ///
 /// Used to reference scalars in sparse linear combinations.
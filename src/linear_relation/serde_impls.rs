@@ -0,0 +1,219 @@
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use group::prime::PrimeGroup;
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{
+    CanonicalLinearRelation, GroupMap, GroupVar, LinearMap, LinearRelation, ScalarTerm, ScalarVar,
+    Sum, Term, Weighted,
+};
+use crate::codec::ChallengeSpace;
+use crate::group::serde_helpers::{elements, optional_elements, scalar};
+
+/// Serialized form of a [`CanonicalLinearRelation`].
+///
+/// Each equation is `image[i] = Σ scalar_index * group_elements[group_index]`.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", rename = "CanonicalLinearRelation")]
+struct CanonicalLinearRelationRepr<G: PrimeGroup> {
+    num_scalars: usize,
+    #[serde(with = "elements")]
+    group_elements: Vec<G>,
+    #[serde(with = "elements")]
+    image: Vec<G>,
+    linear_combinations: Vec<Vec<(usize, usize)>>,
+    #[serde(default)]
+    challenge_space: ChallengeSpace,
+}
+
+impl<G: PrimeGroup> Serialize for CanonicalLinearRelation<G> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let group_elements = self
+            .group_elements
+            .iter()
+            .map(|(var, element)| {
+                element.copied().ok_or_else(|| {
+                    ser::Error::custom(format_args!("unassigned group variable {}", var.0))
+                })
+            })
+            .collect::<Result<Vec<_>, S::Error>>()?;
+        let linear_combinations = self
+            .linear_combinations
+            .iter()
+            .map(|lc| lc.iter().map(|(s, g)| (s.0, g.0)).collect())
+            .collect();
+        CanonicalLinearRelationRepr {
+            num_scalars: self.num_scalars,
+            group_elements,
+            image: self.image.clone(),
+            linear_combinations,
+            challenge_space: self.challenge_space,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, G: PrimeGroup> Deserialize<'de> for CanonicalLinearRelation<G> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = CanonicalLinearRelationRepr::<G>::deserialize(deserializer)?;
+        if repr.image.len() != repr.linear_combinations.len() {
+            return Err(de::Error::custom(
+                "number of equations must be equal to number of image elements",
+            ));
+        }
+
+        let mut linear_combinations = Vec::with_capacity(repr.linear_combinations.len());
+        for lc in repr.linear_combinations {
+            let mut terms = Vec::with_capacity(lc.len());
+            for (scalar_index, group_index) in lc {
+                if scalar_index >= repr.num_scalars {
+                    return Err(de::Error::custom(format_args!(
+                        "scalar index {scalar_index} out of range"
+                    )));
+                }
+                if group_index >= repr.group_elements.len() {
+                    return Err(de::Error::custom(format_args!(
+                        "group element index {group_index} out of range"
+                    )));
+                }
+                terms.push((
+                    ScalarVar(scalar_index, PhantomData),
+                    GroupVar(group_index, PhantomData),
+                ));
+            }
+            linear_combinations.push(terms);
+        }
+
+        Ok(Self {
+            image: repr.image,
+            linear_combinations,
+            group_elements: GroupMap(repr.group_elements.into_iter().map(Some).collect()),
+            num_scalars: repr.num_scalars,
+            challenge_space: repr.challenge_space,
+        })
+    }
+}
+
+/// Serialized form of a term `weight * (scalar * element)` of a [`LinearRelation`].
+///
+/// A missing scalar index stands for the unit scalar.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct TermRepr<G: PrimeGroup> {
+    scalar: Option<usize>,
+    element: usize,
+    #[serde(with = "scalar")]
+    weight: G::Scalar,
+}
+
+/// Serialized form of an equation `image = Σ terms` of a [`LinearRelation`].
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct EquationRepr<G: PrimeGroup> {
+    image: usize,
+    terms: Vec<TermRepr<G>>,
+}
+
+/// Serialized form of a [`LinearRelation`].
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "", rename = "LinearRelation")]
+struct LinearRelationRepr<G: PrimeGroup> {
+    num_scalars: usize,
+    num_elements: usize,
+    #[serde(with = "optional_elements")]
+    group_elements: Vec<Option<G>>,
+    equations: Vec<EquationRepr<G>>,
+}
+
+impl<G: PrimeGroup> Serialize for LinearRelation<G> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if self.image.len() != self.linear_map.linear_combinations.len() {
+            return Err(ser::Error::custom(
+                "number of equations must be equal to number of image elements",
+            ));
+        }
+        let equations = core::iter::zip(&self.image, &self.linear_map.linear_combinations)
+            .map(|(image, lc)| EquationRepr {
+                image: image.0,
+                terms: lc
+                    .terms()
+                    .iter()
+                    .map(|weighted| TermRepr {
+                        scalar: match weighted.term.scalar {
+                            ScalarTerm::Var(var) => Some(var.0),
+                            ScalarTerm::Unit => None,
+                        },
+                        element: weighted.term.elem.0,
+                        weight: weighted.weight,
+                    })
+                    .collect(),
+            })
+            .collect();
+        LinearRelationRepr {
+            num_scalars: self.linear_map.num_scalars,
+            num_elements: self.linear_map.num_elements,
+            group_elements: self.linear_map.group_elements.0.clone(),
+            equations,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, G: PrimeGroup> Deserialize<'de> for LinearRelation<G> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = LinearRelationRepr::<G>::deserialize(deserializer)?;
+        if repr.group_elements.len() > repr.num_elements {
+            return Err(de::Error::custom(
+                "more group elements assigned than allocated",
+            ));
+        }
+        let group_var = |index: usize| {
+            if index < repr.num_elements {
+                Ok(GroupVar(index, PhantomData))
+            } else {
+                Err(de::Error::custom(format_args!(
+                    "group element index {index} out of range"
+                )))
+            }
+        };
+
+        let mut image = Vec::with_capacity(repr.equations.len());
+        let mut linear_combinations = Vec::with_capacity(repr.equations.len());
+        for equation in repr.equations {
+            image.push(group_var(equation.image)?);
+            let mut terms = Vec::with_capacity(equation.terms.len());
+            for term in equation.terms {
+                let scalar = match term.scalar {
+                    Some(index) if index < repr.num_scalars => {
+                        ScalarTerm::Var(ScalarVar(index, PhantomData))
+                    }
+                    Some(index) => {
+                        return Err(de::Error::custom(format_args!(
+                            "scalar index {index} out of range"
+                        )))
+                    }
+                    None => ScalarTerm::Unit,
+                };
+                terms.push(Weighted {
+                    term: Term {
+                        scalar,
+                        elem: group_var(term.element)?,
+                    },
+                    weight: term.weight,
+                });
+            }
+            linear_combinations.push(Sum(terms));
+        }
+
+        Ok(Self {
+            linear_map: LinearMap {
+                linear_combinations,
+                group_elements: GroupMap(repr.group_elements),
+                num_scalars: repr.num_scalars,
+                num_elements: repr.num_elements,
+            },
+            image,
+        })
+    }
+}
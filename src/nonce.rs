@@ -0,0 +1,132 @@
+//! Derivation of prover nonces from a duplex sponge.
+//!
+//! This module defines [`NonceMode`], which selects how [`Nizk`](crate::Nizk) samples
+//! the randomness of the prover, and the sponge-based generator
+//! used by the hedged and deterministic modes.
+//!
+//! Following RFC 6979 and the synthetic nonces of BIP-340, the sponge absorbs the witness
+//! along with the statement, so that a weak random number generator cannot leak the witness.
+
+use crate::codec::{compute_iv, KeccakDuplexSponge};
+use crate::duplex_sponge::DuplexSpongeInterface;
+use zeroize::Zeroizing;
+
+#[cfg(feature = "std")]
+use rand::{CryptoRng, RngCore};
+#[cfg(not(feature = "std"))]
+use rand_core::{CryptoRng, RngCore};
+
+/// Protocol identifier used to initialize the sponge of a [`NonceRng`].
+const NONCE_PROTOCOL_IDENTIFIER: &[u8] = b"sigma-proofs/nonce";
+
+/// Number of bytes drawn from the caller's generator in [`NonceMode::Hedged`].
+const HEDGING_RANDOMNESS_SIZE: usize = 32;
+
+/// How a prover samples its nonces.
+///
+/// All the randomness used by the prover, including the transcripts simulated
+/// for the inactive branches of OR compositions, is drawn from the same source.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NonceMode {
+    /// Nonces are drawn from the random number generator given by the caller.
+    #[default]
+    Random,
+    /// Nonces are derived from the witness, the statement, the session, the message
+    /// and fresh randomness from the caller's generator.
+    ///
+    /// Nonces remain secret if the generator is weak, as long as the witness is.
+    Hedged,
+    /// Nonces are derived from the witness, the statement, the session and the message only.
+    ///
+    /// Proving twice the same statement yields the same proof,
+    /// which is useful to generate reproducible test vectors.
+    Deterministic,
+}
+
+impl NonceMode {
+    fn label(&self) -> &'static [u8] {
+        match self {
+            NonceMode::Random => b"random",
+            NonceMode::Hedged => b"hedged",
+            NonceMode::Deterministic => b"deterministic",
+        }
+    }
+}
+
+/// A random number generator backed by a duplex sponge.
+///
+/// The sponge is initialized with [`NONCE_PROTOCOL_IDENTIFIER`] and the nonce mode,
+/// and absorbs, each prefixed by its length as a 64-bit big-endian integer:
+/// - the session binding of the proof (the IV of the instance),
+/// - the message, preceded by a byte set to 1 if there is one, or a single 0 byte otherwise,
+/// - the serialized witness,
+/// - in [`NonceMode::Hedged`], 32 bytes from the caller's generator.
+///
+/// Random bytes are then squeezed from the sponge.
+pub(crate) struct NonceRng {
+    sponge: KeccakDuplexSponge,
+}
+
+impl NonceRng {
+    /// Seeds a new generator.
+    ///
+    /// The `rng` is only used in [`NonceMode::Hedged`].
+    pub(crate) fn new(
+        mode: NonceMode,
+        session_binding: &[u8],
+        message: Option<&[u8]>,
+        witness: &[u8],
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Self {
+        let iv = compute_iv::<KeccakDuplexSponge>(NONCE_PROTOCOL_IDENTIFIER, mode.label(), b"");
+        let mut nonce_rng = Self {
+            sponge: KeccakDuplexSponge::new(iv),
+        };
+        nonce_rng.absorb_with_length(session_binding);
+        match message {
+            Some(message) => {
+                nonce_rng.sponge.absorb(&[1]);
+                nonce_rng.absorb_with_length(message);
+            }
+            None => nonce_rng.sponge.absorb(&[0]),
+        }
+        nonce_rng.absorb_with_length(witness);
+        if mode == NonceMode::Hedged {
+            let mut randomness = Zeroizing::new([0u8; HEDGING_RANDOMNESS_SIZE]);
+            rng.fill_bytes(randomness.as_mut());
+            nonce_rng.absorb_with_length(randomness.as_ref());
+        }
+        nonce_rng
+    }
+
+    fn absorb_with_length(&mut self, data: &[u8]) {
+        self.sponge.absorb(&(data.len() as u64).to_be_bytes());
+        self.sponge.absorb(data);
+    }
+}
+
+impl RngCore for NonceRng {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let output = Zeroizing::new(self.sponge.squeeze(dest.len()));
+        dest.copy_from_slice(&output);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for NonceRng {}
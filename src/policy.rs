@@ -0,0 +1,345 @@
+//! # Proof Policies
+//!
+//! This module defines a small textual language for [`ComposedRelation`]s, so that proof policies
+//! can be written in configuration files rather than in code. A policy is a tree of operators
+//! over the names of linear relations:
+//!
+//! ```text
+//! and(age_over_18, or(gov_id, 2of3(ref_a, ref_b, ref_c)))
+//! ```
+//!
+//! - `and(p1, ..., pn)` holds if all of `p1, ..., pn` hold,
+//! - `or(p1, ..., pn)` holds if any of `p1, ..., pn` holds,
+//! - `kofn(p1, ..., pn)` holds if at least `k` of the `n` policies `p1, ..., pn` hold,
+//! - a name holds if the prover knows a witness for the relation registered under that name.
+//!
+//! Names start with a letter or an underscore, followed by letters, digits, underscores,
+//! dots or dashes. Whitespace is allowed between tokens.
+//!
+//! A [`Policy`] is compiled against a [`RelationRegistry`] into a [`ComposedRelation`]
+//! and a [`PolicyWitnessBuilder`], which builds the matching [`ComposedWitness`]
+//! from the witnesses of the relations known to the prover.
+
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use ff::Field;
+use group::prime::PrimeGroup;
+
+use crate::composition::{ComposedRelation, ComposedWitness};
+use crate::errors::InvalidInstance;
+use crate::linear_relation::CanonicalLinearRelation;
+
+/// The maximal nesting depth of the operators of a policy.
+///
+/// Policies are parsed recursively, and deeper policies are rejected
+/// instead of exhausting the stack.
+pub const MAX_POLICY_DEPTH: usize = 64;
+
+/// A proof policy: a composition of named linear relations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Policy {
+    Relation(String),
+    And(Vec<Policy>),
+    Or(Vec<Policy>),
+    Threshold(usize, Vec<Policy>),
+}
+
+/// A set of linear relations, stored under the names policies refer them by.
+#[derive(Clone, Debug, Default)]
+pub struct RelationRegistry<G: PrimeGroup> {
+    relations: BTreeMap<String, CanonicalLinearRelation<G>>,
+}
+
+impl<G: PrimeGroup> RelationRegistry<G> {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self {
+            relations: BTreeMap::new(),
+        }
+    }
+
+    /// Stores `relation` under `name`, returning the relation previously stored under this name, if any.
+    pub fn insert(
+        &mut self,
+        name: &str,
+        relation: CanonicalLinearRelation<G>,
+    ) -> Option<CanonicalLinearRelation<G>> {
+        self.relations.insert(name.to_string(), relation)
+    }
+
+    /// Returns the relation stored under `name`.
+    pub fn get(&self, name: &str) -> Option<&CanonicalLinearRelation<G>> {
+        self.relations.get(name)
+    }
+
+    /// Returns the number of stored relations.
+    pub fn len(&self) -> usize {
+        self.relations.len()
+    }
+
+    /// Returns `true` if the registry stores no relation.
+    pub fn is_empty(&self) -> bool {
+        self.relations.is_empty()
+    }
+}
+
+/// Builds the witnesses of a compiled [`Policy`] from the witnesses of the relations known to the prover.
+///
+/// It is returned by [`Policy::compile`], and records the number of scalars of each relation,
+/// so that it can fill in the witnesses of the unknown relations.
+#[derive(Clone, Debug)]
+pub struct PolicyWitnessBuilder<G: PrimeGroup> {
+    policy: Policy,
+    num_scalars: BTreeMap<String, usize>,
+    _group: PhantomData<G>,
+}
+
+impl<G: PrimeGroup> PolicyWitnessBuilder<G> {
+    /// Builds the witness of the compiled policy from the witnesses of the known relations,
+    /// indexed by name.
+    ///
+    /// The witnesses of the other relations are placeholders of zero scalars,
+    /// which the prover simulates in the branches of OR and threshold compositions.
+    ///
+    /// Returns an error if a known witness does not have the number of scalars of its relation,
+    /// or if the known relations do not satisfy the policy.
+    pub fn build(
+        &self,
+        known: &BTreeMap<String, Vec<G::Scalar>>,
+    ) -> Result<ComposedWitness<G>, InvalidInstance> {
+        let (witness, satisfied) = self.build_node(&self.policy, known)?;
+        if satisfied {
+            Ok(witness)
+        } else {
+            Err(InvalidInstance::new(
+                "The known witnesses do not satisfy the policy.",
+            ))
+        }
+    }
+
+    /// Builds the witness of `policy`, and returns whether the known relations satisfy it.
+    fn build_node(
+        &self,
+        policy: &Policy,
+        known: &BTreeMap<String, Vec<G::Scalar>>,
+    ) -> Result<(ComposedWitness<G>, bool), InvalidInstance> {
+        let children = |policies: &[Policy]| {
+            let mut witnesses = Vec::with_capacity(policies.len());
+            let mut satisfied = 0;
+            for policy in policies {
+                let (witness, is_satisfied) = self.build_node(policy, known)?;
+                witnesses.push(witness);
+                satisfied += is_satisfied as usize;
+            }
+            Ok::<_, InvalidInstance>((witnesses, satisfied))
+        };
+        match policy {
+            Policy::Relation(name) => {
+                let num_scalars = self.num_scalars[name];
+                match known.get(name) {
+                    Some(witness) if witness.len() != num_scalars => {
+                        Err(InvalidInstance::new(format!(
+                            "The witness of relation {name} has {} scalars, expected {num_scalars}.",
+                            witness.len()
+                        )))
+                    }
+                    Some(witness) => Ok((ComposedWitness::Simple(witness.clone()), true)),
+                    None => Ok((
+                        ComposedWitness::Simple(vec![G::Scalar::ZERO; num_scalars]),
+                        false,
+                    )),
+                }
+            }
+            Policy::And(policies) => {
+                let (witnesses, satisfied) = children(policies)?;
+                Ok((ComposedWitness::And(witnesses), satisfied == policies.len()))
+            }
+            Policy::Or(policies) => {
+                let (witnesses, satisfied) = children(policies)?;
+                Ok((ComposedWitness::Or(witnesses), satisfied >= 1))
+            }
+            Policy::Threshold(threshold, policies) => {
+                let (witnesses, satisfied) = children(policies)?;
+                Ok((
+                    ComposedWitness::Threshold(witnesses),
+                    satisfied >= *threshold,
+                ))
+            }
+        }
+    }
+}
+
+impl Policy {
+    /// Parses a policy from its textual form.
+    ///
+    /// Returns an error describing the first syntax error, with its byte offset in `source`.
+    pub fn parse(source: &str) -> Result<Self, InvalidInstance> {
+        let mut parser = Parser {
+            source,
+            position: 0,
+        };
+        let policy = parser.policy(0)?;
+        parser.skip_whitespace();
+        if parser.position != source.len() {
+            return Err(parser.error("expected the end of the policy"));
+        }
+        Ok(policy)
+    }
+
+    /// Compiles the policy into a [`ComposedRelation`] over the relations of `registry`,
+    /// and a [`PolicyWitnessBuilder`] for its witnesses.
+    ///
+    /// Returns an error if the policy refers to a relation that is not in the registry.
+    pub fn compile<G: PrimeGroup>(
+        &self,
+        registry: &RelationRegistry<G>,
+    ) -> Result<(ComposedRelation<G>, PolicyWitnessBuilder<G>), InvalidInstance> {
+        let mut num_scalars = BTreeMap::new();
+        let relation = self.compile_node(registry, &mut num_scalars)?;
+        let builder = PolicyWitnessBuilder {
+            policy: self.clone(),
+            num_scalars,
+            _group: PhantomData,
+        };
+        Ok((relation, builder))
+    }
+
+    fn compile_node<G: PrimeGroup>(
+        &self,
+        registry: &RelationRegistry<G>,
+        num_scalars: &mut BTreeMap<String, usize>,
+    ) -> Result<ComposedRelation<G>, InvalidInstance> {
+        let mut children = |policies: &[Policy]| {
+            policies
+                .iter()
+                .map(|policy| policy.compile_node(registry, num_scalars))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(match self {
+            Policy::Relation(name) => {
+                let relation = registry.get(name).ok_or_else(|| {
+                    InvalidInstance::new(format!("Unknown relation {name} in policy."))
+                })?;
+                num_scalars.insert(name.clone(), relation.num_scalars);
+                ComposedRelation::Simple(relation.clone())
+            }
+            Policy::And(policies) => ComposedRelation::And(children(policies)?),
+            Policy::Or(policies) => ComposedRelation::Or(children(policies)?),
+            Policy::Threshold(threshold, policies) => {
+                ComposedRelation::Threshold(*threshold, children(policies)?)
+            }
+        })
+    }
+}
+
+/// A recursive descent parser of policies.
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> InvalidInstance {
+        InvalidInstance::new(format!(
+            "Invalid policy at byte {}: {message}.",
+            self.position
+        ))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.source[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.source[self.position..].chars().next()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), InvalidInstance> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{expected}'")))
+        }
+    }
+
+    /// Reads a word of letters, digits, underscores, dots and dashes.
+    fn word(&mut self) -> &str {
+        let start = self.position;
+        while let Some(c) = self.peek() {
+            if !(c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')) {
+                break;
+            }
+            self.position += 1;
+        }
+        &self.source[start..self.position]
+    }
+
+    fn policy(&mut self, depth: usize) -> Result<Policy, InvalidInstance> {
+        self.skip_whitespace();
+        let start = self.position;
+        let word = self.word().to_string();
+        if word.is_empty() {
+            return Err(self.error("expected a relation name or an operator"));
+        }
+        self.skip_whitespace();
+        if self.peek() != Some('(') {
+            if !word.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+                self.position = start;
+                return Err(self.error("relation names start with a letter or an underscore"));
+            }
+            return Ok(Policy::Relation(word));
+        }
+
+        if depth == MAX_POLICY_DEPTH {
+            return Err(self.error("the policy is nested too deeply"));
+        }
+        let threshold = match word.as_str() {
+            "and" | "or" => None,
+            _ => {
+                let threshold = word
+                    .split_once("of")
+                    .and_then(|(k, n)| Some((k.parse::<usize>().ok()?, n.parse::<usize>().ok()?)));
+                if threshold.is_none() {
+                    self.position = start;
+                    return Err(self.error(&format!("unknown operator '{word}'")));
+                }
+                threshold
+            }
+        };
+
+        self.expect('(')?;
+        let mut policies = vec![self.policy(depth + 1)?];
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(',') {
+                break;
+            }
+            self.position += 1;
+            policies.push(self.policy(depth + 1)?);
+        }
+        self.expect(')')?;
+
+        Ok(match (word.as_str(), threshold) {
+            ("and", _) => Policy::And(policies),
+            ("or", _) => Policy::Or(policies),
+            (_, Some((k, n))) => {
+                if n != policies.len() || k == 0 || k > n {
+                    self.position = start;
+                    return Err(self.error(&format!(
+                        "invalid threshold '{word}' over {} policies",
+                        policies.len()
+                    )));
+                }
+                Policy::Threshold(k, policies)
+            }
+            _ => unreachable!(),
+        })
+    }
+}
@@ -4,24 +4,94 @@
 //! a Sigma protocol proving different types of discrete logarithm relations (eg. Schnorr, Pedersen's commitments)
 //! through a group morphism abstraction (see [Maurer09](https://crypto-test.ethz.ch/publications/files/Maurer09.pdf)).
 
+use crate::codec::ChallengeSpace;
 use crate::errors::Error;
 use crate::group::serialization::{
     deserialize_elements, deserialize_scalars, serialize_elements, serialize_scalars,
 };
+use crate::group::zeroize_helpers::{extend_secret_scalars, zeroize_scalars};
 use crate::linear_relation::CanonicalLinearRelation;
-use crate::traits::{SigmaProtocol, SigmaProtocolSimulator};
+use crate::traits::sealed::TrialResponse;
+use crate::traits::{
+    SigmaProtocol, SigmaProtocolBatchVerifier, SigmaProtocolSimulator, SigmaProtocolTrialResponse,
+    VerificationEquation,
+};
 use alloc::vec::Vec;
 
-use ff::Field;
+use ff::{Field, PrimeField};
 use group::prime::PrimeGroup;
 #[cfg(feature = "std")]
 use rand::{CryptoRng, Rng, RngCore};
 #[cfg(not(feature = "std"))]
 use rand_core::{CryptoRng, RngCore, RngCore as Rng};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// The prover state of the Schnorr protocol for a [`CanonicalLinearRelation`].
+///
+/// It holds the nonces of the commitment and a copy of the witness,
+/// which are overwritten with zeros when the state is dropped.
+pub struct SchnorrProverState<G: PrimeGroup> {
+    nonces: Vec<G::Scalar>,
+    witness: Vec<G::Scalar>,
+}
+
+impl<G: PrimeGroup> SchnorrProverState<G> {
+    /// Creates a prover state from the nonces of the commitment and the witness.
+    pub fn new(nonces: Vec<G::Scalar>, witness: Vec<G::Scalar>) -> Self {
+        Self { nonces, witness }
+    }
+}
+
+impl<G: PrimeGroup> Zeroize for SchnorrProverState<G> {
+    fn zeroize(&mut self) {
+        zeroize_scalars(&mut self.nonces);
+        zeroize_scalars(&mut self.witness);
+    }
+}
+
+impl<G: PrimeGroup> Drop for SchnorrProverState<G> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<G: PrimeGroup> ZeroizeOnDrop for SchnorrProverState<G> {}
+
+impl<G: PrimeGroup> CanonicalLinearRelation<G> {
+    /// Computes the commitment of the prover with the given nonces, one per scalar variable.
+    ///
+    /// This is [`SigmaProtocol::prover_commit`] with caller-chosen nonces,
+    /// used by compositions in which relations share some of their nonces.
+    pub(crate) fn commit_with_nonces(
+        &self,
+        nonces: Vec<G::Scalar>,
+        witness: &[G::Scalar],
+    ) -> Result<(Vec<G>, SchnorrProverState<G>), Error> {
+        if witness.len() < self.num_scalars || nonces.len() != self.num_scalars {
+            return Err(Error::InvalidInstanceWitnessPair);
+        }
+
+        // TODO: Check this when constructing the CanonicalLinearRelation instead of here.
+        // If the image is the identity, then the relation must be
+        // trivial, or else the proof will be unsound
+        if self
+            .image
+            .iter()
+            .zip(self.linear_combinations.iter())
+            .any(|(&x, c)| x == G::identity() && !c.is_empty())
+        {
+            return Err(Error::InvalidInstanceWitnessPair);
+        }
+
+        let commitment = self.evaluate(&nonces);
+        let prover_state = SchnorrProverState::new(nonces, witness.to_vec());
+        Ok((commitment, prover_state))
+    }
+}
 
 impl<G: PrimeGroup> SigmaProtocol for CanonicalLinearRelation<G> {
     type Commitment = Vec<G>;
-    type ProverState = (Vec<G::Scalar>, Vec<G::Scalar>);
+    type ProverState = SchnorrProverState<G>;
     type Response = Vec<G::Scalar>;
     type Witness = Vec<G::Scalar>;
     type Challenge = G::Scalar;
@@ -46,29 +116,10 @@ impl<G: PrimeGroup> SigmaProtocol for CanonicalLinearRelation<G> {
         witness: &Self::Witness,
         rng: &mut (impl RngCore + CryptoRng),
     ) -> Result<(Self::Commitment, Self::ProverState), Error> {
-        if witness.len() < self.num_scalars {
-            return Err(Error::InvalidInstanceWitnessPair);
-        }
-
-        // TODO: Check this when constructing the CanonicalLinearRelation instead of here.
-        // If the image is the identity, then the relation must be
-        // trivial, or else the proof will be unsound
-        if self
-            .image
-            .iter()
-            .zip(self.linear_combinations.iter())
-            .any(|(&x, c)| x == G::identity() && !c.is_empty())
-        {
-            return Err(Error::InvalidInstanceWitnessPair);
-        }
-
         let nonces = (0..self.num_scalars)
             .map(|_| G::Scalar::random(&mut *rng))
             .collect::<Vec<_>>();
-
-        let commitment = self.evaluate(&nonces);
-        let prover_state = (nonces.to_vec(), witness.to_vec());
-        Ok((commitment, prover_state))
+        self.commit_with_nonces(nonces, witness)
     }
 
     /// Computes the prover's response (second message) using the challenge.
@@ -87,14 +138,8 @@ impl<G: PrimeGroup> SigmaProtocol for CanonicalLinearRelation<G> {
         prover_state: Self::ProverState,
         challenge: &Self::Challenge,
     ) -> Result<Self::Response, Error> {
-        let (nonces, witness) = prover_state;
-
-        let responses = nonces
-            .into_iter()
-            .zip(witness)
-            .map(|(r, w)| r + w * challenge)
-            .collect();
-        Ok(responses)
+        // The state is zeroized when dropped at the end of this function.
+        self.prover_trial_response(&prover_state, challenge)
     }
     /// Verifies the correctness of the proof.
     ///
@@ -153,14 +198,15 @@ impl<G: PrimeGroup> SigmaProtocol for CanonicalLinearRelation<G> {
     ///
     /// Converts the challenge scalar into a fixed-length byte encoding. This can be used
     /// for Fiat–Shamir hashing, transcript recording, or proof transmission.
+    /// The length depends on the challenge space of the relation.
     ///
     /// # Parameters
     /// - `challenge`: The scalar challenge value.
     ///
     /// # Returns
-    /// A `Vec<u8>` containing the serialized scalar.
-    fn serialize_challenge(&self, &challenge: &Self::Challenge) -> Vec<u8> {
-        serialize_scalars::<G>(&[challenge])
+    /// A `Vec<u8>` containing the serialized challenge.
+    fn serialize_challenge(&self, challenge: &Self::Challenge) -> Vec<u8> {
+        self.challenge_space.serialize(challenge)
     }
 
     /// Serializes the prover's response vector into a byte format.
@@ -209,8 +255,9 @@ impl<G: PrimeGroup> SigmaProtocol for CanonicalLinearRelation<G> {
     /// # Errors
     /// - Returns [`Error::VerificationFailure`] if deserialization fails or data is invalid.
     fn deserialize_challenge(&self, data: &[u8]) -> Result<Self::Challenge, Error> {
-        let scalars = deserialize_scalars::<G>(data, 1).ok_or(Error::VerificationFailure)?;
-        Ok(scalars[0])
+        self.challenge_space
+            .deserialize(data)
+            .ok_or(Error::VerificationFailure)
     }
 
     /// Deserializes a byte slice into the prover's response vector.
@@ -235,10 +282,43 @@ impl<G: PrimeGroup> SigmaProtocol for CanonicalLinearRelation<G> {
     }
 
     fn protocol_identifier(&self) -> impl AsRef<[u8]> {
-        b"draft-zkproof-fiat-shamir"
+        match self.challenge_space {
+            ChallengeSpace::Full => b"draft-zkproof-fiat-shamir".as_slice(),
+            ChallengeSpace::Short => b"draft-zkproof-fiat-shamir/short-challenges".as_slice(),
+        }
+    }
+
+    fn challenge_space(&self) -> ChallengeSpace {
+        self.challenge_space
+    }
+
+    fn serialize_witness(&self, witness: &Self::Witness) -> Option<Zeroizing<Vec<u8>>> {
+        let scalar_len = <G::Scalar as PrimeField>::Repr::default().as_ref().len();
+        let mut bytes = Zeroizing::new(Vec::with_capacity(witness.len() * scalar_len));
+        extend_secret_scalars(&mut bytes, witness);
+        Some(bytes)
     }
 }
 
+impl<G: PrimeGroup> TrialResponse for CanonicalLinearRelation<G> {
+    /// Computes the response `nonce + witness * challenge` for each scalar variable.
+    fn prover_trial_response(
+        &self,
+        state: &Self::ProverState,
+        challenge: &Self::Challenge,
+    ) -> Result<Self::Response, Error> {
+        let responses = state
+            .nonces
+            .iter()
+            .zip(&state.witness)
+            .map(|(r, w)| *r + *w * challenge)
+            .collect();
+        Ok(responses)
+    }
+}
+
+impl<G: PrimeGroup> SigmaProtocolTrialResponse for CanonicalLinearRelation<G> {}
+
 impl<G> SigmaProtocolSimulator for CanonicalLinearRelation<G>
 where
     G: PrimeGroup,
@@ -269,7 +349,7 @@ where
         &self,
         rng: &mut R,
     ) -> Result<(Self::Commitment, Self::Challenge, Self::Response), Error> {
-        let challenge = G::Scalar::random(&mut *rng);
+        let challenge = self.challenge_space.random(&mut *rng);
         let response = self.simulate_response(&mut *rng);
         let commitment = self.simulate_commitment(&challenge, &response)?;
         Ok((commitment, challenge, response))
@@ -304,3 +384,49 @@ where
         Ok(commitment)
     }
 }
+
+impl<G: PrimeGroup> SigmaProtocolBatchVerifier for CanonicalLinearRelation<G> {
+    type Group = G;
+
+    /// Returns one verification equation per linear constraint.
+    ///
+    /// The `i`-th equation is `Σ response_j * G_k - challenge * image_i - commitment_i = 0`.
+    ///
+    /// # Errors
+    /// - [`Error::InvalidInstanceWitnessPair`] if the lengths of commitment or response do not match the expected counts.
+    fn verification_equations(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Self::Challenge,
+        response: &Self::Response,
+    ) -> Result<Vec<VerificationEquation<G>>, Error> {
+        if commitment.len() != self.image.len() || response.len() != self.num_scalars {
+            return Err(Error::InvalidInstanceWitnessPair);
+        }
+
+        let mut equations = Vec::with_capacity(self.image.len());
+        for ((lc, image), commitment) in self
+            .linear_combinations
+            .iter()
+            .zip(&self.image)
+            .zip(commitment)
+        {
+            let mut scalars = Vec::with_capacity(lc.len() + 2);
+            let mut bases = Vec::with_capacity(lc.len() + 2);
+            for (scalar_var, group_var) in lc {
+                scalars.push(response[scalar_var.index()]);
+                bases.push(
+                    self.group_elements
+                        .get(*group_var)
+                        .map_err(|_| Error::InvalidInstanceWitnessPair)?,
+                );
+            }
+            scalars.push(-*challenge);
+            bases.push(*image);
+            scalars.push(-G::Scalar::ONE);
+            bases.push(*commitment);
+            equations.push((scalars, bases));
+        }
+        Ok(equations)
+    }
+}
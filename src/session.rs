@@ -0,0 +1,136 @@
+//! Sequential proofs of several statements on a single codec.
+//!
+//! This module defines [`ProofSession`], which proves a sequence of [`SigmaProtocol`]
+//! instances with the Fiat-Shamir transformation, using one running codec for the whole sequence.
+//! Unlike proofs produced by independent [`Nizk`](crate::Nizk) instances,
+//! the challenge of each proof depends on all the statements, commitments and responses before it.
+//!
+//! The verifier replays the same sequence, verifying the proofs in the order they were produced.
+
+use crate::codec::Codec;
+use crate::errors::Error;
+use crate::traits::SigmaProtocol;
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use rand::{CryptoRng, RngCore};
+#[cfg(not(feature = "std"))]
+use rand_core::{CryptoRng, RngCore};
+
+/// Protocol identifier used to initialize the codec of a [`ProofSession`].
+const SESSION_PROTOCOL_IDENTIFIER: &[u8] = b"sigma-proofs/proof-session";
+
+/// A sequence of non-interactive proofs sharing one running codec.
+///
+/// For each statement, the codec absorbs:
+/// - the protocol identifier and the instance label, as labels (see [`Codec::absorb_label`]),
+/// - the serialized commitment,
+///
+/// then squeezes the challenge, and finally absorbs the serialized response.
+///
+/// Proofs are serialized as `commitment || response`, like [`Nizk::prove_batchable`](crate::Nizk::prove_batchable).
+/// A failed proving or verification step leaves the session unchanged.
+///
+/// # Type Parameters
+/// - `C`: the codec used for Fiat-Shamir.
+#[derive(Clone, Debug)]
+pub struct ProofSession<C: Codec> {
+    /// Current codec state.
+    pub hash_state: C,
+}
+
+impl<C: Codec + Clone> ProofSession<C> {
+    /// Creates a new session.
+    ///
+    /// # Parameters
+    /// - `session_identifier`: Domain separation tag for the hash function (e.g., protocol name or context).
+    pub fn new(session_identifier: &[u8]) -> Self {
+        Self {
+            hash_state: C::new(SESSION_PROTOCOL_IDENTIFIER, session_identifier, b""),
+        }
+    }
+
+    /// Absorbs the statement and the commitment, and returns the challenge.
+    fn statement_challenge<P>(
+        protocol: &P,
+        hash_state: &mut C,
+        commitment: &P::Commitment,
+    ) -> P::Challenge
+    where
+        P: SigmaProtocol<Challenge = C::Challenge>,
+    {
+        hash_state.absorb_label(protocol.protocol_identifier().as_ref());
+        hash_state.absorb_label(protocol.instance_label().as_ref());
+        hash_state.prover_message(&protocol.serialize_commitment(commitment));
+        hash_state.verifier_challenge_in(protocol.challenge_space())
+    }
+
+    /// Proves the next statement of the session.
+    ///
+    /// # Parameters
+    /// - `protocol`: The instance of the Sigma protocol to prove.
+    /// - `witness`: The secret witness.
+    /// - `rng`: A cryptographically secure random number generator.
+    ///
+    /// # Returns
+    /// The serialized proof, to be verified with [`ProofSession::verify`] at the same position of the sequence.
+    pub fn prove<P>(
+        &mut self,
+        protocol: &P,
+        witness: &P::Witness,
+        rng: &mut (impl RngCore + CryptoRng),
+    ) -> Result<Vec<u8>, Error>
+    where
+        P: SigmaProtocol<Challenge = C::Challenge>,
+    {
+        let mut hash_state = self.hash_state.clone();
+
+        let (commitment, prover_state) = protocol.prover_commit(witness, rng)?;
+        let challenge = Self::statement_challenge(protocol, &mut hash_state, &commitment);
+        let response = protocol.prover_response(prover_state, &challenge)?;
+        let serialized_response = protocol.serialize_response(&response);
+        hash_state.prover_message(&serialized_response);
+
+        self.hash_state = hash_state;
+        let mut proof = protocol.serialize_commitment(&commitment);
+        proof.extend_from_slice(&serialized_response);
+        Ok(proof)
+    }
+
+    /// Verifies the next proof of the session.
+    ///
+    /// # Parameters
+    /// - `protocol`: The instance of the Sigma protocol the proof is for.
+    /// - `proof`: The serialized proof.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the proof is valid.
+    /// - `Err(Error)` if deserialization or verification fails.
+    ///
+    /// # Errors
+    /// - Returns [`Error::VerificationFailure`] if:
+    ///   - The proof is malformed or has trailing bytes.
+    ///   - The response fails verification under the Sigma protocol.
+    pub fn verify<P>(&mut self, protocol: &P, proof: &[u8]) -> Result<(), Error>
+    where
+        P: SigmaProtocol<Challenge = C::Challenge>,
+    {
+        let commitment = protocol.deserialize_commitment(proof)?;
+        let commitment_size = protocol.serialize_commitment(&commitment).len();
+        let response = protocol.deserialize_response(&proof[commitment_size..])?;
+        let serialized_response = protocol.serialize_response(&response);
+
+        // Proof size check
+        if proof.len() != commitment_size + serialized_response.len() {
+            return Err(Error::VerificationFailure);
+        }
+
+        let mut hash_state = self.hash_state.clone();
+        let challenge = Self::statement_challenge(protocol, &mut hash_state, &commitment);
+        protocol.verifier(&commitment, &challenge, &response)?;
+        hash_state.prover_message(&serialized_response);
+
+        self.hash_state = hash_state;
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+//! # Signature ciphersuites
+//!
+//! This module holds the plumbing shared by the ciphersuites producing standard Schnorr signatures,
+//! the `bip340` and `ed25519` modules, each behind the feature of the same name.
+//!
+//! - [`SignatureCodec`] records the message and the prover messages of a signature,
+//!   and hashes them in the order of the signature scheme when deriving a challenge.
+//! - [`SignatureNonceRng`] outputs the nonce seed of a signature, followed by a hash chain.
+//! - [`Nizk::from_public_key`], [`Nizk::from_secret_key`] and [`Nizk::from_protocol`]
+//!   create the [`Nizk`] producing and verifying the signatures of a public key.
+
+use crate::codec::{ChallengeSpace, Codec};
+use crate::errors::Error;
+use crate::fiat_shamir::Nizk;
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+use digest::{Digest, Output};
+use zeroize::Zeroize;
+
+#[cfg(feature = "std")]
+use rand::{CryptoRng, RngCore};
+#[cfg(not(feature = "std"))]
+use rand_core::{CryptoRng, RngCore};
+
+/// Size in bytes of encoded public keys and nonces.
+pub(crate) const ENCODING_SIZE: usize = 32;
+
+/// A Schnorr protocol whose proofs are the signatures of a standard scheme.
+///
+/// This trait is sealed: it is implemented by `Bip340Schnorr` and `Ed25519Schnorr`.
+pub trait SignatureScheme: sealed::Scheme {}
+
+pub(crate) mod sealed {
+    use super::ENCODING_SIZE;
+    use crate::errors::InvalidInstance;
+    use crate::traits::SigmaProtocol;
+    use digest::{Digest, Output};
+
+    /// Describes the hashes and keys of a [`SignatureScheme`](super::SignatureScheme).
+    pub trait Scheme: SigmaProtocol + Sized {
+        /// The secret key of a signer.
+        type SecretKey: ?Sized;
+
+        /// The hash function of the scheme.
+        type Hash: Digest;
+
+        /// The protocol identifier of the instances.
+        const PROTOCOL_IDENTIFIER: &'static [u8];
+
+        /// The domain of the hash deriving challenges.
+        const CHALLENGE_DOMAIN: &'static [u8];
+
+        /// The domain of the hash squeezing bytes, beyond challenges.
+        const SQUEEZE_DOMAIN: &'static [u8];
+
+        /// The domain of the hash binding an IV outside of signatures.
+        const IV_DOMAIN: &'static [u8];
+
+        /// Returns the hash function with `domain` absorbed.
+        fn hasher(domain: &[u8]) -> Self::Hash;
+
+        /// Maps the hash deriving a challenge to the challenge.
+        fn challenge_from_hash(hash: Output<Self::Hash>) -> Self::Challenge;
+
+        /// Creates the instance of an encoded public key.
+        fn from_public_key(public_key: &[u8; ENCODING_SIZE]) -> Result<Self, InvalidInstance>;
+
+        /// Creates the instance of the public key of `secret_key`, and the corresponding witness.
+        fn from_secret_key(
+            secret_key: &Self::SecretKey,
+        ) -> Result<(Self, Self::Witness), InvalidInstance>;
+    }
+}
+
+/// The codec of the signatures of the scheme `S`.
+///
+/// The codec records the prover messages, and derives the challenge as the hash of
+/// `R || P || m || prover messages`, in the domain of the challenges of `S`, where:
+/// - `R` is the last commitment absorbed, the last 32 bytes of the prover messages,
+/// - `P` is the encoded public key, the instance label,
+/// - `m` is the message absorbed with [`Codec::absorb_message`],
+/// - the prover messages are the ones before the commitment.
+///
+/// Later challenges and squeezed bytes depend on the previous ones.
+///
+/// The IV of a signature is the public key, followed by zeros.
+/// Otherwise, as with a session identifier, the public key is replaced by a hash
+/// of the protocol identifier, the session identifier and the instance label:
+/// the proofs remain sound, but are not signatures.
+pub struct SignatureCodec<S> {
+    public_key: [u8; ENCODING_SIZE],
+    message: Vec<u8>,
+    prover_messages: Vec<u8>,
+    _scheme: PhantomData<S>,
+}
+
+impl<S> Clone for SignatureCodec<S> {
+    fn clone(&self) -> Self {
+        Self {
+            public_key: self.public_key,
+            message: self.message.clone(),
+            prover_messages: self.prover_messages.clone(),
+            _scheme: PhantomData,
+        }
+    }
+}
+
+impl<S> fmt::Debug for SignatureCodec<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignatureCodec")
+            .field("public_key", &self.public_key)
+            .field("message", &self.message)
+            .field("prover_messages", &self.prover_messages)
+            .finish()
+    }
+}
+
+impl<S: SignatureScheme> Codec for SignatureCodec<S> {
+    type Challenge = S::Challenge;
+
+    /// Returns the IV of the signatures of the public key of an instance of `S`
+    /// without a session identifier, and the IV of sound proofs which are not signatures otherwise.
+    fn iv(
+        protocol_identifier: &[u8],
+        session_identifier: &[u8],
+        instance_label: &[u8],
+    ) -> [u8; 64] {
+        let mut iv = [0u8; 64];
+        if protocol_identifier == S::PROTOCOL_IDENTIFIER
+            && session_identifier.is_empty()
+            && instance_label.len() == ENCODING_SIZE
+        {
+            iv[..ENCODING_SIZE].copy_from_slice(instance_label);
+        } else {
+            let mut hasher = S::hasher(S::IV_DOMAIN);
+            for field in [protocol_identifier, session_identifier, instance_label] {
+                Digest::update(&mut hasher, (field.len() as u64).to_be_bytes());
+                Digest::update(&mut hasher, field);
+            }
+            iv[..ENCODING_SIZE].copy_from_slice(&hasher.finalize()[..ENCODING_SIZE]);
+        }
+        iv
+    }
+
+    fn from_iv(iv: [u8; 64]) -> Self {
+        let mut public_key = [0u8; ENCODING_SIZE];
+        public_key.copy_from_slice(&iv[..ENCODING_SIZE]);
+        Self {
+            public_key,
+            message: Vec::new(),
+            prover_messages: Vec::new(),
+            _scheme: PhantomData,
+        }
+    }
+
+    fn prover_message(&mut self, data: &[u8]) {
+        self.prover_messages.extend_from_slice(data);
+    }
+
+    /// Records the message `m` of the signature.
+    fn absorb_message(&mut self, message: &[u8]) {
+        self.message.extend_from_slice(message);
+    }
+
+    fn verifier_challenge(&mut self) -> Self::Challenge {
+        let split = self.prover_messages.len().saturating_sub(ENCODING_SIZE);
+        let (prover_messages, nonce) = self.prover_messages.split_at(split);
+        let hash = S::hasher(S::CHALLENGE_DOMAIN)
+            .chain_update(nonce)
+            .chain_update(self.public_key)
+            .chain_update(&self.message)
+            .chain_update(prover_messages)
+            .finalize();
+        self.prover_messages.extend_from_slice(&hash);
+        S::challenge_from_hash(hash)
+    }
+
+    /// Signature challenges are full scalars: the signature schemes always use [`ChallengeSpace::Full`].
+    fn verifier_challenge_in(&mut self, _challenge_space: ChallengeSpace) -> Self::Challenge {
+        self.verifier_challenge()
+    }
+
+    fn squeeze_bytes(&mut self, length: usize) -> Vec<u8> {
+        let mut output = Vec::with_capacity(length);
+        let mut counter = 0u64;
+        while output.len() < length {
+            let block = S::hasher(S::SQUEEZE_DOMAIN)
+                .chain_update(self.public_key)
+                .chain_update(&self.message)
+                .chain_update(&self.prover_messages)
+                .chain_update(counter.to_be_bytes())
+                .finalize();
+            output.extend_from_slice(&block);
+            counter += 1;
+        }
+        output.truncate(length);
+        self.prover_messages.extend_from_slice(&output);
+        output
+    }
+}
+
+impl<S> Nizk<S, SignatureCodec<S>>
+where
+    S: SignatureScheme,
+    S::Challenge: PartialEq,
+{
+    /// Creates the [`Nizk`] producing and verifying the signatures of an encoded public key.
+    ///
+    /// # Errors
+    /// - [`Error::InvalidInstanceWitnessPair`] if the public key is not a valid key of the scheme.
+    pub fn from_public_key(public_key: &[u8; ENCODING_SIZE]) -> Result<Self, Error> {
+        Ok(Self::from_protocol(S::from_public_key(public_key)?))
+    }
+
+    /// Creates the [`Nizk`] of the public key of `secret_key`, and the corresponding witness.
+    ///
+    /// # Errors
+    /// - [`Error::InvalidInstanceWitnessPair`] if the secret key has no public key.
+    pub fn from_secret_key(secret_key: &S::SecretKey) -> Result<(Self, S::Witness), Error> {
+        let (protocol, witness) = S::from_secret_key(secret_key)?;
+        Ok((Self::from_protocol(protocol), witness))
+    }
+
+    /// Creates the [`Nizk`] producing and verifying the signatures of the public key of `protocol`.
+    pub fn from_protocol(protocol: S) -> Self {
+        Self::new(b"", protocol)
+    }
+}
+
+/// The nonce generator of the signatures of the scheme `S`.
+///
+/// The first bytes of output are a seed computed by the scheme from the secret key and the message,
+/// and further bytes, which are only read if the nonce is zero, are hashes of the previous block.
+pub struct SignatureNonceRng<S: SignatureScheme> {
+    block: Output<S::Hash>,
+    index: usize,
+}
+
+impl<S: SignatureScheme> SignatureNonceRng<S> {
+    /// Outputs `seed`, followed by its successive hashes.
+    pub(crate) fn from_seed(seed: Output<S::Hash>) -> Self {
+        Self {
+            block: seed,
+            index: 0,
+        }
+    }
+}
+
+impl<S: SignatureScheme> RngCore for SignatureNonceRng<S> {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            if self.index == self.block.len() {
+                self.block = S::Hash::digest(&self.block);
+                self.index = 0;
+            }
+            *byte = self.block[self.index];
+            self.index += 1;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl<S: SignatureScheme> CryptoRng for SignatureNonceRng<S> {}
+
+impl<S: SignatureScheme> Drop for SignatureNonceRng<S> {
+    fn drop(&mut self) {
+        self.block.as_mut_slice().zeroize();
+    }
+}
@@ -1,5 +1,20 @@
+mod regression;
 mod spec;
 
+mod test_challenge_derivation;
+mod test_challenge_space;
+mod test_codec;
 mod test_composition;
+mod test_fiat_shamir;
+mod test_fischlin;
+mod test_interactive;
+mod test_iv;
+mod test_nonce;
+mod test_policy;
 mod test_relations;
+#[cfg(feature = "serde")]
+mod test_serde;
+mod test_session;
+mod test_snapshot;
 mod test_validation_criteria;
+mod test_zeroize;
@@ -0,0 +1,7 @@
+//! Regression vectors generated by this crate, rather than taken from the specification.
+//!
+//! They pin the encodings and proofs of features the specification does not cover yet,
+//! so that changes to them are deliberate. Unlike the vectors of [`super::spec`],
+//! they do not check interoperability with other implementations.
+
+mod test_composed_vectors;
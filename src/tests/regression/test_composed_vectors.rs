@@ -0,0 +1,117 @@
+use bls12_381::{G1Projective as G, Scalar};
+use ff::Field;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::codec::KeccakByteSchnorrCodec;
+use crate::composition::{ComposedRelation, ComposedWitness};
+use crate::fiat_shamir::Nizk;
+use crate::tests::spec::rng::TestDRNG;
+use crate::tests::test_relations::*;
+use crate::traits::SigmaProtocol;
+
+type ComposedNizk = Nizk<ComposedRelation<G>, KeccakByteSchnorrCodec<G>>;
+
+/// A regression vector of a composed proof, generated by this crate.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TestVector {
+    ciphersuite: String,
+    session_id: String,
+    statement: String,
+    protocol_id: String,
+    witness: String,
+    #[serde(rename = "IV")]
+    iv: String,
+    proof: String,
+}
+
+fn load_test_vectors() -> BTreeMap<String, TestVector> {
+    let json_data = include_str!("./vectors/composedSigmaProtocols.json");
+    serde_json::from_str(json_data).expect("Failed to parse test vectors JSON")
+}
+
+/// A witness of `length` zero scalars, which is not valid for any of the test relations.
+fn invalid_witness(length: usize) -> Vec<Scalar> {
+    vec![Scalar::ZERO; length]
+}
+
+/// Builds the compositions of the test vectors, from relations sampled with a seeded generator.
+fn compositions() -> Vec<(&'static str, ComposedRelation<G>, ComposedWitness<G>)> {
+    let mut rng = TestDRNG::new(b"composed_relations_seed");
+    let (dlog, dlog_witness) = discrete_logarithm::<G, _>(&mut rng);
+    let (dleq, dleq_witness) = dleq::<G, _>(&mut rng);
+    let (pedersen, pedersen_witness) = pedersen_commitment::<G, _>(&mut rng);
+    let (bbs, bbs_witness) = bbs_blind_commitment::<G, _>(&mut rng);
+
+    vec![
+        (
+            "and",
+            ComposedRelation::and([dleq.clone(), pedersen.clone()]),
+            ComposedWitness::and([dleq_witness.clone(), pedersen_witness.clone()]),
+        ),
+        (
+            "or",
+            ComposedRelation::or([dleq.clone(), pedersen.clone()]),
+            ComposedWitness::or([
+                invalid_witness(dleq_witness.len()),
+                pedersen_witness.clone(),
+            ]),
+        ),
+        (
+            "threshold",
+            ComposedRelation::threshold(2, [dlog.clone(), dleq.clone(), pedersen.clone()]),
+            ComposedWitness::threshold([
+                dlog_witness.clone(),
+                invalid_witness(dleq_witness.len()),
+                pedersen_witness.clone(),
+            ]),
+        ),
+        (
+            "nested",
+            ComposedRelation::and([
+                ComposedRelation::or([dlog, dleq]),
+                ComposedRelation::threshold(1, [pedersen, bbs]),
+            ]),
+            ComposedWitness::and([
+                ComposedWitness::or([dlog_witness, invalid_witness(dleq_witness.len())]),
+                ComposedWitness::threshold([invalid_witness(pedersen_witness.len()), bbs_witness]),
+            ]),
+        ),
+    ]
+}
+
+#[test]
+fn test_composed_vectors() {
+    let vectors = load_test_vectors();
+
+    for (name, relation, witness) in compositions() {
+        let vector = &vectors[name];
+        assert_eq!(vector.ciphersuite, "sigma/OWKeccak1600+Bls12381");
+        let session_id = hex::decode(&vector.session_id).unwrap();
+
+        assert_eq!(
+            hex::encode(relation.instance_label()),
+            vector.statement,
+            "wrong instance label for {name}"
+        );
+        assert_eq!(
+            hex::encode(relation.protocol_identifier()),
+            vector.protocol_id,
+            "wrong protocol identifier for {name}"
+        );
+        assert_eq!(
+            hex::encode(relation.serialize_witness(&witness).unwrap()),
+            vector.witness,
+            "wrong witness for {name}"
+        );
+
+        let nizk = ComposedNizk::new(&session_id, relation);
+        assert_eq!(hex::encode(nizk.iv()), vector.iv, "wrong IV for {name}");
+
+        let mut proof_rng = TestDRNG::new(b"proof_generation_seed");
+        let proof = nizk.prove_batchable(&witness, &mut proof_rng).unwrap();
+        assert_eq!(hex::encode(&proof), vector.proof, "wrong proof for {name}");
+        assert!(nizk.verify_batchable(&proof).is_ok());
+    }
+}
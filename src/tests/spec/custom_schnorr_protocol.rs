@@ -2,7 +2,7 @@ use group::prime::PrimeGroup;
 use rand::{CryptoRng, Rng};
 
 use crate::errors::Error;
-use crate::linear_relation::{CanonicalLinearRelation, LinearRelation};
+use crate::linear_relation::{CanonicalLinearRelation, LinearRelation, SchnorrProverState};
 use crate::tests::spec::random::SRandom;
 use crate::traits::{SigmaProtocol, SigmaProtocolSimulator};
 
@@ -40,7 +40,7 @@ impl<G: SRandom + PrimeGroup> SigmaProtocol for DeterministicSchnorrProof<G> {
             nonces.push(<G as SRandom>::random_scalar_elt(rng));
         }
         let commitment = self.0.evaluate(&nonces);
-        let prover_state = (nonces.to_vec(), witness.to_vec());
+        let prover_state = SchnorrProverState::new(nonces, witness.to_vec());
         Ok((commitment, prover_state))
     }
 
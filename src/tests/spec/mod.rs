@@ -1,7 +1,11 @@
 mod bls12_381;
-mod custom_schnorr_protocol;
+pub mod custom_schnorr_protocol;
 mod random;
 pub mod rng;
 
+#[cfg(feature = "bip340")]
+mod test_bip340;
 mod test_duplex_sponge;
+#[cfg(feature = "ed25519")]
+mod test_ed25519;
 mod test_vectors;
@@ -0,0 +1,124 @@
+use ff::PrimeField;
+use k256::{FieldBytes, Scalar};
+use serde::Deserialize;
+
+use crate::bip340::{Bip340Nizk, Bip340NonceRng, Bip340Schnorr};
+
+/// A test vector of BIP-340, from `bip-0340/test-vectors.csv`.
+#[derive(Debug, Deserialize)]
+struct TestVector {
+    index: usize,
+    secret_key: String,
+    public_key: String,
+    aux_rand: String,
+    message: String,
+    signature: String,
+    verification_result: bool,
+    comment: String,
+}
+
+fn load_test_vectors() -> Vec<TestVector> {
+    let json_data = include_str!("./vectors/bip340Vectors.json");
+    serde_json::from_str(json_data).expect("Failed to parse test vectors JSON")
+}
+
+#[test]
+fn test_bip340_sign_vectors() {
+    for vector in load_test_vectors() {
+        if vector.secret_key.is_empty() {
+            continue;
+        }
+        let secret_key_bytes: [u8; 32] =
+            hex::decode(&vector.secret_key).unwrap().try_into().unwrap();
+        let secret_key = Scalar::from_repr(FieldBytes::from(secret_key_bytes)).unwrap();
+        let aux_rand: [u8; 32] = hex::decode(&vector.aux_rand).unwrap().try_into().unwrap();
+        let message = hex::decode(&vector.message).unwrap();
+
+        let (nizk, witness) = Bip340Nizk::from_secret_key(&secret_key).unwrap();
+        assert_eq!(
+            hex::encode_upper(nizk.interactive_proof.public_key()),
+            vector.public_key,
+            "wrong public key for index {}",
+            vector.index
+        );
+
+        let mut nonce_rng = Bip340NonceRng::new(&secret_key, &message, &aux_rand);
+        let signature = nizk
+            .prove_compact_with_message(&witness, &message, &mut nonce_rng)
+            .unwrap();
+        assert_eq!(
+            hex::encode_upper(signature),
+            vector.signature,
+            "wrong signature for index {} ({})",
+            vector.index,
+            vector.comment
+        );
+    }
+}
+
+#[test]
+fn test_bip340_verify_vectors() {
+    for vector in load_test_vectors() {
+        let public_key: [u8; 32] = hex::decode(&vector.public_key).unwrap().try_into().unwrap();
+        let message = hex::decode(&vector.message).unwrap();
+        let signature = hex::decode(&vector.signature).unwrap();
+
+        let valid = Bip340Nizk::from_public_key(&public_key).is_ok_and(|nizk| {
+            nizk.verify_compact_with_message(&signature, &message)
+                .is_ok()
+        });
+        assert_eq!(
+            valid, vector.verification_result,
+            "incorrect validation for index {} ({})",
+            vector.index, vector.comment
+        );
+    }
+}
+
+#[test]
+fn test_bip340_proofs() {
+    let mut rng = rand::thread_rng();
+    let (nizk, witness) = Bip340Nizk::from_secret_key(&Scalar::from(0x0340u64)).unwrap();
+
+    // Signatures with fresh randomness, and without a message.
+    let signature = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_compact(&signature).is_ok());
+    assert!(nizk.verify_compact_with_message(&signature, b"").is_ok());
+
+    // Compact and batchable proofs are both signatures.
+    let signature = nizk
+        .prove_compact_with_message(&witness, b"message", &mut rng)
+        .unwrap();
+    assert_eq!(signature.len(), 64);
+    assert!(nizk
+        .verify_batchable_with_message(&signature, b"message")
+        .is_ok());
+    assert!(nizk
+        .verify_compact_with_message(&signature, b"other")
+        .is_err());
+
+    // The witness must be the secret key of the public key.
+    let (_, other_witness) = Bip340Schnorr::from_secret_key(&Scalar::from(7u64)).unwrap();
+    assert!(nizk.prove_batchable(&other_witness, &mut rng).is_err());
+}
+
+#[test]
+fn test_bip340_session_identifier() {
+    // Proofs bound to a session are sound, but are not signatures.
+    let mut rng = rand::thread_rng();
+    let (protocol, witness) = Bip340Schnorr::from_secret_key(&Scalar::from(0x0340u64)).unwrap();
+    let nizk = Bip340Nizk::new(b"session", protocol.clone());
+    let proof = nizk
+        .prove_compact_with_message(&witness, b"message", &mut rng)
+        .unwrap();
+    assert!(nizk.verify_compact_with_message(&proof, b"message").is_ok());
+    assert!(Bip340Nizk::new(b"other", protocol.clone())
+        .verify_compact_with_message(&proof, b"message")
+        .is_err());
+    assert!(Bip340Nizk::from_protocol(protocol)
+        .verify_compact_with_message(&proof, b"message")
+        .is_err());
+
+    // Invalid public keys are rejected with an error.
+    assert!(Bip340Nizk::from_public_key(&[0xff; 32]).is_err());
+}
@@ -1,5 +1,6 @@
 use crate::duplex_sponge::{
-    keccak::KeccakDuplexSponge, shake::ShakeDuplexSponge, DuplexSpongeInterface,
+    digest::DigestDuplexSponge, keccak::KeccakDuplexSponge, shake::ShakeDuplexSponge,
+    xof::XofDuplexSponge, DuplexSpongeInterface,
 };
 use libtest_mimic::{Arguments, Failed, Trial};
 use serde::{Deserialize, Serialize};
@@ -36,7 +37,13 @@ fn hex_decode(hex_str: &str) -> Vec<u8> {
 
 fn load_test_vectors() -> HashMap<String, TestVector> {
     let json_data = include_str!("./vectors/duplexSpongeVectors.json");
-    serde_json::from_str(json_data).expect("Failed to parse test vectors JSON")
+    let mut test_vectors: HashMap<String, TestVector> =
+        serde_json::from_str(json_data).expect("Failed to parse test vectors JSON");
+    let json_data = include_str!("./vectors/digestDuplexSpongeVectors.json");
+    let digest_test_vectors: HashMap<String, TestVector> =
+        serde_json::from_str(json_data).expect("Failed to parse test vectors JSON");
+    test_vectors.extend(digest_test_vectors);
+    test_vectors
 }
 
 fn run_test_vector(name: &str, test_vector: &TestVector) -> Result<(), Failed> {
@@ -46,6 +53,9 @@ fn run_test_vector(name: &str, test_vector: &TestVector) -> Result<(), Failed> {
     let mut sponge: Box<dyn DuplexSpongeInterface> = match test_vector.hash_function.as_str() {
         "Keccak-f[1600] overwrite mode" => Box::new(KeccakDuplexSponge::new(iv_array)),
         "SHAKE128" => Box::new(ShakeDuplexSponge::new(iv_array)),
+        "SHA-256 hash chain" => Box::new(DigestDuplexSponge::<sha2::Sha256>::new(iv_array)),
+        "SHA-512 hash chain" => Box::new(DigestDuplexSponge::<sha2::Sha512>::new(iv_array)),
+        "SHAKE256 XOF" => Box::new(XofDuplexSponge::<sha3::Shake256>::new(iv_array)),
         _ => panic!("Unknown hash function: {}", test_vector.hash_function),
     };
     let mut final_output = Vec::new();
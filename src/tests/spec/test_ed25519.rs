@@ -0,0 +1,228 @@
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::Scalar;
+use hex_literal::hex;
+use serde::Deserialize;
+use sha2::{Digest, Sha512};
+
+use crate::ed25519::{Ed25519Nizk, Ed25519NonceRng, Ed25519Schnorr};
+
+/// The order of the prime-order subgroup, in little-endian bytes.
+const GROUP_ORDER: [u8; 32] =
+    hex!("edd3f55c1a631258d69cf7a2def9de1400000000000000000000000000000010");
+
+/// The point `(0, -1)` of order 2.
+const TORSION_POINT: [u8; 32] =
+    hex!("ecffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f");
+
+/// The identity, encoded with the non-canonical Y coordinate `p + 1`.
+const NON_CANONICAL_IDENTITY: [u8; 32] =
+    hex!("eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f");
+
+/// A test vector of RFC 8032, Section 7.1.
+#[derive(Debug, Deserialize)]
+struct TestVector {
+    name: String,
+    secret_key: String,
+    public_key: String,
+    message: String,
+    signature: String,
+}
+
+fn load_test_vectors() -> Vec<TestVector> {
+    let json_data = include_str!("./vectors/ed25519Vectors.json");
+    serde_json::from_str(json_data).expect("Failed to parse test vectors JSON")
+}
+
+/// Verifies a signature as in RFC 8032.
+fn verify(public_key: &[u8; 32], message: &[u8], signature: &[u8]) -> bool {
+    Ed25519Nizk::from_public_key(public_key).is_ok_and(|nizk| {
+        nizk.verify_batchable_with_message(signature, message)
+            .is_ok()
+    })
+}
+
+/// Verifies a signature, rejecting points outside of the prime-order subgroup.
+fn verify_strict(public_key: &[u8; 32], message: &[u8], signature: &[u8]) -> bool {
+    Ed25519Schnorr::from_public_key_strict(public_key).is_ok_and(|protocol| {
+        Ed25519Nizk::from_protocol(protocol)
+            .verify_batchable_with_message(signature, message)
+            .is_ok()
+    })
+}
+
+/// Adds the point of order 2 to an encoded point.
+fn add_torsion(encoding: &[u8]) -> [u8; 32] {
+    let point = CompressedEdwardsY::from_slice(encoding)
+        .unwrap()
+        .decompress()
+        .unwrap();
+    let torsion = CompressedEdwardsY(TORSION_POINT).decompress().unwrap();
+    (point + torsion).compress().to_bytes()
+}
+
+#[test]
+fn test_ed25519_sign_vectors() {
+    for vector in load_test_vectors() {
+        let secret_key: [u8; 32] = hex::decode(&vector.secret_key).unwrap().try_into().unwrap();
+        let message = hex::decode(&vector.message).unwrap();
+
+        let (nizk, witness) = Ed25519Nizk::from_secret_key(&secret_key).unwrap();
+        assert_eq!(
+            hex::encode(nizk.interactive_proof.public_key()),
+            vector.public_key,
+            "wrong public key for {}",
+            vector.name
+        );
+
+        let mut nonce_rng = Ed25519NonceRng::new(&secret_key, &message);
+        let signature = nizk
+            .prove_batchable_with_message(&witness, &message, &mut nonce_rng)
+            .unwrap();
+        assert_eq!(
+            hex::encode(signature),
+            vector.signature,
+            "wrong signature for {}",
+            vector.name
+        );
+    }
+}
+
+#[test]
+fn test_ed25519_verify_vectors() {
+    for vector in load_test_vectors() {
+        let public_key: [u8; 32] = hex::decode(&vector.public_key).unwrap().try_into().unwrap();
+        let message = hex::decode(&vector.message).unwrap();
+        let signature = hex::decode(&vector.signature).unwrap();
+        assert!(
+            verify(&public_key, &message, &signature),
+            "valid signature rejected for {}",
+            vector.name
+        );
+        assert!(
+            verify_strict(&public_key, &message, &signature),
+            "valid signature rejected in strict mode for {}",
+            vector.name
+        );
+
+        // Signatures of other messages, or with a tampered nonce or response, are rejected.
+        let mut other_message = message.clone();
+        other_message.push(0);
+        assert!(!verify(&public_key, &other_message, &signature));
+        for index in [0, 32] {
+            let mut tampered = signature.clone();
+            tampered[index] ^= 1;
+            assert!(!verify(&public_key, &message, &tampered));
+        }
+
+        // Responses must be reduced modulo the group order.
+        let mut unreduced = signature.clone();
+        let mut carry = 0u16;
+        for (byte, order_byte) in unreduced[32..].iter_mut().zip(GROUP_ORDER) {
+            let sum = *byte as u16 + order_byte as u16 + carry;
+            *byte = sum as u8;
+            carry = sum >> 8;
+        }
+        assert!(!verify(&public_key, &message, &unreduced));
+    }
+}
+
+#[test]
+fn test_ed25519_encodings() {
+    // Non-canonical encodings are rejected.
+    assert!(Ed25519Schnorr::from_public_key(&NON_CANONICAL_IDENTITY).is_err());
+
+    // Points of small order are rejected.
+    assert!(Ed25519Schnorr::from_public_key(&TORSION_POINT).is_err());
+    assert!(Ed25519Schnorr::from_public_key_strict(&TORSION_POINT).is_err());
+
+    // Points with a small-order component are only rejected in strict mode.
+    let vector = &load_test_vectors()[0];
+    let public_key = add_torsion(&hex::decode(&vector.public_key).unwrap());
+    assert!(Ed25519Schnorr::from_public_key(&public_key).is_ok());
+    assert!(Ed25519Schnorr::from_public_key_strict(&public_key).is_err());
+}
+
+#[test]
+fn test_ed25519_mixed_order_signatures() {
+    // Signatures whose public key or nonce have a small-order component verify
+    // under the cofactored equation of RFC 8032, but not in strict mode.
+    let mut rng = rand::thread_rng();
+    let (protocol, witness) = Ed25519Schnorr::from_secret_key(&[0x25; 32]).unwrap();
+    let message = b"message";
+
+    let mixed_public_key = add_torsion(&protocol.public_key());
+    let nizk = Ed25519Nizk::from_public_key(&mixed_public_key).unwrap();
+    let signature = nizk
+        .prove_batchable_with_message(&witness, message, &mut rng)
+        .unwrap();
+    assert!(verify(&mixed_public_key, message, &signature));
+    assert!(!verify_strict(&mixed_public_key, message, &signature));
+
+    let nonce = Scalar::from_bytes_mod_order_wide(&[0x42; 64]);
+    let mixed_nonce = add_torsion(&EdwardsPoint::mul_base(&nonce).compress().to_bytes());
+    let challenge = Scalar::from_bytes_mod_order_wide(
+        &Sha512::new()
+            .chain_update(mixed_nonce)
+            .chain_update(protocol.public_key())
+            .chain_update(message)
+            .finalize()
+            .into(),
+    );
+    let mut signature = mixed_nonce.to_vec();
+    signature.extend_from_slice(&(nonce + challenge * witness[0]).to_bytes());
+    assert!(verify(&protocol.public_key(), message, &signature));
+    assert!(!verify_strict(&protocol.public_key(), message, &signature));
+}
+
+#[test]
+fn test_ed25519_proofs() {
+    let mut rng = rand::thread_rng();
+    let (nizk, witness) = Ed25519Nizk::from_secret_key(&[0x25; 32]).unwrap();
+
+    // Signatures with fresh randomness, and without a message.
+    let signature = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&signature).is_ok());
+    assert!(nizk.verify_batchable_with_message(&signature, b"").is_ok());
+
+    // Compact proofs verify, in both modes, but are not signatures.
+    let strict_nizk = Ed25519Nizk::from_protocol(
+        Ed25519Schnorr::from_public_key_strict(&nizk.interactive_proof.public_key()).unwrap(),
+    );
+    let proof = strict_nizk
+        .prove_compact_with_message(&witness, b"message", &mut rng)
+        .unwrap();
+    assert!(strict_nizk
+        .verify_compact_with_message(&proof, b"message")
+        .is_ok());
+    assert!(nizk.verify_compact_with_message(&proof, b"message").is_ok());
+    let proof = nizk
+        .prove_compact_with_message(&witness, b"message", &mut rng)
+        .unwrap();
+    assert!(nizk.verify_compact_with_message(&proof, b"message").is_ok());
+    assert!(nizk.verify_compact_with_message(&proof, b"other").is_err());
+
+    // The witness must be the secret scalar of the public key.
+    let (_, other_witness) = Ed25519Schnorr::from_secret_key(&[7; 32]).unwrap();
+    assert!(nizk.prove_batchable(&other_witness, &mut rng).is_err());
+}
+
+#[test]
+fn test_ed25519_session_identifier() {
+    // Proofs bound to a session are sound, but are not signatures.
+    let mut rng = rand::thread_rng();
+    let (protocol, witness) = Ed25519Schnorr::from_secret_key(&[0x25; 32]).unwrap();
+    let nizk = Ed25519Nizk::new(b"session", protocol.clone());
+    let proof = nizk
+        .prove_batchable_with_message(&witness, b"message", &mut rng)
+        .unwrap();
+    assert!(nizk
+        .verify_batchable_with_message(&proof, b"message")
+        .is_ok());
+    assert!(Ed25519Nizk::new(b"other", protocol.clone())
+        .verify_batchable_with_message(&proof, b"message")
+        .is_err());
+    assert!(!verify(&protocol.public_key(), b"message", &proof));
+
+    // Invalid public keys are rejected with an error.
+    assert!(Ed25519Nizk::from_public_key(&NON_CANONICAL_IDENTITY).is_err());
+}
@@ -0,0 +1,148 @@
+use bls12_381::{G1Projective as G, Scalar};
+use curve25519_dalek::ristretto::RistrettoPoint;
+use hex_literal::hex;
+use num_bigint::BigUint;
+
+use super::test_relations::*;
+use crate::codec::{
+    cardinal, hash_to_field, ByteSchnorrCodec, Codec, ExpandMessage, ExpandMsgXmd, ExpandMsgXof,
+    HashToField, KeccakDuplexSponge, RejectionSampling, ShakeDuplexSponge, UniformBytesReduction,
+    HASH_TO_FIELD_DST, HASH_TO_FIELD_SEED_SIZE,
+};
+use crate::duplex_sponge::DuplexSpongeInterface;
+use crate::fiat_shamir::Nizk;
+use crate::NonceMode;
+
+#[test]
+fn test_modular_reduction() {
+    // The default strategy reduces 48 bytes of output modulo the group order.
+    let iv = [5u8; 64];
+    let mut codec = ByteSchnorrCodec::<G, KeccakDuplexSponge>::from_iv(iv);
+    codec.prover_message(b"message");
+    let mut sponge = KeccakDuplexSponge::new(iv);
+    sponge.absorb(b"message");
+
+    let uniform_bytes = BigUint::from_bytes_be(&sponge.squeeze(48));
+    let reduced = (uniform_bytes % cardinal::<Scalar>()).to_bytes_le();
+    let mut repr = [0u8; 32];
+    repr[..reduced.len()].copy_from_slice(&reduced);
+    assert_eq!(
+        codec.verifier_challenge(),
+        Scalar::from_bytes(&repr).unwrap()
+    );
+    assert_eq!(codec.squeeze_bytes(32), sponge.squeeze(32));
+}
+
+#[test]
+fn test_uniform_bytes_reduction() {
+    type Reduction = ByteSchnorrCodec<RistrettoPoint, ShakeDuplexSponge>;
+    type FastReduction = ByteSchnorrCodec<RistrettoPoint, ShakeDuplexSponge, UniformBytesReduction>;
+
+    for i in 0..8u8 {
+        let mut codec = Reduction::from_iv([i; 64]);
+        let mut fast_codec = FastReduction::from_iv([i; 64]);
+        assert_eq!(codec.verifier_challenge(), fast_codec.verifier_challenge());
+        assert_eq!(codec.squeeze_bytes(16), fast_codec.squeeze_bytes(16));
+    }
+}
+
+#[test]
+fn test_derivations_separate_nonces() {
+    // Codecs deriving challenges differently must not reuse deterministic nonces
+    // for the same statement, session and message.
+    type Reduction = ByteSchnorrCodec<RistrettoPoint, ShakeDuplexSponge>;
+    type FastReduction = ByteSchnorrCodec<RistrettoPoint, ShakeDuplexSponge, UniformBytesReduction>;
+
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = dleq::<RistrettoPoint, _>(&mut rng);
+    let nizk = Nizk::<_, Reduction>::new(b"derivation", relation.clone())
+        .with_nonce_mode(NonceMode::Deterministic);
+    let fast_nizk = Nizk::<_, FastReduction>::new(b"derivation", relation)
+        .with_nonce_mode(NonceMode::Deterministic);
+    assert_ne!(nizk.iv(), fast_nizk.iv());
+
+    let proof = nizk
+        .prove_batchable_with_message(&witness, b"message", &mut rng)
+        .unwrap();
+    let fast_proof = fast_nizk
+        .prove_batchable_with_message(&witness, b"message", &mut rng)
+        .unwrap();
+    // The proofs start with the two commitments of the DLEQ, computed from the nonces.
+    assert_ne!(proof[..64], fast_proof[..64]);
+    assert!(fast_nizk
+        .verify_batchable_with_message(&fast_proof, b"message")
+        .is_ok());
+}
+
+#[test]
+fn test_expand_message() {
+    // Test vectors from RFC 9380, Appendix K.
+    let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+    assert_eq!(
+        ExpandMsgXmd::<sha2::Sha256>::expand_message(b"", dst, 0x20),
+        hex!("68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235")
+    );
+    assert_eq!(
+        ExpandMsgXmd::<sha2::Sha256>::expand_message(b"abc", dst, 0x20),
+        hex!("d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615")
+    );
+    assert_eq!(
+        ExpandMsgXmd::<sha2::Sha256>::expand_message(b"abc", dst, 0x80),
+        hex!(
+            "abba86a6129e366fc877aab32fc4ffc70120d8996c88aee2fe4b32d6c7b6437a"
+            "647e6c3163d40b76a73cf6a5674ef1d890f95b664ee0afa5359a5c4e07985635"
+            "bbecbac65d747d3d2da7ec2b8221b17b0ca9dc8a1ac1c07ea6a1e60583e2cb00"
+            "058e77b7b72a298425cd1b941ad4ec65e8afc50303a22c0f99b0509b4c895f40"
+        )
+    );
+
+    let dst = b"QUUX-V01-CS02-with-expander-SHAKE128";
+    assert_eq!(
+        ExpandMsgXof::<sha3::Shake128>::expand_message(b"", dst, 0x20),
+        hex!("86518c9cd86581486e9485aa74ab35ba150d1c75c88e26b7043e44e2acd735a2")
+    );
+    assert_eq!(
+        ExpandMsgXof::<sha3::Shake128>::expand_message(b"abc", dst, 0x20),
+        hex!("8696af52a4d862417c0763556073f47bc9b9ba43c99b505305cb1ec04a9ab468")
+    );
+}
+
+#[test]
+fn test_hash_to_field() {
+    type Expander = ExpandMsgXmd<sha2::Sha256>;
+    let iv = [9u8; 64];
+    let mut codec = ByteSchnorrCodec::<G, ShakeDuplexSponge, HashToField<Expander>>::from_iv(iv);
+    let mut sponge = ShakeDuplexSponge::new(iv);
+
+    let seed = sponge.squeeze(HASH_TO_FIELD_SEED_SIZE);
+    let expected: Scalar = hash_to_field::<_, Expander>(&seed, HASH_TO_FIELD_DST);
+    assert_eq!(codec.verifier_challenge(), expected);
+}
+
+#[test]
+fn test_challenge_derivations_nizk() {
+    type XmdCodec = ByteSchnorrCodec<G, ShakeDuplexSponge, HashToField<ExpandMsgXmd<sha2::Sha256>>>;
+    type XofCodec =
+        ByteSchnorrCodec<G, ShakeDuplexSponge, HashToField<ExpandMsgXof<sha3::Shake128>>>;
+    type RejectionCodec = ByteSchnorrCodec<G, ShakeDuplexSponge, RejectionSampling>;
+
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = dleq::<G, _>(&mut rng);
+
+    let nizk = Nizk::<_, XmdCodec>::new(b"derivation", relation.clone());
+    let proof = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&proof).is_ok());
+    let proof = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_compact(&proof).is_ok());
+    assert!(Nizk::<_, XofCodec>::new(b"derivation", relation.clone())
+        .verify_compact(&proof)
+        .is_err());
+
+    let nizk = Nizk::<_, XofCodec>::new(b"derivation", relation.clone());
+    let proof = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_compact(&proof).is_ok());
+
+    let nizk = Nizk::<_, RejectionCodec>::new(b"derivation", relation);
+    let proof = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_compact(&proof).is_ok());
+}
@@ -0,0 +1,158 @@
+use bls12_381::{G1Projective as G, Scalar};
+use ff::Field;
+
+use super::test_relations::*;
+use crate::codec::{ChallengeSpace, Shake128DuplexSponge, SHORT_CHALLENGE_SIZE};
+use crate::composition::{ComposedRelation, ComposedWitness};
+use crate::fiat_shamir::Nizk;
+use crate::interactive::{InteractiveProver, InteractiveVerifier};
+use crate::linear_relation::CanonicalLinearRelation;
+
+type SchnorrNizk = Nizk<CanonicalLinearRelation<G>, Shake128DuplexSponge<G>>;
+
+#[test]
+fn test_short_challenge_encoding() {
+    let mut rng = rand::thread_rng();
+    let space = ChallengeSpace::Short;
+
+    let a = space.random::<Scalar>(&mut rng);
+    let b = space.random::<Scalar>(&mut rng);
+    let bytes = space.serialize(&a);
+    assert_eq!(bytes.len(), SHORT_CHALLENGE_SIZE);
+    assert_eq!(space.deserialize::<Scalar>(&bytes), Some(a));
+    assert_eq!(space.deserialize::<Scalar>(&bytes[1..]), None);
+
+    // Combined challenges stay short, and removing a challenge undoes combining it.
+    let c = space.combine(&a, &b);
+    assert_eq!(space.deserialize::<Scalar>(&space.serialize(&c)), Some(c));
+    assert_eq!(space.remove(&c, &b), a);
+}
+
+#[test]
+fn test_short_challenges_simple() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = pedersen_commitment::<G, _>(&mut rng);
+    let full_nizk = SchnorrNizk::new(b"short", relation.clone());
+    let nizk = SchnorrNizk::new(
+        b"short",
+        relation.with_challenge_space(ChallengeSpace::Short),
+    );
+
+    let full_proof = full_nizk.prove_compact(&witness, &mut rng).unwrap();
+    let proof = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert_eq!(proof.len(), full_proof.len() - 32 + SHORT_CHALLENGE_SIZE);
+    assert!(nizk.verify_compact(&proof).is_ok());
+
+    let batchable = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&batchable).is_ok());
+
+    // The challenge space is bound to the proof.
+    let full_batchable = full_nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&full_batchable).is_err());
+    assert!(full_nizk.verify_batchable(&batchable).is_err());
+}
+
+#[test]
+fn test_short_challenges_composed() {
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<G, _>(&mut rng);
+    let (relation2, _) = pedersen_commitment::<G, _>(&mut rng);
+    let (relation3, _) = discrete_logarithm::<G, _>(&mut rng);
+
+    let instance = ComposedRelation::or([
+        ComposedRelation::from(relation1),
+        relation2.into(),
+        relation3.into(),
+    ]);
+    let witness = ComposedWitness::or([witness1, vec![Scalar::ZERO; 2], vec![Scalar::ZERO]]);
+    let full_nizk = instance.clone().into_nizk(b"short");
+    let nizk = instance
+        .with_challenge_space(ChallengeSpace::Short)
+        .into_nizk(b"short");
+
+    // The challenge and the two challenges of the OR composition are shorter.
+    let full_proof = full_nizk.prove_compact(&witness, &mut rng).unwrap();
+    let proof = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert_eq!(
+        proof.len(),
+        full_proof.len() - 3 * (32 - SHORT_CHALLENGE_SIZE)
+    );
+    assert!(nizk.verify_compact(&proof).is_ok());
+
+    let proofs = (0..3)
+        .map(|_| nizk.prove_batchable(&witness, &mut rng).unwrap())
+        .collect::<Vec<_>>();
+    let batch = proofs
+        .iter()
+        .map(|proof| (&nizk, proof.as_slice()))
+        .collect::<Vec<_>>();
+    assert!(Nizk::verify_batchable_many(&batch, &mut rng).is_ok());
+}
+
+#[test]
+fn test_short_challenges_interactive() {
+    let mut rng = rand::thread_rng();
+    let (relation1, _) = dleq::<G, _>(&mut rng);
+    let (relation2, witness2) = discrete_logarithm::<G, _>(&mut rng);
+    let instance =
+        ComposedRelation::or([relation1, relation2]).with_challenge_space(ChallengeSpace::Short);
+    let witness = ComposedWitness::or([vec![Scalar::ZERO], witness2]);
+    let mut prover = InteractiveProver::new(instance.clone());
+    let mut verifier = InteractiveVerifier::new(instance);
+
+    let commitment = prover.commit(&witness, &mut rng).unwrap();
+    let challenge = verifier.challenge(&commitment, &mut rng).unwrap();
+    // The framed challenge is a tag, a 32-bit length and the challenge.
+    assert_eq!(challenge.len(), 1 + 4 + SHORT_CHALLENGE_SIZE);
+    let response = prover.respond(&challenge).unwrap();
+    assert!(verifier.verify(&response).is_ok());
+}
+
+#[cfg(feature = "bip340")]
+#[test]
+fn test_short_challenge_encoding_big_endian_field() {
+    use ff::PrimeField;
+    use k256::Scalar;
+
+    // The representation of secp256k1 scalars is big-endian.
+    let mut rng = rand::thread_rng();
+    let space = ChallengeSpace::Short;
+
+    let a = space.random::<Scalar>(&mut rng);
+    let b = space.random::<Scalar>(&mut rng);
+    let bytes = space.serialize(&a);
+    assert_eq!(space.deserialize::<Scalar>(&bytes), Some(a));
+    assert_eq!(
+        space.serialize(&Scalar::from_u128(0x0102)),
+        0x0102u128.to_be_bytes()
+    );
+
+    let c = space.combine(&a, &b);
+    assert_eq!(space.deserialize::<Scalar>(&space.serialize(&c)), Some(c));
+    assert_eq!(space.remove(&c, &b), a);
+}
+
+#[cfg(feature = "bip340")]
+#[test]
+fn test_short_challenges_composed_big_endian_field() {
+    use k256::{ProjectivePoint, Scalar};
+
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<ProjectivePoint, _>(&mut rng);
+    let (relation2, _) = pedersen_commitment::<ProjectivePoint, _>(&mut rng);
+    let (relation3, _) = discrete_logarithm::<ProjectivePoint, _>(&mut rng);
+
+    let instance = ComposedRelation::or([
+        ComposedRelation::from(relation1),
+        relation2.into(),
+        relation3.into(),
+    ])
+    .with_challenge_space(ChallengeSpace::Short);
+    let witness = ComposedWitness::or([witness1, vec![Scalar::ZERO; 2], vec![Scalar::ZERO]]);
+    let nizk = instance.into_nizk(b"short");
+
+    let proof = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_compact(&proof).is_ok());
+    let proof = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&proof).is_ok());
+}
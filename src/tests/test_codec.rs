@@ -0,0 +1,122 @@
+use bls12_381::{G1Projective as G, Scalar};
+use ff::Field;
+use group::Group;
+
+use super::test_relations::*;
+use crate::codec::{
+    AsconByteSchnorrCodec, ByteSchnorrCodec, Codec, DigestDuplexSponge, KeccakByteSchnorrCodec,
+    PoseidonCodec, PoseidonDuplexSponge, XofDuplexSponge,
+};
+use crate::duplex_sponge::DuplexSpongeInterface;
+use crate::fiat_shamir::Nizk;
+use crate::group::serialization::{serialize_elements, serialize_scalars};
+use crate::linear_relation::CanonicalLinearRelation;
+
+type TestCodec = KeccakByteSchnorrCodec<G>;
+
+#[test]
+fn test_typed_absorb() {
+    let mut rng = rand::thread_rng();
+    let elements = [G::random(&mut rng), G::random(&mut rng)];
+    let scalars = [Scalar::random(&mut rng), Scalar::random(&mut rng)];
+
+    let mut typed = TestCodec::new(b"codec", b"typed", b"");
+    typed.absorb_elements(&elements);
+    typed.absorb_scalars::<G>(&scalars);
+
+    let mut raw = TestCodec::new(b"codec", b"typed", b"");
+    raw.prover_message(&serialize_elements(&elements));
+    raw.prover_message(&serialize_scalars::<G>(&scalars));
+
+    assert_eq!(typed.verifier_challenge(), raw.verifier_challenge());
+}
+
+#[test]
+fn test_absorb_label() {
+    let mut codec1 = TestCodec::new(b"codec", b"label", b"");
+    codec1.absorb_label(b"a");
+    codec1.prover_message(b"bc");
+
+    let mut codec2 = TestCodec::new(b"codec", b"label", b"");
+    codec2.absorb_label(b"ab");
+    codec2.prover_message(b"c");
+
+    assert_ne!(codec1.squeeze_bytes(32), codec2.squeeze_bytes(32));
+}
+
+#[test]
+fn test_squeeze_scalars() {
+    let mut codec = TestCodec::new(b"codec", b"squeeze", b"");
+    codec.prover_message(b"message");
+
+    // The challenge is the first scalar squeezed.
+    let mut challenge_codec = codec.clone();
+    let challenge = challenge_codec.verifier_challenge();
+    let scalars = codec.squeeze_scalars::<G>(3);
+    assert_eq!(scalars.len(), 3);
+    assert_eq!(scalars[0], challenge);
+    assert_ne!(scalars[0], scalars[1]);
+    assert_ne!(scalars[1], scalars[2]);
+
+    // Squeezing continues the output stream.
+    assert_eq!(challenge_codec.squeeze_scalars::<G>(2), scalars[1..]);
+    assert_ne!(codec.squeeze_bytes(16), codec.squeeze_bytes(16));
+}
+
+#[test]
+fn test_poseidon_codec_absorbs_scalars_natively() {
+    let mut rng = rand::thread_rng();
+    let scalars = [Scalar::random(&mut rng), Scalar::random(&mut rng)];
+    let iv = [7u8; 64];
+
+    let mut codec = PoseidonCodec::<G>::from_iv(iv);
+    codec.absorb_scalars::<G>(&scalars);
+    let challenges = codec.squeeze_scalars::<G>(3);
+
+    let mut sponge = PoseidonDuplexSponge::<Scalar>::new(iv);
+    sponge.absorb_elements(&scalars);
+    assert_eq!(challenges, sponge.squeeze_elements(3));
+}
+
+#[test]
+fn test_poseidon_nizk() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = dleq::<G, _>(&mut rng);
+    let nizk = Nizk::<CanonicalLinearRelation<G>, PoseidonCodec<G>>::new(b"poseidon", relation);
+
+    let batchable = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&batchable).is_ok());
+    let compact = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_compact(&compact).is_ok());
+}
+
+#[test]
+fn test_digest_and_xof_codecs() {
+    type Sha256Codec = ByteSchnorrCodec<G, DigestDuplexSponge<sha2::Sha256>>;
+    type Shake256Codec = ByteSchnorrCodec<G, XofDuplexSponge<sha3::Shake256>>;
+
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = pedersen_commitment::<G, _>(&mut rng);
+    let sha256_nizk = Nizk::<_, Sha256Codec>::new(b"digest", relation.clone());
+    let shake256_nizk = Nizk::<_, Shake256Codec>::new(b"digest", relation);
+
+    let proof = sha256_nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert!(sha256_nizk.verify_batchable(&proof).is_ok());
+    assert!(shake256_nizk.verify_batchable(&proof).is_err());
+
+    let proof = shake256_nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert!(shake256_nizk.verify_compact(&proof).is_ok());
+    assert!(sha256_nizk.verify_compact(&proof).is_err());
+}
+
+#[test]
+fn test_ascon_codec() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = discrete_logarithm::<G, _>(&mut rng);
+    let nizk = Nizk::<_, AsconByteSchnorrCodec<G>>::new(b"ascon", relation);
+
+    let proof = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&proof).is_ok());
+    let proof = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_compact(&proof).is_ok());
+}
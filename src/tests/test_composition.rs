@@ -2,7 +2,9 @@ use curve25519_dalek::ristretto::RistrettoPoint;
 use group::Group;
 
 use super::test_relations::*;
-use crate::composition::{ComposedRelation, ComposedWitness};
+use crate::composition::{ComposedRelation, ComposedResponse, ComposedWitness, WitnessTransformer};
+use crate::linear_relation::{CanonicalLinearRelation, LinearRelation, ScalarVar};
+use crate::traits::SigmaProtocol;
 
 type G = RistrettoPoint;
 
@@ -107,3 +109,392 @@ fn test_or_both_true() {
     assert!(nizk.verify_batchable(&proof_batchable_bytes).is_ok());
     assert!(nizk.verify_compact(&proof_compact_bytes).is_ok());
 }
+
+#[test]
+fn test_threshold() {
+    // Test a 2-out-of-5 threshold composition, for several sets of known witnesses.
+    let mut rng = rand::thread_rng();
+    let (relations, witnesses): (Vec<_>, Vec<_>) =
+        (0..5).map(|_| discrete_logarithm::<G, _>(&mut rng)).unzip();
+    let wrong_witnesses = witnesses
+        .iter()
+        .map(|w| {
+            (0..w.len())
+                .map(|_| <G as Group>::Scalar::random(&mut rng))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let threshold_protocol = ComposedRelation::threshold(2, relations);
+    let nizk = threshold_protocol.into_nizk(b"test_threshold");
+
+    for known in [[0, 1], [3, 4], [0, 4], [1, 3]] {
+        let witness = ComposedWitness::threshold((0..5).map(|i| {
+            if known.contains(&i) {
+                witnesses[i].clone()
+            } else {
+                wrong_witnesses[i].clone()
+            }
+        }));
+        let proof_batchable_bytes = nizk.prove_batchable(&witness, &mut rng).unwrap();
+        let proof_compact_bytes = nizk.prove_compact(&witness, &mut rng).unwrap();
+        assert!(nizk.verify_batchable(&proof_batchable_bytes).is_ok());
+        assert!(nizk.verify_compact(&proof_compact_bytes).is_ok());
+    }
+
+    // Knowing more witnesses than the threshold is fine.
+    let witness = ComposedWitness::threshold(witnesses.clone());
+    let proof = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&proof).is_ok());
+
+    // Knowing fewer witnesses than the threshold is not.
+    let witness = ComposedWitness::threshold((0..5).map(|i| {
+        if i == 2 {
+            witnesses[i].clone()
+        } else {
+            wrong_witnesses[i].clone()
+        }
+    }));
+    assert!(nizk.prove_batchable(&witness, &mut rng).is_err());
+}
+
+#[test]
+fn test_threshold_bounds() {
+    // Thresholds of 1 and n behave like OR and AND compositions.
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<G, _>(&mut rng);
+    let (relation2, witness2) = pedersen_commitment::<G, _>(&mut rng);
+    let (relation3, witness3) = discrete_logarithm::<G, _>(&mut rng);
+    let relations = [relation1, relation2, relation3];
+    let wrong_witness1 = (0..witness1.len())
+        .map(|_| <G as Group>::Scalar::random(&mut rng))
+        .collect::<Vec<_>>();
+
+    let nizk =
+        ComposedRelation::threshold(1, relations.clone()).into_nizk(b"test_threshold_bounds");
+    let witness =
+        ComposedWitness::threshold([wrong_witness1.clone(), witness2.clone(), witness3.clone()]);
+    let proof = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_compact(&proof).is_ok());
+
+    let nizk =
+        ComposedRelation::threshold(3, relations.clone()).into_nizk(b"test_threshold_bounds");
+    let witness = ComposedWitness::threshold([witness1, witness2.clone(), witness3.clone()]);
+    let proof = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&proof).is_ok());
+    let wrong_witness = ComposedWitness::threshold([wrong_witness1, witness2, witness3]);
+    assert!(nizk.prove_batchable(&wrong_witness, &mut rng).is_err());
+
+    // Thresholds of 0 or more than n are invalid.
+    for threshold in [0, 4] {
+        let nizk = ComposedRelation::threshold(threshold, relations.clone())
+            .into_nizk(b"test_threshold_bounds");
+        assert!(nizk.prove_batchable(&witness, &mut rng).is_err());
+    }
+}
+
+#[test]
+fn test_nested_threshold() {
+    // Test a threshold composition inside an AND composition, with an OR composition as a branch.
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<G, _>(&mut rng);
+    let (relation2, witness2) = pedersen_commitment::<G, _>(&mut rng);
+    let (relation3, witness3) = discrete_logarithm::<G, _>(&mut rng);
+    let (relation4, witness4) = pedersen_commitment_dleq::<G, _>(&mut rng);
+    let wrong_witness2 = (0..witness2.len())
+        .map(|_| <G as Group>::Scalar::random(&mut rng))
+        .collect::<Vec<_>>();
+
+    let threshold_protocol = ComposedRelation::threshold(
+        2,
+        [
+            ComposedRelation::or([relation1, relation2.clone()]),
+            relation2.into(),
+            relation3.into(),
+        ],
+    );
+    let instance = ComposedRelation::and([threshold_protocol, relation4.into()]);
+    let witness = ComposedWitness::and([
+        ComposedWitness::threshold([
+            ComposedWitness::or([witness1, wrong_witness2.clone()]),
+            wrong_witness2.into(),
+            witness3.into(),
+        ]),
+        witness4.into(),
+    ]);
+
+    let nizk = instance.into_nizk(b"test_nested_threshold");
+    let proof_batchable_bytes = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    let proof_compact_bytes = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&proof_batchable_bytes).is_ok());
+    assert!(nizk.verify_compact(&proof_compact_bytes).is_ok());
+
+    let batch = [(&nizk, proof_batchable_bytes.as_slice())];
+    assert!(crate::Nizk::verify_batchable_many(&batch, &mut rng).is_ok());
+
+    // Tampering with a challenge of the threshold composition invalidates the proof.
+    let mut tampered = proof_compact_bytes.clone();
+    tampered[40] ^= 1;
+    assert!(nizk.verify_compact(&tampered).is_err());
+}
+
+#[test]
+fn test_composition_structure_is_bound() {
+    // Compositions of different shapes over the same relations have different IVs,
+    // so that a proof cannot be replayed as a proof of another composition.
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<G, _>(&mut rng);
+    let (relation2, witness2) = pedersen_commitment::<G, _>(&mut rng);
+    let relations = [relation1.clone(), relation2.clone()];
+
+    let compositions = [
+        ComposedRelation::and(relations.clone()),
+        ComposedRelation::or(relations.clone()),
+        ComposedRelation::threshold(1, relations.clone()),
+        ComposedRelation::threshold(2, relations.clone()),
+        ComposedRelation::and([ComposedRelation::and([relation1]), relation2.into()]),
+    ];
+    let nizks = compositions
+        .into_iter()
+        .map(|relation| relation.into_nizk(b"test_composition_structure_is_bound"))
+        .collect::<Vec<_>>();
+    for (i, nizk) in nizks.iter().enumerate() {
+        for other in &nizks[i + 1..] {
+            assert_ne!(nizk.iv(), other.iv());
+        }
+    }
+
+    // An AND proof has the same format as a proof of the threshold of all its relations.
+    let witness = ComposedWitness::and([witness1, witness2]);
+    let proof = nizks[0].prove_batchable(&witness, &mut rng).unwrap();
+    assert!(nizks[0].verify_batchable(&proof).is_ok());
+    assert!(nizks[3].verify_batchable(&proof).is_err());
+}
+
+/// A Pedersen commitment `C = x * G + r * H` and a DLEQ `X = x * G, Y = x * K`,
+/// returned with the variable `x` of each relation and their witnesses.
+#[allow(non_snake_case, clippy::type_complexity)]
+fn pedersen_and_dleq(
+    x: <G as Group>::Scalar,
+    y: <G as Group>::Scalar,
+    rng: &mut (impl rand::RngCore + rand::CryptoRng),
+) -> (
+    [CanonicalLinearRelation<G>; 2],
+    [ScalarVar<G>; 2],
+    [Vec<<G as Group>::Scalar>; 2],
+) {
+    let r = <G as Group>::Scalar::random(&mut *rng);
+    let [H, K] = [G::random(&mut *rng), G::random(&mut *rng)];
+
+    let mut pedersen = LinearRelation::new();
+    let [var_r, var_x] = pedersen.allocate_scalars();
+    let [var_G, var_H] = pedersen.allocate_elements();
+    pedersen.allocate_eq(var_x * var_G + var_r * var_H);
+    pedersen.set_elements([(var_G, G::generator()), (var_H, H)]);
+    pedersen.compute_image(&[r, x]).unwrap();
+
+    let mut dleq = LinearRelation::new();
+    let var_y = dleq.allocate_scalar();
+    let [var_G, var_K] = dleq.allocate_elements();
+    dleq.allocate_eq(var_y * var_G);
+    dleq.allocate_eq(var_y * var_K);
+    dleq.set_elements([(var_G, G::generator()), (var_K, K)]);
+    dleq.compute_image(&[y]).unwrap();
+
+    (
+        [pedersen.canonical().unwrap(), dleq.canonical().unwrap()],
+        [var_x, var_y],
+        [vec![r, x], vec![y]],
+    )
+}
+
+#[test]
+fn test_and_with_shared_scalars() {
+    let mut rng = rand::thread_rng();
+    let x = <G as Group>::Scalar::random(&mut rng);
+    let ([pedersen, dleq], [var_x, var_y], [pedersen_witness, dleq_witness]) =
+        pedersen_and_dleq(x, x, &mut rng);
+
+    let shared = ComposedRelation::and_with_shared_scalars(
+        [pedersen.clone(), dleq.clone()],
+        [((0, var_x), (1, var_y))],
+    )
+    .unwrap();
+    let witness = ComposedWitness::and([pedersen_witness.clone(), dleq_witness.clone()]);
+    let nizk = shared.clone().into_nizk(b"test_and_with_shared_scalars");
+
+    let proof_batchable_bytes = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    let proof_compact_bytes = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&proof_batchable_bytes).is_ok());
+    assert!(nizk.verify_compact(&proof_compact_bytes).is_ok());
+    let batch = [(&nizk, proof_batchable_bytes.as_slice())];
+    assert!(crate::Nizk::verify_batchable_many(&batch, &mut rng).is_ok());
+
+    // The shared scalar has a single response, so the proof is one scalar shorter than an AND proof.
+    let and_nizk = ComposedRelation::and([pedersen.clone(), dleq.clone()])
+        .into_nizk(b"test_and_with_shared_scalars");
+    let and_proof = and_nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert_eq!(proof_compact_bytes.len() + 32, and_proof.len());
+
+    // Shared AND compositions can be nested, and simulated when their witness is unknown.
+    let (relation, relation_witness) = discrete_logarithm::<G, _>(&mut rng);
+    let or_nizk = ComposedRelation::or([shared, relation.into()]).into_nizk(b"test_or_shared");
+    let or_witness = ComposedWitness::or([
+        ComposedWitness::and([pedersen_witness, vec![<G as Group>::Scalar::ONE]]),
+        relation_witness.into(),
+    ]);
+    let or_proof = or_nizk.prove_batchable(&or_witness, &mut rng).unwrap();
+    assert!(or_nizk.verify_batchable(&or_proof).is_ok());
+}
+
+#[test]
+fn test_and_with_shared_scalars_rejects_distinct_scalars() {
+    let mut rng = rand::thread_rng();
+    let x = <G as Group>::Scalar::random(&mut rng);
+    let y = <G as Group>::Scalar::random(&mut rng);
+    let ([pedersen, dleq], [var_x, var_y], [pedersen_witness, dleq_witness]) =
+        pedersen_and_dleq(x, y, &mut rng);
+    let witness = ComposedWitness::and([pedersen_witness, dleq_witness]);
+
+    // Each relation holds, but the scalars declared equal are not, so the proof does not verify.
+    let shared = ComposedRelation::and_with_shared_scalars(
+        [pedersen.clone(), dleq.clone()],
+        [((0, var_x), (1, var_y))],
+    )
+    .unwrap();
+    let nizk = shared.into_nizk(b"test_and_with_shared_scalars_rejects_distinct_scalars");
+    let proof = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&proof).is_err());
+
+    // A proof of the AND of the relations is not a proof of the shared AND.
+    let and_nizk = ComposedRelation::and([pedersen.clone(), dleq.clone()])
+        .into_nizk(b"test_and_with_shared_scalars_rejects_distinct_scalars");
+    let and_proof = and_nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&and_proof).is_err());
+
+    // Equalities must refer to existing relations and scalars:
+    // the DLEQ has a single scalar, and `x` is the second scalar of the Pedersen commitment.
+    let missing_relation = ((2, var_y), (0, var_x));
+    let missing_scalar = ((1, var_x), (0, var_x));
+    for equality in [missing_relation, missing_scalar] {
+        assert!(ComposedRelation::and_with_shared_scalars(
+            [pedersen.clone(), dleq.clone()],
+            [equality]
+        )
+        .is_err());
+    }
+}
+
+#[test]
+fn test_and_with_shared_scalars_malformed_response() {
+    let mut rng = rand::thread_rng();
+    let x = <G as Group>::Scalar::random(&mut rng);
+    let ([pedersen, dleq], [var_x, var_y], _) = pedersen_and_dleq(x, x, &mut rng);
+    let shared =
+        ComposedRelation::and_with_shared_scalars([pedersen, dleq], [((0, var_x), (1, var_y))])
+            .unwrap();
+
+    // Responses which do not match the relations are serialized without panicking,
+    // and the bytes are not a response of the composition.
+    let malformed = [
+        ComposedResponse::And(vec![ComposedResponse::Simple(vec![x])]),
+        ComposedResponse::And(vec![
+            ComposedResponse::Simple(vec![x]),
+            ComposedResponse::And(vec![]),
+        ]),
+    ];
+    for response in malformed {
+        let bytes = shared.serialize_response(&response);
+        assert!(shared.deserialize_response(&bytes).is_err());
+    }
+}
+
+#[test]
+fn test_optimize() {
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<G, _>(&mut rng);
+    let (relation2, witness2) = pedersen_commitment::<G, _>(&mut rng);
+    let (relation3, witness3) = discrete_logarithm::<G, _>(&mut rng);
+    let (relation4, witness4) = bbs_blind_commitment::<G, _>(&mut rng);
+    let (relation5, witness5) = pedersen_commitment_dleq::<G, _>(&mut rng);
+    let wrong_witness5 = vec![<G as Group>::Scalar::ONE; witness5.len()];
+
+    // And(And(dleq, pedersen), Or(Or(dlog, bbs), pedersen_dleq), Or(And(dlog)), Threshold(1, [Or(bbs)]))
+    let relation = ComposedRelation::and([
+        ComposedRelation::and([relation1, relation2]),
+        ComposedRelation::or([
+            ComposedRelation::or([relation3.clone(), relation4.clone()]),
+            relation5.into(),
+        ]),
+        ComposedRelation::or([ComposedRelation::and([relation3])]),
+        ComposedRelation::threshold(1, [ComposedRelation::or([relation4])]),
+    ]);
+    let witness = ComposedWitness::and([
+        ComposedWitness::and([witness1, witness2]),
+        ComposedWitness::or([
+            ComposedWitness::or([witness3.clone(), witness4.clone()]),
+            wrong_witness5.into(),
+        ]),
+        ComposedWitness::or([ComposedWitness::and([witness3])]),
+        ComposedWitness::threshold([ComposedWitness::or([witness4])]),
+    ]);
+
+    let (optimized, transformer) = relation.clone().optimize();
+    // And(Simple(dleq, pedersen, dlog), Or(dlog, bbs, pedersen_dleq), Threshold(1, [bbs]))
+    let ComposedRelation::And(children) = &optimized else {
+        panic!("the optimized composition is not an AND");
+    };
+    assert!(matches!(&children[0], ComposedRelation::Simple(p) if p.image.len() == 4));
+    assert!(matches!(&children[1], ComposedRelation::Or(ps) if ps.len() == 3));
+    assert!(matches!(
+        &children[2],
+        ComposedRelation::Threshold(1, ps) if matches!(ps[..], [ComposedRelation::Simple(_)])
+    ));
+    assert_eq!(children.len(), 3);
+
+    let optimized_witness = transformer.transform(&witness).unwrap();
+    let nizk = optimized.into_nizk(b"test_optimize");
+    let proof_batchable_bytes = nizk.prove_batchable(&optimized_witness, &mut rng).unwrap();
+    let proof_compact_bytes = nizk.prove_compact(&optimized_witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&proof_batchable_bytes).is_ok());
+    assert!(nizk.verify_compact(&proof_compact_bytes).is_ok());
+
+    // The optimized composition is a different protocol.
+    let original_nizk = relation.into_nizk(b"test_optimize");
+    assert_ne!(nizk.iv(), original_nizk.iv());
+    assert!(original_nizk
+        .verify_batchable(&proof_batchable_bytes)
+        .is_err());
+
+    // Witnesses that do not have the shape of the original composition are rejected.
+    assert!(transformer
+        .transform(&ComposedWitness::and([optimized_witness]))
+        .is_err());
+}
+
+#[test]
+fn test_optimize_singletons() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = discrete_logarithm::<G, _>(&mut rng);
+
+    // Singleton ANDs and ORs reduce to their relation.
+    let nested = ComposedRelation::or([ComposedRelation::and([ComposedRelation::or([
+        relation.clone()
+    ])])]);
+    let (optimized, transformer) = nested.optimize();
+    assert!(matches!(optimized, ComposedRelation::Simple(_)));
+    assert_eq!(transformer, WitnessTransformer::Keep(vec![0, 0, 0]));
+
+    let nested_witness = ComposedWitness::or([ComposedWitness::and([ComposedWitness::<G>::or([
+        witness.clone(),
+    ])])]);
+    assert!(matches!(
+        transformer.transform(&nested_witness).unwrap(),
+        ComposedWitness::Simple(ref scalars) if *scalars == witness
+    ));
+
+    // Simple relations are left unchanged.
+    let (optimized, transformer) = ComposedRelation::from(relation.clone()).optimize();
+    assert!(matches!(optimized, ComposedRelation::Simple(p) if p.label() == relation.label()));
+    assert_eq!(transformer, WitnessTransformer::Keep(vec![]));
+}
@@ -0,0 +1,257 @@
+use bls12_381::{G1Projective as G, Scalar};
+use ff::Field;
+
+use super::test_relations::*;
+use crate::codec::Shake128DuplexSponge;
+use crate::composition::{ComposedRelation, ComposedWitness};
+use crate::errors::Error;
+use crate::fiat_shamir::{BatchableProof, CompactProof, Nizk};
+use crate::linear_relation::CanonicalLinearRelation;
+
+type SchnorrNizk = Nizk<CanonicalLinearRelation<G>, Shake128DuplexSponge<G>>;
+
+#[test]
+fn test_verify_batchable_many() {
+    let mut rng = rand::thread_rng();
+    let (dlog, dlog_witness) = discrete_logarithm::<G, _>(&mut rng);
+    let (pedersen, pedersen_witness) = pedersen_commitment::<G, _>(&mut rng);
+    let (bbs, bbs_witness) = bbs_blind_commitment::<G, _>(&mut rng);
+
+    let nizks = [
+        (SchnorrNizk::new(b"batch", dlog), dlog_witness),
+        (SchnorrNizk::new(b"batch", pedersen), pedersen_witness),
+        (SchnorrNizk::new(b"batch", bbs), bbs_witness),
+    ];
+    let proofs = nizks
+        .iter()
+        .map(|(nizk, witness)| nizk.prove_batchable(witness, &mut rng).unwrap())
+        .collect::<Vec<_>>();
+
+    let batch = nizks
+        .iter()
+        .zip(&proofs)
+        .map(|((nizk, _), proof)| (nizk, proof.as_slice()))
+        .collect::<Vec<_>>();
+    assert!(SchnorrNizk::verify_batchable_many(&batch, &mut rng).is_ok());
+    assert!(SchnorrNizk::verify_batchable_many(&[], &mut rng).is_ok());
+}
+
+#[test]
+fn test_verify_batchable_many_reports_invalid_proofs() {
+    let mut rng = rand::thread_rng();
+    let (dleq1, witness1) = dleq::<G, _>(&mut rng);
+    let (dleq2, witness2) = dleq::<G, _>(&mut rng);
+    let nizk1 = SchnorrNizk::new(b"batch", dleq1);
+    let nizk2 = SchnorrNizk::new(b"batch", dleq2);
+
+    let proof1 = nizk1.prove_batchable(&witness1, &mut rng).unwrap();
+    let proof2 = nizk2.prove_batchable(&witness2, &mut rng).unwrap();
+
+    // The second proof is well-formed but for a different instance, the last one is truncated.
+    let batch = [
+        (&nizk1, proof1.as_slice()),
+        (&nizk2, proof1.as_slice()),
+        (&nizk2, proof2.as_slice()),
+        (&nizk1, &proof1[1..]),
+    ];
+    match SchnorrNizk::verify_batchable_many(&batch, &mut rng) {
+        Err(Error::BatchVerificationFailure { indices }) => assert_eq!(indices, vec![1, 3]),
+        _ => panic!("batch verification should fail"),
+    }
+}
+
+#[test]
+fn test_verify_batchable_many_bisects() {
+    let mut rng = rand::thread_rng();
+    let instances = (0..9)
+        .map(|_| {
+            let (relation, witness) = discrete_logarithm::<G, _>(&mut rng);
+            (SchnorrNizk::new(b"batch", relation), witness)
+        })
+        .collect::<Vec<_>>();
+    let mut proofs = instances
+        .iter()
+        .map(|(nizk, witness)| nizk.prove_batchable(witness, &mut rng).unwrap())
+        .collect::<Vec<_>>();
+    // Tamper with the responses of a few proofs.
+    for i in [0, 4, 5, 8] {
+        let last = proofs[i].len() - 1;
+        proofs[i][last] ^= 1;
+    }
+
+    let batch = instances
+        .iter()
+        .zip(&proofs)
+        .map(|((nizk, _), proof)| (nizk, proof.as_slice()))
+        .collect::<Vec<_>>();
+    match SchnorrNizk::verify_batchable_many(&batch, &mut rng) {
+        Err(Error::BatchVerificationFailure { indices }) => assert_eq!(indices, vec![0, 4, 5, 8]),
+        _ => panic!("batch verification should fail"),
+    }
+}
+
+#[test]
+fn test_verify_batchable_many_with_messages() {
+    let mut rng = rand::thread_rng();
+    let (dlog, dlog_witness) = discrete_logarithm::<G, _>(&mut rng);
+    let (pedersen, pedersen_witness) = pedersen_commitment::<G, _>(&mut rng);
+    let nizk1 = SchnorrNizk::new(b"batch", dlog);
+    let nizk2 = SchnorrNizk::new(b"batch", pedersen);
+
+    let proof1 = nizk1
+        .prove_batchable_with_message(&dlog_witness, b"first", &mut rng)
+        .unwrap();
+    let proof2 = nizk2
+        .prove_batchable_with_message(&pedersen_witness, b"second", &mut rng)
+        .unwrap();
+
+    let batch = [
+        (&nizk1, proof1.as_slice(), b"first".as_slice()),
+        (&nizk2, proof2.as_slice(), b"second".as_slice()),
+    ];
+    assert!(SchnorrNizk::verify_batchable_many_with_messages(&batch, &mut rng).is_ok());
+
+    // Proofs checked against another message, or without their message, are rejected.
+    let batch = [
+        (&nizk1, proof1.as_slice(), b"first".as_slice()),
+        (&nizk2, proof2.as_slice(), b"first".as_slice()),
+    ];
+    match SchnorrNizk::verify_batchable_many_with_messages(&batch, &mut rng) {
+        Err(Error::BatchVerificationFailure { indices }) => assert_eq!(indices, vec![1]),
+        _ => panic!("batch verification should fail"),
+    }
+    let batch = [(&nizk1, proof1.as_slice())];
+    assert!(SchnorrNizk::verify_batchable_many(&batch, &mut rng).is_err());
+}
+
+#[test]
+fn test_verify_batchable_many_composed() {
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<G, _>(&mut rng);
+    let (relation2, _) = pedersen_commitment::<G, _>(&mut rng);
+    let (relation3, witness3) = discrete_logarithm::<G, _>(&mut rng);
+    let wrong_witness2 = vec![Scalar::ZERO; 2];
+
+    let instance = ComposedRelation::and([
+        ComposedRelation::or([relation1, relation2]),
+        relation3.into(),
+    ]);
+    let witness = ComposedWitness::and([
+        ComposedWitness::or([witness1, wrong_witness2]),
+        witness3.into(),
+    ]);
+    let nizk = instance.into_nizk(b"batch");
+
+    let proofs = (0..4)
+        .map(|_| nizk.prove_batchable(&witness, &mut rng).unwrap())
+        .collect::<Vec<_>>();
+    let batch = proofs
+        .iter()
+        .map(|proof| (&nizk, proof.as_slice()))
+        .collect::<Vec<_>>();
+    assert!(Nizk::verify_batchable_many(&batch, &mut rng).is_ok());
+}
+
+#[test]
+fn test_prove_with_message() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = pedersen_commitment::<G, _>(&mut rng);
+    let nizk = SchnorrNizk::new(b"signature", relation);
+
+    let batchable = nizk
+        .prove_batchable_with_message(&witness, b"message", &mut rng)
+        .unwrap();
+    let compact = nizk
+        .prove_compact_with_message(&witness, b"message", &mut rng)
+        .unwrap();
+
+    assert!(nizk
+        .verify_batchable_with_message(&batchable, b"message")
+        .is_ok());
+    assert!(nizk
+        .verify_compact_with_message(&compact, b"message")
+        .is_ok());
+
+    // The proofs are bound to the message.
+    assert!(nizk
+        .verify_batchable_with_message(&batchable, b"another message")
+        .is_err());
+    assert!(nizk
+        .verify_compact_with_message(&compact, b"another message")
+        .is_err());
+    assert!(nizk.verify_batchable(&batchable).is_err());
+    assert!(nizk.verify_compact(&compact).is_err());
+
+    // Proofs without a message do not verify as proofs on the empty message.
+    let batchable = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable_with_message(&batchable, b"").is_err());
+}
+
+#[test]
+fn test_proof_conversion() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = bbs_blind_commitment::<G, _>(&mut rng);
+    let nizk = SchnorrNizk::new(b"conversion", relation);
+
+    let batchable_bytes = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    let batchable = BatchableProof::from_bytes(&nizk, &batchable_bytes).unwrap();
+    assert_eq!(batchable.to_bytes(&nizk), batchable_bytes);
+
+    let compact = batchable.to_compact(&nizk);
+    let compact_bytes = compact.to_bytes(&nizk);
+    assert!(nizk.verify_compact(&compact_bytes).is_ok());
+    assert_eq!(
+        CompactProof::from_bytes(&nizk, &compact_bytes)
+            .unwrap()
+            .to_bytes(&nizk),
+        compact_bytes
+    );
+
+    // Converting back yields the original batchable proof.
+    let batchable_again = compact.to_batchable(&nizk).unwrap();
+    assert_eq!(batchable_again.to_bytes(&nizk), batchable_bytes);
+
+    // Trailing bytes are rejected.
+    let mut extended = batchable_bytes.clone();
+    extended.push(0);
+    assert!(BatchableProof::from_bytes(&nizk, &extended).is_err());
+}
+
+#[test]
+fn test_proof_conversion_with_message() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = discrete_logarithm::<G, _>(&mut rng);
+    let nizk = SchnorrNizk::new(b"conversion", relation);
+
+    let proof = nizk
+        .prove_batchable_with_message(&witness, b"message", &mut rng)
+        .unwrap();
+    let batchable = BatchableProof::from_bytes(&nizk, &proof).unwrap();
+
+    let compact = batchable.to_compact_with_message(&nizk, b"message");
+    assert!(nizk
+        .verify_compact_with_message(&compact.to_bytes(&nizk), b"message")
+        .is_ok());
+    let compact = batchable.to_compact(&nizk);
+    assert!(nizk
+        .verify_compact_with_message(&compact.to_bytes(&nizk), b"message")
+        .is_err());
+}
+
+#[test]
+fn test_proof_conversion_composed() {
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<G, _>(&mut rng);
+    let (relation2, _) = pedersen_commitment::<G, _>(&mut rng);
+    let instance = ComposedRelation::or([relation1, relation2]);
+    let witness = ComposedWitness::or([witness1, vec![Scalar::ZERO; 2]]);
+    let nizk = instance.into_nizk(b"conversion");
+
+    let compact_bytes = nizk.prove_compact(&witness, &mut rng).unwrap();
+    let compact = CompactProof::from_bytes(&nizk, &compact_bytes).unwrap();
+    let batchable_bytes = compact.to_batchable(&nizk).unwrap().to_bytes(&nizk);
+    assert!(nizk.verify_batchable(&batchable_bytes).is_ok());
+
+    let batchable = BatchableProof::from_bytes(&nizk, &batchable_bytes).unwrap();
+    assert_eq!(batchable.to_compact(&nizk).to_bytes(&nizk), compact_bytes);
+}
@@ -0,0 +1,90 @@
+use bls12_381::{G1Projective as G, Scalar};
+use ff::Field;
+
+use super::test_relations::*;
+use crate::codec::{KeccakDuplexSponge, ShakeDuplexSponge};
+use crate::composition::{ComposedRelation, ComposedWitness};
+use crate::fischlin::{FischlinNizk, FischlinParameters};
+
+#[test]
+fn test_fischlin_canonical_relation() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = pedersen_commitment_dleq::<G, _>(&mut rng);
+    let nizk = FischlinNizk::<_, ShakeDuplexSponge>::new(
+        b"fischlin",
+        relation.clone(),
+        FischlinParameters::default(),
+    );
+
+    let proof = nizk.prove(&witness, &mut rng).unwrap();
+    assert!(nizk.verify(&proof).is_ok());
+
+    // Every repetition contributes a challenge and a response.
+    let expected_len = 16 * (32 + 2 * 32);
+    assert_eq!(proof.len(), expected_len);
+
+    // The proof is bound to the session and the parameters.
+    let other_session =
+        FischlinNizk::<_, ShakeDuplexSponge>::new(b"other", relation.clone(), nizk.parameters);
+    assert!(other_session.verify(&proof).is_err());
+    let other_parameters = FischlinNizk::<_, ShakeDuplexSponge>::new(
+        b"fischlin",
+        relation,
+        FischlinParameters::new(16, 7).unwrap(),
+    );
+    assert!(other_parameters.verify(&proof).is_err());
+}
+
+#[test]
+fn test_fischlin_rejects_malformed_proofs() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = discrete_logarithm::<G, _>(&mut rng);
+    let parameters = FischlinParameters::new(4, 12).unwrap();
+    let nizk = FischlinNizk::<_, KeccakDuplexSponge>::new(b"fischlin", relation, parameters);
+
+    let proof = nizk.prove(&witness, &mut rng).unwrap();
+    assert!(nizk.verify(&proof).is_ok());
+
+    let mut tampered = proof.clone();
+    tampered[proof.len() - 1] ^= 1;
+    assert!(nizk.verify(&tampered).is_err());
+
+    let mut extended = proof.clone();
+    extended.push(0);
+    assert!(nizk.verify(&extended).is_err());
+    assert!(nizk.verify(&proof[..proof.len() - 1]).is_err());
+}
+
+#[test]
+fn test_fischlin_composed_relation() {
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<G, _>(&mut rng);
+    let (relation2, _) = pedersen_commitment::<G, _>(&mut rng);
+    let (relation3, witness3) = bbs_blind_commitment::<G, _>(&mut rng);
+    let instance = ComposedRelation::and([
+        ComposedRelation::or([relation1, relation2]),
+        relation3.into(),
+    ]);
+    let witness = ComposedWitness::and([
+        ComposedWitness::or([witness1, vec![Scalar::ZERO; 2]]),
+        witness3.into(),
+    ]);
+    let nizk = FischlinNizk::<_, ShakeDuplexSponge>::new(
+        b"fischlin",
+        instance,
+        FischlinParameters::new(8, 8).unwrap(),
+    );
+
+    let proof = nizk.prove(&witness, &mut rng).unwrap();
+    assert!(nizk.verify(&proof).is_ok());
+}
+
+#[test]
+fn test_fischlin_rejects_trivial_parameters() {
+    assert!(FischlinParameters::new(0, 8).is_err());
+    assert!(FischlinParameters::new(16, 0).is_err());
+    assert_eq!(
+        FischlinParameters::new(16, 8).unwrap(),
+        FischlinParameters::default()
+    );
+}
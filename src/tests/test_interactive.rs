@@ -0,0 +1,120 @@
+use bls12_381::{G1Projective as G, Scalar};
+use ff::Field;
+
+use super::test_relations::*;
+use crate::composition::{ComposedRelation, ComposedWitness};
+use crate::errors::Error;
+use crate::interactive::{InteractiveProver, InteractiveVerifier};
+
+#[test]
+fn test_interactive_session() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = pedersen_commitment::<G, _>(&mut rng);
+    let mut prover = InteractiveProver::new(relation.clone());
+    let mut verifier = InteractiveVerifier::new(relation);
+
+    let commitment = prover.commit(&witness, &mut rng).unwrap();
+    let challenge = verifier.challenge(&commitment, &mut rng).unwrap();
+    let response = prover.respond(&challenge).unwrap();
+    assert!(verifier.verify(&response).is_ok());
+}
+
+#[test]
+fn test_interactive_session_composed() {
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<G, _>(&mut rng);
+    let (relation2, _) = discrete_logarithm::<G, _>(&mut rng);
+    let instance = ComposedRelation::or([relation1, relation2]);
+    let witness = ComposedWitness::or([witness1, vec![Scalar::ZERO]]);
+    let mut prover = InteractiveProver::new(instance.clone());
+    let mut verifier = InteractiveVerifier::new(instance);
+
+    let commitment = prover.commit(&witness, &mut rng).unwrap();
+    let challenge = verifier.challenge(&commitment, &mut rng).unwrap();
+    let response = prover.respond(&challenge).unwrap();
+    assert!(verifier.verify(&response).is_ok());
+}
+
+#[test]
+fn test_interactive_session_misuse() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = discrete_logarithm::<G, _>(&mut rng);
+    let mut prover = InteractiveProver::new(relation.clone());
+    let mut verifier = InteractiveVerifier::new(relation.clone());
+
+    // Messages must follow the order commitment, challenge, response.
+    assert!(matches!(
+        prover.respond(&[]),
+        Err(Error::InvalidProtocolState)
+    ));
+    assert!(matches!(
+        verifier.verify(&[]),
+        Err(Error::InvalidProtocolState)
+    ));
+
+    let commitment = prover.commit(&witness, &mut rng).unwrap();
+    assert!(matches!(
+        prover.commit(&witness, &mut rng),
+        Err(Error::InvalidProtocolState)
+    ));
+    let challenge = verifier.challenge(&commitment, &mut rng).unwrap();
+    assert!(matches!(
+        verifier.challenge(&commitment, &mut rng),
+        Err(Error::InvalidProtocolState)
+    ));
+
+    // The prover answers a single challenge per commitment.
+    let response = prover.respond(&challenge).unwrap();
+    assert!(matches!(
+        prover.respond(&challenge),
+        Err(Error::InvalidProtocolState)
+    ));
+    assert!(verifier.verify(&response).is_ok());
+    assert!(matches!(
+        verifier.verify(&response),
+        Err(Error::InvalidProtocolState)
+    ));
+
+    // A message of the wrong type aborts the session.
+    let mut prover = InteractiveProver::new(relation);
+    let commitment = prover.commit(&witness, &mut rng).unwrap();
+    assert!(prover.respond(&commitment).is_err());
+    assert!(matches!(
+        prover.respond(&challenge),
+        Err(Error::InvalidProtocolState)
+    ));
+}
+
+#[test]
+fn test_interactive_session_rejects_bad_messages() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = discrete_logarithm::<G, _>(&mut rng);
+    let (other_relation, other_witness) = discrete_logarithm::<G, _>(&mut rng);
+
+    // A response for another instance does not verify.
+    let mut prover = InteractiveProver::new(other_relation);
+    let mut verifier = InteractiveVerifier::new(relation.clone());
+    let commitment = prover.commit(&other_witness, &mut rng).unwrap();
+    let challenge = verifier.challenge(&commitment, &mut rng).unwrap();
+    let response = prover.respond(&challenge).unwrap();
+    assert!(matches!(
+        verifier.verify(&response),
+        Err(Error::VerificationFailure)
+    ));
+
+    // Truncated and extended messages are rejected.
+    let mut prover = InteractiveProver::new(relation.clone());
+    let commitment = prover.commit(&witness, &mut rng).unwrap();
+    let mut extended = commitment.clone();
+    extended.push(0);
+    let mut verifier = InteractiveVerifier::new(relation.clone());
+    assert!(matches!(
+        verifier.challenge(&extended, &mut rng),
+        Err(Error::VerificationFailure)
+    ));
+    let mut verifier = InteractiveVerifier::new(relation);
+    assert!(matches!(
+        verifier.challenge(&commitment[..commitment.len() - 1], &mut rng),
+        Err(Error::VerificationFailure)
+    ));
+}
@@ -0,0 +1,92 @@
+use bls12_381::G1Projective as G;
+
+use super::test_relations::*;
+use crate::codec::Shake128DuplexSponge;
+use crate::errors::Error;
+use crate::fiat_shamir::Nizk;
+use crate::linear_relation::CanonicalLinearRelation;
+use crate::IvRegistry;
+
+type SchnorrNizk = Nizk<CanonicalLinearRelation<G>, Shake128DuplexSponge<G>>;
+
+#[test]
+fn test_precomputed_iv() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = pedersen_commitment::<G, _>(&mut rng);
+
+    let iv = SchnorrNizk::compute_iv(b"session", &relation);
+    let nizk = SchnorrNizk::new(b"session", relation.clone());
+    assert_eq!(nizk.iv(), iv);
+
+    // Verifiers built from the IV accept the proofs of the prover.
+    let proof = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    let verifier = SchnorrNizk::from_iv(iv, relation.clone());
+    assert!(verifier.verify_batchable(&proof).is_ok());
+    let verifier = SchnorrNizk::from_pinned_iv(iv, b"session", relation.clone()).unwrap();
+    assert!(verifier.verify_batchable(&proof).is_ok());
+
+    let compact = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert!(verifier.verify_compact(&compact).is_ok());
+}
+
+#[test]
+fn test_pinned_iv_mismatch() {
+    let mut rng = rand::thread_rng();
+    let (relation, _) = pedersen_commitment::<G, _>(&mut rng);
+    let (other_relation, other_witness) = pedersen_commitment::<G, _>(&mut rng);
+    let iv = SchnorrNizk::compute_iv(b"session", &relation);
+
+    assert!(matches!(
+        SchnorrNizk::from_pinned_iv(iv, b"session", other_relation.clone()),
+        Err(Error::IvMismatch)
+    ));
+    assert!(matches!(
+        SchnorrNizk::from_pinned_iv(iv, b"other session", relation),
+        Err(Error::IvMismatch)
+    ));
+
+    // Without pinning, a wrong IV is only detected when verifying.
+    let proof = SchnorrNizk::new(b"session", other_relation.clone())
+        .prove_batchable(&other_witness, &mut rng)
+        .unwrap();
+    let verifier = SchnorrNizk::from_iv(iv, other_relation);
+    assert!(matches!(
+        verifier.verify_batchable(&proof),
+        Err(Error::VerificationFailure)
+    ));
+}
+
+#[test]
+fn test_iv_registry() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = pedersen_commitment::<G, _>(&mut rng);
+    let (other_relation, _) = pedersen_commitment::<G, _>(&mut rng);
+
+    let mut registry = IvRegistry::new();
+    assert!(registry.is_empty());
+    registry.insert(b"pedersen", SchnorrNizk::compute_iv(b"session", &relation));
+    registry.insert(
+        b"other",
+        SchnorrNizk::compute_iv(b"session", &other_relation),
+    );
+    assert_eq!(registry.len(), 2);
+    assert_eq!(registry.get(b"missing"), None);
+
+    let bytes = registry.to_bytes();
+    let restored = IvRegistry::from_bytes(&bytes).unwrap();
+    assert_eq!(restored, registry);
+
+    let proof = SchnorrNizk::new(b"session", relation.clone())
+        .prove_batchable(&witness, &mut rng)
+        .unwrap();
+    let verifier =
+        SchnorrNizk::from_pinned_iv(restored.get(b"pedersen").unwrap(), b"session", relation)
+            .unwrap();
+    assert!(verifier.verify_batchable(&proof).is_ok());
+
+    // Truncated or extended encodings are rejected.
+    assert!(IvRegistry::from_bytes(&bytes[..bytes.len() - 1]).is_none());
+    let mut extended = bytes.clone();
+    extended.push(0);
+    assert!(IvRegistry::from_bytes(&extended).is_none());
+}
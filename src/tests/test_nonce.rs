@@ -0,0 +1,164 @@
+use bls12_381::{G1Projective as G, Scalar};
+use ff::Field;
+use rand::{CryptoRng, Error as RandError, RngCore};
+
+use super::test_relations::*;
+use crate::codec::{KeccakByteSchnorrCodec, Shake128DuplexSponge};
+use crate::composition::{ComposedRelation, ComposedWitness};
+use crate::errors::Error;
+use crate::fiat_shamir::Nizk;
+use crate::linear_relation::CanonicalLinearRelation;
+use crate::tests::spec::custom_schnorr_protocol::DeterministicSchnorrProof;
+use crate::NonceMode;
+
+type SchnorrNizk = Nizk<CanonicalLinearRelation<G>, Shake128DuplexSponge<G>>;
+
+/// A broken generator that always outputs zeros.
+struct ZeroRng;
+
+impl RngCore for ZeroRng {
+    fn next_u32(&mut self) -> u32 {
+        0
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        0
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        dest.fill(0);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), RandError> {
+        dest.fill(0);
+        Ok(())
+    }
+}
+
+impl CryptoRng for ZeroRng {}
+
+#[test]
+fn test_deterministic_nonces() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = pedersen_commitment::<G, _>(&mut rng);
+    let nizk =
+        SchnorrNizk::new(b"nonces", relation.clone()).with_nonce_mode(NonceMode::Deterministic);
+
+    // The proof does not depend on the generator.
+    let proof = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert_eq!(nizk.prove_batchable(&witness, &mut ZeroRng).unwrap(), proof);
+    assert!(nizk.verify_batchable(&proof).is_ok());
+
+    // Proofs are reproducible by another prover of the same instance.
+    let other_prover =
+        SchnorrNizk::new(b"nonces", relation.clone()).with_nonce_mode(NonceMode::Deterministic);
+    assert_eq!(
+        other_prover.prove_batchable(&witness, &mut rng).unwrap(),
+        proof
+    );
+
+    let compact = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert_eq!(nizk.prove_compact(&witness, &mut ZeroRng).unwrap(), compact);
+    assert!(nizk.verify_compact(&compact).is_ok());
+
+    // Nonces depend on the message and on the session.
+    let signature = nizk
+        .prove_batchable_with_message(&witness, b"message", &mut rng)
+        .unwrap();
+    assert!(nizk
+        .verify_batchable_with_message(&signature, b"message")
+        .is_ok());
+    let other_signature = nizk
+        .prove_batchable_with_message(&witness, b"another message", &mut rng)
+        .unwrap();
+    let empty_signature = nizk
+        .prove_batchable_with_message(&witness, b"", &mut rng)
+        .unwrap();
+    let other_session = SchnorrNizk::new(b"other nonces", relation)
+        .with_nonce_mode(NonceMode::Deterministic)
+        .prove_batchable(&witness, &mut rng)
+        .unwrap();
+
+    let commitment_size = proof.len() - 2 * 32;
+    let commitments = [
+        &proof,
+        &signature,
+        &other_signature,
+        &empty_signature,
+        &other_session,
+    ]
+    .map(|proof| &proof[..commitment_size]);
+    for (i, a) in commitments.iter().enumerate() {
+        for b in &commitments[i + 1..] {
+            assert_ne!(a, b);
+        }
+    }
+}
+
+#[test]
+fn test_hedged_nonces() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = dleq::<G, _>(&mut rng);
+    let deterministic_proof = SchnorrNizk::new(b"nonces", relation.clone())
+        .with_nonce_mode(NonceMode::Deterministic)
+        .prove_batchable(&witness, &mut rng)
+        .unwrap();
+    let nizk = SchnorrNizk::new(b"nonces", relation).with_nonce_mode(NonceMode::Hedged);
+
+    let proof1 = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    let proof2 = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    assert_ne!(proof1, proof2);
+    assert!(nizk.verify_batchable(&proof1).is_ok());
+    assert!(nizk.verify_batchable(&proof2).is_ok());
+
+    // With a broken generator, nonces still depend on the witness and the message.
+    let proof = nizk.prove_batchable(&witness, &mut ZeroRng).unwrap();
+    assert!(nizk.verify_batchable(&proof).is_ok());
+    assert_ne!(proof, deterministic_proof);
+    let signature = nizk
+        .prove_batchable_with_message(&witness, b"message", &mut ZeroRng)
+        .unwrap();
+    assert_ne!(proof[..96], signature[..96]);
+}
+
+#[test]
+fn test_deterministic_nonces_composed() {
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<G, _>(&mut rng);
+    let (relation2, _) = pedersen_commitment::<G, _>(&mut rng);
+    let (relation3, witness3) = discrete_logarithm::<G, _>(&mut rng);
+
+    let instance = ComposedRelation::and([
+        ComposedRelation::or([relation1, relation2]),
+        relation3.into(),
+    ]);
+    let witness = ComposedWitness::and([
+        ComposedWitness::or([witness1, vec![Scalar::ZERO; 2]]),
+        witness3.into(),
+    ]);
+    let nizk = instance
+        .into_nizk(b"nonces")
+        .with_nonce_mode(NonceMode::Deterministic);
+
+    // The transcripts simulated for the OR composition are deterministic too.
+    let proof = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert_eq!(nizk.prove_compact(&witness, &mut rng).unwrap(), proof);
+    assert!(nizk.verify_compact(&proof).is_ok());
+}
+
+#[test]
+fn test_unsupported_nonce_mode() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = discrete_logarithm::<G, _>(&mut rng);
+    let nizk = Nizk::<_, KeccakByteSchnorrCodec<G>>::new(
+        b"nonces",
+        DeterministicSchnorrProof::from(relation),
+    );
+    assert!(nizk.prove_batchable(&witness, &mut rng).is_ok());
+
+    let nizk = nizk.with_nonce_mode(NonceMode::Hedged);
+    assert!(matches!(
+        nizk.prove_batchable(&witness, &mut rng),
+        Err(Error::UnsupportedNonceMode)
+    ));
+}
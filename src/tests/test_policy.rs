@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use group::Group;
+
+use super::test_relations::*;
+use crate::composition::ComposedRelation;
+use crate::policy::{Policy, RelationRegistry, MAX_POLICY_DEPTH};
+
+type G = RistrettoPoint;
+
+const POLICY: &str = "and(age_over_18, or(gov_id, 2of3(ref_a, ref_b, ref_c)))";
+
+fn relation(name: &str) -> Policy {
+    Policy::Relation(name.to_string())
+}
+
+#[test]
+fn test_policy_parse() {
+    let expected = Policy::And(vec![
+        relation("age_over_18"),
+        Policy::Or(vec![
+            relation("gov_id"),
+            Policy::Threshold(
+                2,
+                vec![relation("ref_a"), relation("ref_b"), relation("ref_c")],
+            ),
+        ]),
+    ]);
+    assert_eq!(Policy::parse(POLICY).unwrap(), expected);
+    assert_eq!(
+        Policy::parse(" and ( age_over_18 ,or(gov_id,\n\t2of3 (ref_a, ref_b, ref_c) ) ) ").unwrap(),
+        expected
+    );
+    assert_eq!(Policy::parse("id.v2-beta").unwrap(), relation("id.v2-beta"));
+
+    let invalid = [
+        "",
+        "and()",
+        "and(a,",
+        "and(a b)",
+        "or(a))",
+        "xor(a, b)",
+        "0of1(a)",
+        "3of2(a, b)",
+        "2of3(a, b)",
+        "proof_of_age(a)",
+        "1a",
+        "a, b",
+    ];
+    for source in invalid {
+        assert!(Policy::parse(source).is_err(), "{source:?} was parsed");
+    }
+
+    // Deeply nested policies are rejected before exhausting the stack.
+    let nested = |depth: usize| format!("{}a{}", "and(".repeat(depth), ")".repeat(depth));
+    assert!(Policy::parse(&nested(MAX_POLICY_DEPTH)).is_ok());
+    assert!(Policy::parse(&nested(MAX_POLICY_DEPTH + 1)).is_err());
+    assert!(Policy::parse(&nested(100_000)).is_err());
+}
+
+#[test]
+fn test_policy_compile() {
+    let mut rng = rand::thread_rng();
+    let mut registry = RelationRegistry::<G>::new();
+    let mut witnesses = BTreeMap::new();
+    let relations = [
+        ("age_over_18", discrete_logarithm(&mut rng)),
+        ("gov_id", bbs_blind_commitment(&mut rng)),
+        ("ref_a", dleq(&mut rng)),
+        ("ref_b", pedersen_commitment(&mut rng)),
+        ("ref_c", pedersen_commitment_dleq(&mut rng)),
+    ];
+    for (name, (relation, witness)) in relations {
+        registry.insert(name, relation);
+        witnesses.insert(name.to_string(), witness);
+    }
+    assert_eq!(registry.len(), 5);
+
+    let policy = Policy::parse(POLICY).unwrap();
+    let (relation, builder) = policy.compile(&registry).unwrap();
+    assert!(matches!(relation, ComposedRelation::And(_)));
+    let nizk = relation.into_nizk(b"test_policy_compile");
+
+    // The prover knows the age relation and two of the references, but not the government ID.
+    let mut known = witnesses.clone();
+    known.remove("gov_id");
+    known.remove("ref_b");
+    let witness = builder.build(&known).unwrap();
+    let proof_batchable_bytes = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    let proof_compact_bytes = nizk.prove_compact(&witness, &mut rng).unwrap();
+    assert!(nizk.verify_batchable(&proof_batchable_bytes).is_ok());
+    assert!(nizk.verify_compact(&proof_compact_bytes).is_ok());
+
+    // Knowing a single reference does not satisfy the policy, nor does missing the age relation.
+    known.remove("ref_c");
+    assert!(builder.build(&known).is_err());
+    let mut known = witnesses.clone();
+    known.remove("age_over_18");
+    assert!(builder.build(&known).is_err());
+
+    // Witnesses must have the number of scalars of their relation.
+    let mut known = witnesses.clone();
+    known.insert("ref_b".to_string(), vec![<G as Group>::Scalar::ONE]);
+    assert!(builder.build(&known).is_err());
+    let mut known = witnesses.clone();
+    known
+        .get_mut("ref_b")
+        .unwrap()
+        .push(<G as Group>::Scalar::ONE);
+    assert!(builder.build(&known).is_err());
+
+    // Policies can only refer to relations of the registry.
+    let policy = Policy::parse("or(age_over_18, unknown)").unwrap();
+    assert!(policy.compile(&registry).is_err());
+}
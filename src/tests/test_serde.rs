@@ -0,0 +1,151 @@
+use bls12_381::{G1Projective as G, Scalar};
+use ff::Field;
+use group::Group;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::test_relations::*;
+use crate::composition::{ComposedRelation, ComposedWitness};
+use crate::fiat_shamir::{BatchableProof, CompactProof, Nizk};
+use crate::linear_relation::CanonicalLinearRelation;
+use crate::LinearRelation;
+
+fn json_roundtrip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    let json = serde_json::to_string(value).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+fn cbor_roundtrip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+    let mut cbor = Vec::new();
+    ciborium::into_writer(value, &mut cbor).unwrap();
+    ciborium::from_reader(cbor.as_slice()).unwrap()
+}
+
+#[test]
+fn test_serde_canonical_relation() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = bbs_blind_commitment::<G, _>(&mut rng);
+
+    for decoded in [json_roundtrip(&relation), cbor_roundtrip(&relation)] {
+        assert_eq!(decoded.label(), relation.label());
+        assert_eq!(decoded.num_scalars, relation.num_scalars);
+        assert!(bool::from(decoded.is_witness_valid(&witness)));
+    }
+}
+
+#[test]
+fn test_serde_human_readable_encoding() {
+    let mut rng = rand::thread_rng();
+    let (relation, _) = discrete_logarithm::<G, _>(&mut rng);
+    let json = serde_json::to_value(&relation).unwrap();
+
+    // Group elements are hex-encoded compressed points.
+    let generator = hex::encode(group::GroupEncoding::to_bytes(&G::generator()));
+    assert_eq!(json["group_elements"][0], generator.as_str());
+
+    // Scalars are hex-encoded in big-endian order.
+    let json = serde_json::to_value(ComposedWitness::<G>::Simple(vec![Scalar::ONE])).unwrap();
+    let one = format!("{:064x}", 1);
+    assert_eq!(json["Simple"][0], one.as_str());
+}
+
+#[test]
+fn test_serde_rejects_invalid_relations() {
+    let mut rng = rand::thread_rng();
+    let (relation, _) = discrete_logarithm::<G, _>(&mut rng);
+
+    let mut json = serde_json::to_value(&relation).unwrap();
+    json["linear_combinations"][0][0][1] = 7.into();
+    assert!(serde_json::from_value::<CanonicalLinearRelation<G>>(json).is_err());
+
+    let mut json = serde_json::to_value(&relation).unwrap();
+    json["linear_combinations"][0][0][0] = 1.into();
+    assert!(serde_json::from_value::<CanonicalLinearRelation<G>>(json).is_err());
+
+    let mut json = serde_json::to_value(&relation).unwrap();
+    json["image"][0] = "00".into();
+    assert!(serde_json::from_value::<CanonicalLinearRelation<G>>(json).is_err());
+
+    // The scalar field modulus is not a canonical scalar encoding.
+    let modulus = "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001";
+    let json = serde_json::json!({ "Simple": [modulus] });
+    assert!(serde_json::from_value::<ComposedWitness<G>>(json).is_err());
+}
+
+#[test]
+fn test_serde_linear_relation() {
+    let mut rng = rand::thread_rng();
+    let mut relation = LinearRelation::<G>::new();
+    let [var_x, var_r] = relation.allocate_scalars();
+    let [var_G, var_H] = relation.allocate_elements();
+    relation.allocate_eq((var_x + Scalar::from(3)) * var_G + var_H * var_r);
+    relation.set_elements([(var_G, G::generator()), (var_H, G::random(&mut rng))]);
+    let witness = vec![Scalar::random(&mut rng), Scalar::random(&mut rng)];
+    relation.compute_image(&witness).unwrap();
+
+    // Unassigned elements are preserved.
+    let unassigned = relation.allocate_element();
+
+    for decoded in [json_roundtrip(&relation), cbor_roundtrip(&relation)] {
+        assert!(decoded.linear_map.group_elements.get(unassigned).is_err());
+        let expected = CanonicalLinearRelation::try_from(&relation).unwrap();
+        let got = CanonicalLinearRelation::try_from(&decoded).unwrap();
+        assert_eq!(got.label(), expected.label());
+        assert!(bool::from(got.is_witness_valid(&witness)));
+    }
+
+    let mut json = serde_json::to_value(&relation).unwrap();
+    json["equations"][0]["terms"][0]["scalar"] = 2.into();
+    assert!(serde_json::from_value::<LinearRelation<G>>(json).is_err());
+}
+
+#[test]
+fn test_serde_composed_relation_and_proofs() {
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<G, _>(&mut rng);
+    let (relation2, _) = pedersen_commitment::<G, _>(&mut rng);
+    let (relation3, witness3) = discrete_logarithm::<G, _>(&mut rng);
+    let instance = ComposedRelation::and([
+        ComposedRelation::or([relation1, relation2]),
+        relation3.into(),
+    ]);
+    let witness = ComposedWitness::and([
+        ComposedWitness::or([witness1, vec![Scalar::ZERO; 2]]),
+        witness3.into(),
+    ]);
+
+    let instance = json_roundtrip(&instance);
+    let witness = cbor_roundtrip(&json_roundtrip(&witness));
+    let nizk = instance.into_nizk(b"serde");
+
+    let proof = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    let batchable = BatchableProof::from_bytes(&nizk, &proof).unwrap();
+    for decoded in [json_roundtrip(&batchable), cbor_roundtrip(&batchable)] {
+        assert_eq!(decoded.to_bytes(&nizk), proof);
+    }
+
+    let proof = nizk.prove_compact(&witness, &mut rng).unwrap();
+    let compact = CompactProof::from_bytes(&nizk, &proof).unwrap();
+    for decoded in [json_roundtrip(&compact), cbor_roundtrip(&compact)] {
+        assert_eq!(decoded.to_bytes(&nizk), proof);
+    }
+}
+
+#[test]
+fn test_serde_canonical_proofs() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = pedersen_commitment_dleq::<G, _>(&mut rng);
+    let nizk = Nizk::<_, crate::codec::Shake128DuplexSponge<G>>::new(b"serde", relation);
+
+    let proof = nizk.prove_batchable(&witness, &mut rng).unwrap();
+    let batchable = BatchableProof::from_bytes(&nizk, &proof).unwrap();
+    for decoded in [json_roundtrip(&batchable), cbor_roundtrip(&batchable)] {
+        assert_eq!(decoded.to_bytes(&nizk), proof);
+    }
+
+    let proof = nizk.prove_compact(&witness, &mut rng).unwrap();
+    let compact = CompactProof::from_bytes(&nizk, &proof).unwrap();
+    for decoded in [json_roundtrip(&compact), cbor_roundtrip(&compact)] {
+        assert_eq!(decoded.to_bytes(&nizk), proof);
+    }
+}
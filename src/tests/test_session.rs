@@ -0,0 +1,81 @@
+use bls12_381::{G1Projective as G, Scalar};
+use ff::Field;
+
+use super::test_relations::*;
+use crate::codec::Shake128DuplexSponge;
+use crate::composition::{ComposedRelation, ComposedWitness};
+use crate::session::ProofSession;
+use crate::Nizk;
+
+type Session = ProofSession<Shake128DuplexSponge<G>>;
+
+#[test]
+fn test_proof_session() {
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = discrete_logarithm::<G, _>(&mut rng);
+    let (relation2, witness2) = pedersen_commitment::<G, _>(&mut rng);
+    let (relation3, witness3) = dleq::<G, _>(&mut rng);
+
+    let mut prover = Session::new(b"session");
+    let proof1 = prover.prove(&relation1, &witness1, &mut rng).unwrap();
+    let proof2 = prover.prove(&relation2, &witness2, &mut rng).unwrap();
+    let proof3 = prover.prove(&relation3, &witness3, &mut rng).unwrap();
+
+    let mut verifier = Session::new(b"session");
+    assert!(verifier.verify(&relation1, &proof1).is_ok());
+    assert!(verifier.verify(&relation2, &proof2).is_ok());
+    assert!(verifier.verify(&relation3, &proof3).is_ok());
+
+    // Later proofs do not verify outside of their position in the sequence.
+    let mut verifier = Session::new(b"session");
+    assert!(verifier.verify(&relation2, &proof2).is_err());
+    let nizk = Nizk::<_, Shake128DuplexSponge<G>>::new(b"session", relation2);
+    assert!(nizk.verify_batchable(&proof2).is_err());
+
+    // The proofs are bound to the session identifier.
+    let mut verifier = Session::new(b"another session");
+    assert!(verifier.verify(&relation1, &proof1).is_err());
+}
+
+#[test]
+fn test_proof_session_binds_previous_proofs() {
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = discrete_logarithm::<G, _>(&mut rng);
+    let (relation2, witness2) = bbs_blind_commitment::<G, _>(&mut rng);
+
+    let mut prover = Session::new(b"session");
+    let proof1 = prover.prove(&relation1, &witness1, &mut rng).unwrap();
+    let proof2 = prover.prove(&relation2, &witness2, &mut rng).unwrap();
+
+    // Another valid first proof changes the challenge of the second one.
+    let other_proof1 = Session::new(b"session")
+        .prove(&relation1, &witness1, &mut rng)
+        .unwrap();
+    let mut verifier = Session::new(b"session");
+    assert!(verifier.verify(&relation1, &other_proof1).is_ok());
+    assert!(verifier.verify(&relation2, &proof2).is_err());
+
+    // A failed verification leaves the session unchanged.
+    let mut verifier = Session::new(b"session");
+    assert!(verifier.verify(&relation1, &proof1[1..]).is_err());
+    assert!(verifier.verify(&relation1, &proof1).is_ok());
+    assert!(verifier.verify(&relation2, &proof2).is_ok());
+}
+
+#[test]
+fn test_proof_session_composed() {
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<G, _>(&mut rng);
+    let (relation2, _) = pedersen_commitment::<G, _>(&mut rng);
+    let (relation3, witness3) = discrete_logarithm::<G, _>(&mut rng);
+    let instance = ComposedRelation::or([relation1, relation2]);
+    let witness = ComposedWitness::or([witness1, vec![Scalar::ZERO; 2]]);
+
+    let mut prover = Session::new(b"session");
+    let proof1 = prover.prove(&instance, &witness, &mut rng).unwrap();
+    let proof2 = prover.prove(&relation3, &witness3, &mut rng).unwrap();
+
+    let mut verifier = Session::new(b"session");
+    assert!(verifier.verify(&instance, &proof1).is_ok());
+    assert!(verifier.verify(&relation3, &proof2).is_ok());
+}
@@ -0,0 +1,104 @@
+use bls12_381::{G1Projective as G, Scalar};
+
+use crate::codec::{
+    AsconDuplexSponge, ByteSchnorrCodec, Codec, DigestDuplexSponge, KeccakByteSchnorrCodec,
+    KeccakDuplexSponge, PoseidonCodec, PoseidonDuplexSponge, ShakeDuplexSponge,
+    ShakeRatchetDuplexSponge,
+};
+use crate::duplex_sponge::DuplexSpongeInterface;
+use crate::errors::Error;
+
+/// Checks that a sponge resumed from a snapshot, while absorbing and while squeezing,
+/// produces the same output as the original sponge.
+fn check_snapshot_roundtrip<H: DuplexSpongeInterface>() {
+    let mut sponge = H::new([3u8; 64]);
+    sponge.absorb(b"partially absorbed transcript");
+    let mut resumed = H::import_state(&sponge.export_state().unwrap()).unwrap();
+    sponge.absorb(b"finished by another party");
+    resumed.absorb(b"finished by another party");
+    assert_eq!(sponge.squeeze(50), resumed.squeeze(50));
+
+    let mut resumed = H::import_state(&sponge.export_state().unwrap()).unwrap();
+    assert_eq!(sponge.squeeze(100), resumed.squeeze(100));
+    sponge.absorb(b"more");
+    resumed.absorb(b"more");
+    assert_eq!(sponge.squeeze(32), resumed.squeeze(32));
+}
+
+#[test]
+fn test_sponge_snapshots() {
+    check_snapshot_roundtrip::<KeccakDuplexSponge>();
+    check_snapshot_roundtrip::<ShakeDuplexSponge>();
+    check_snapshot_roundtrip::<ShakeRatchetDuplexSponge>();
+    check_snapshot_roundtrip::<AsconDuplexSponge>();
+    check_snapshot_roundtrip::<PoseidonDuplexSponge<Scalar>>();
+}
+
+#[test]
+fn test_invalid_snapshots() {
+    let mut sponge = ShakeDuplexSponge::new([3u8; 64]);
+    sponge.absorb(b"transcript");
+    let snapshot = sponge.export_state().unwrap();
+    assert!(ShakeDuplexSponge::import_state(&snapshot).is_ok());
+
+    // Truncated snapshots.
+    for length in [0, 1, snapshot.len() / 2, snapshot.len() - 1] {
+        assert!(matches!(
+            ShakeDuplexSponge::import_state(&snapshot[..length]),
+            Err(Error::InvalidSnapshot)
+        ));
+    }
+
+    // Tampered snapshots.
+    for position in [0, 10, snapshot.len() - 40, snapshot.len() - 1] {
+        let mut tampered = snapshot.clone();
+        tampered[position] ^= 1;
+        assert!(matches!(
+            ShakeDuplexSponge::import_state(&tampered),
+            Err(Error::InvalidSnapshot)
+        ));
+    }
+
+    // Snapshots of another sponge.
+    assert!(matches!(
+        KeccakDuplexSponge::import_state(&snapshot),
+        Err(Error::InvalidSnapshot)
+    ));
+    assert!(matches!(
+        AsconDuplexSponge::import_state(&snapshot),
+        Err(Error::InvalidSnapshot)
+    ));
+}
+
+#[test]
+fn test_unsupported_snapshots() {
+    let sponge = DigestDuplexSponge::<sha2::Sha256>::new([3u8; 64]);
+    assert!(matches!(
+        sponge.export_state(),
+        Err(Error::UnsupportedSnapshot)
+    ));
+
+    let codec = ByteSchnorrCodec::<G, DigestDuplexSponge<sha2::Sha256>>::from_iv([3u8; 64]);
+    assert!(matches!(
+        codec.export_state(),
+        Err(Error::UnsupportedSnapshot)
+    ));
+}
+
+#[test]
+fn test_codec_snapshots() {
+    let mut codec = KeccakByteSchnorrCodec::<G>::new(b"snapshot", b"session", b"instance");
+    codec.prover_message(b"computed by the backend");
+    let mut resumed =
+        KeccakByteSchnorrCodec::<G>::import_state(&codec.export_state().unwrap()).unwrap();
+    codec.prover_message(b"finished by the app");
+    resumed.prover_message(b"finished by the app");
+    assert_eq!(codec.verifier_challenge(), resumed.verifier_challenge());
+
+    let mut codec = PoseidonCodec::<G>::new(b"snapshot", b"session", b"instance");
+    codec.prover_message(b"computed by the backend");
+    let mut resumed = PoseidonCodec::<G>::import_state(&codec.export_state().unwrap()).unwrap();
+    codec.prover_message(b"finished by the app");
+    resumed.prover_message(b"finished by the app");
+    assert_eq!(codec.verifier_challenge(), resumed.verifier_challenge());
+}
@@ -0,0 +1,66 @@
+use bls12_381::{G1Projective as G, Scalar};
+use ff::Field;
+use zeroize::Zeroize;
+
+use super::test_relations::*;
+use crate::composition::{ComposedRelation, ComposedWitness};
+use crate::traits::sealed::TrialResponse;
+use crate::traits::SigmaProtocol;
+
+#[test]
+fn test_zeroize_prover_state() {
+    let mut rng = rand::thread_rng();
+    let (relation, witness) = pedersen_commitment::<G, _>(&mut rng);
+    let challenge = Scalar::random(&mut rng);
+
+    let (_, mut prover_state) = relation.prover_commit(&witness, &mut rng).unwrap();
+    prover_state.zeroize();
+    // Neither nonces nor witness are left to compute a response.
+    let response = relation.prover_response(prover_state, &challenge).unwrap();
+    assert!(response.is_empty());
+}
+
+#[test]
+fn test_zeroize_composed_prover_state() {
+    let mut rng = rand::thread_rng();
+    let (relation1, witness1) = dleq::<G, _>(&mut rng);
+    let (relation2, _) = discrete_logarithm::<G, _>(&mut rng);
+    let instance = ComposedRelation::or([relation1, relation2]);
+    let witness = ComposedWitness::or([witness1, vec![Scalar::ZERO]]);
+    let challenge = Scalar::random(&mut rng);
+
+    let (commitment, mut prover_state) = instance.prover_commit(&witness, &mut rng).unwrap();
+    let response = instance
+        .prover_trial_response(&prover_state, &challenge)
+        .unwrap();
+    assert!(instance
+        .verifier(&commitment, &challenge, &response)
+        .is_ok());
+
+    prover_state.zeroize();
+    let response = instance.prover_response(prover_state, &challenge).unwrap();
+    assert!(instance
+        .verifier(&commitment, &challenge, &response)
+        .is_err());
+}
+
+#[test]
+fn test_zeroize_composed_witness() {
+    let mut rng = rand::thread_rng();
+    let mut witness = ComposedWitness::<G>::and([
+        ComposedWitness::Simple(vec![Scalar::random(&mut rng); 3]),
+        ComposedWitness::or([vec![Scalar::random(&mut rng)], vec![Scalar::ONE]]),
+    ]);
+    witness.zeroize();
+
+    let ComposedWitness::And(witnesses) = &witness else {
+        panic!("zeroization should not change the structure of the witness");
+    };
+    assert!(matches!(&witnesses[0], ComposedWitness::Simple(w) if w.is_empty()));
+    let ComposedWitness::Or(branches) = &witnesses[1] else {
+        panic!("zeroization should not change the structure of the witness");
+    };
+    assert!(branches
+        .iter()
+        .all(|branch| matches!(branch, ComposedWitness::Simple(w) if w.is_empty())));
+}
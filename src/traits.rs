@@ -1,15 +1,18 @@
 //! Generic interface for 3-message Sigma protocols.
 //!
-//! This module defines the [`SigmaProtocol`] and [`SigmaProtocolSimulator`] traits,
-//! used to describe interactive zero-knowledge proofs of knowledge,
-//! such as Schnorr proofs, that follow the 3-message Sigma protocol structure.
+//! This module defines the [`SigmaProtocol`], [`SigmaProtocolSimulator`],
+//! [`SigmaProtocolBatchVerifier`] and [`SigmaProtocolTrialResponse`] traits, used to describe interactive zero-knowledge
+//! proofs of knowledge, such as Schnorr proofs, that follow the 3-message Sigma protocol structure.
 
+use crate::codec::ChallengeSpace;
 use crate::errors::Error;
 use alloc::vec::Vec;
+use group::prime::PrimeGroup;
 #[cfg(feature = "std")]
 use rand::{CryptoRng, Rng};
 #[cfg(not(feature = "std"))]
 use rand_core::{CryptoRng, RngCore as Rng};
+use zeroize::Zeroizing;
 
 /// A trait defining the behavior of a generic Sigma protocol.
 ///
@@ -101,6 +104,22 @@ pub trait SigmaProtocol {
     fn protocol_identifier(&self) -> impl AsRef<[u8]>;
 
     fn instance_label(&self) -> impl AsRef<[u8]>;
+
+    /// Returns the set the challenges of the protocol are sampled from.
+    ///
+    /// The default implementation returns [`ChallengeSpace::Full`].
+    fn challenge_space(&self) -> ChallengeSpace {
+        ChallengeSpace::Full
+    }
+
+    /// Serializes a witness to bytes, so that nonces can be derived from it.
+    ///
+    /// The bytes are overwritten with zeros when dropped.
+    /// The default implementation returns `None`: the protocol then only supports
+    /// [`NonceMode::Random`](crate::NonceMode::Random).
+    fn serialize_witness(&self, _witness: &Self::Witness) -> Option<Zeroizing<Vec<u8>>> {
+        None
+    }
 }
 
 type Transcript<P> = (
@@ -142,3 +161,59 @@ pub trait SigmaProtocolSimulator: SigmaProtocol {
         rng: &mut R,
     ) -> Result<Transcript<Self>, Error>;
 }
+
+/// A verification equation `Σ scalars[i] * bases[i] = 0`, stored as the pair `(scalars, bases)`.
+pub type VerificationEquation<G> = (Vec<<G as group::Group>::Scalar>, Vec<G>);
+
+/// A trait defining the behavior of a Sigma protocol whose verification reduces to multi-scalar multiplications.
+///
+/// A transcript is valid if and only if each of its verification equations evaluates to the identity.
+/// Taking a random linear combination of the equations of many transcripts
+/// allows to check all of them at once with a single multi-scalar multiplication.
+///
+/// ## Minimal Implementation
+/// Types implementing [`SigmaProtocolBatchVerifier`] must define:
+/// - `verification_equations`
+pub trait SigmaProtocolBatchVerifier: SigmaProtocol {
+    /// The group in which the verification equations are expressed.
+    type Group: PrimeGroup;
+
+    /// Returns the verification equations of the transcript `(commitment, challenge, response)`.
+    ///
+    /// Returns an error if the transcript is malformed, e.g. if the commitment or response lengths
+    /// do not match the instance.
+    fn verification_equations(
+        &self,
+        commitment: &Self::Commitment,
+        challenge: &Self::Challenge,
+        response: &Self::Response,
+    ) -> Result<Vec<VerificationEquation<Self::Group>>, Error>;
+}
+
+/// A trait marking the Sigma protocols whose prover can answer several challenges
+/// for the same commitment.
+///
+/// The randomized Fischlin transform (see [`FischlinNizk`](crate::fischlin::FischlinNizk))
+/// computes trial responses until one of them hashes to a value with enough leading zeros.
+/// Anywhere else, answering two challenges for the same commitment reveals the witness.
+///
+/// The trait is sealed: it is implemented by [`CanonicalLinearRelation`](crate::linear_relation::CanonicalLinearRelation)
+/// and [`ComposedRelation`](crate::composition::ComposedRelation),
+/// and trial responses cannot be computed outside of this crate.
+pub trait SigmaProtocolTrialResponse: sealed::TrialResponse {}
+
+pub(crate) mod sealed {
+    use super::SigmaProtocol;
+    use crate::errors::Error;
+
+    /// Computes trial responses for [`SigmaProtocolTrialResponse`](super::SigmaProtocolTrialResponse).
+    pub trait TrialResponse: SigmaProtocol {
+        /// Computes the response to `challenge` from the nonces and the witness held by `state`,
+        /// without consuming it.
+        fn prover_trial_response(
+            &self,
+            state: &Self::ProverState,
+            challenge: &Self::Challenge,
+        ) -> Result<Self::Response, Error>;
+    }
+}